@@ -1,5 +1,17 @@
 use crate::renderer::*;
-use bevy::{prelude::warn, render::texture::TextureFormat};
+use bevy::{
+    prelude::warn,
+    render::{
+        pipeline::{
+            BlendFactor, BlendOperation, CompareFunction, IndexFormat, PrimitiveTopology,
+            StencilOperation,
+        },
+        texture::{
+            AddressMode, FilterMode, TextureDescriptor, TextureDimension, TextureFormat,
+            TextureViewDimension,
+        },
+    },
+};
 
 pub trait WebGl2From<T> {
     fn from(val: T) -> Self;
@@ -82,3 +94,372 @@ impl WebGl2From<TextureFormat> for (u32, u32, u32) {
         }
     }
 }
+
+impl WebGl2From<AddressMode> for u32 {
+    fn from(val: AddressMode) -> Self {
+        match val {
+            AddressMode::ClampToEdge => Gl::CLAMP_TO_EDGE,
+            AddressMode::Repeat => Gl::REPEAT,
+            AddressMode::MirrorRepeat => Gl::MIRRORED_REPEAT,
+            AddressMode::ClampToBorder => {
+                warn!("ClampToBorder is unsupported, using ClampToEdge instead");
+                Gl::CLAMP_TO_EDGE
+            }
+        }
+    }
+}
+
+impl WebGl2From<FilterMode> for u32 {
+    fn from(val: FilterMode) -> Self {
+        match val {
+            FilterMode::Nearest => Gl::NEAREST,
+            FilterMode::Linear => Gl::LINEAR,
+        }
+    }
+}
+
+/// (min_filter, mipmap_filter) -> TEXTURE_MIN_FILTER
+impl WebGl2From<(FilterMode, FilterMode)> for u32 {
+    fn from(val: (FilterMode, FilterMode)) -> Self {
+        match val {
+            (FilterMode::Nearest, FilterMode::Nearest) => Gl::NEAREST_MIPMAP_NEAREST,
+            (FilterMode::Nearest, FilterMode::Linear) => Gl::NEAREST_MIPMAP_LINEAR,
+            (FilterMode::Linear, FilterMode::Nearest) => Gl::LINEAR_MIPMAP_NEAREST,
+            (FilterMode::Linear, FilterMode::Linear) => Gl::LINEAR_MIPMAP_LINEAR,
+        }
+    }
+}
+
+impl WebGl2From<CompareFunction> for u32 {
+    fn from(val: CompareFunction) -> Self {
+        match val {
+            CompareFunction::Never => Gl::NEVER,
+            CompareFunction::Less => Gl::LESS,
+            CompareFunction::Equal => Gl::EQUAL,
+            CompareFunction::LessEqual => Gl::LEQUAL,
+            CompareFunction::Greater => Gl::GREATER,
+            CompareFunction::NotEqual => Gl::NOTEQUAL,
+            CompareFunction::GreaterEqual => Gl::GEQUAL,
+            CompareFunction::Always => Gl::ALWAYS,
+        }
+    }
+}
+
+impl WebGl2From<StencilOperation> for u32 {
+    fn from(val: StencilOperation) -> Self {
+        match val {
+            StencilOperation::Keep => Gl::KEEP,
+            StencilOperation::Zero => Gl::ZERO,
+            StencilOperation::Replace => Gl::REPLACE,
+            StencilOperation::Invert => Gl::INVERT,
+            StencilOperation::IncrementClamp => Gl::INCR,
+            StencilOperation::DecrementClamp => Gl::DECR,
+            StencilOperation::IncrementWrap => Gl::INCR_WRAP,
+            StencilOperation::DecrementWrap => Gl::DECR_WRAP,
+        }
+    }
+}
+
+impl WebGl2From<BlendFactor> for u32 {
+    fn from(val: BlendFactor) -> Self {
+        match val {
+            BlendFactor::Zero => Gl::ZERO,
+            BlendFactor::One => Gl::ONE,
+            BlendFactor::Src => Gl::SRC_COLOR,
+            BlendFactor::OneMinusSrc => Gl::ONE_MINUS_SRC_COLOR,
+            BlendFactor::SrcAlpha => Gl::SRC_ALPHA,
+            BlendFactor::OneMinusSrcAlpha => Gl::ONE_MINUS_SRC_ALPHA,
+            BlendFactor::Dst => Gl::DST_COLOR,
+            BlendFactor::OneMinusDst => Gl::ONE_MINUS_DST_COLOR,
+            BlendFactor::DstAlpha => Gl::DST_ALPHA,
+            BlendFactor::OneMinusDstAlpha => Gl::ONE_MINUS_DST_ALPHA,
+            BlendFactor::SrcAlphaSaturated => Gl::SRC_ALPHA_SATURATE,
+            // CONSTANT_COLOR uses alpha of blend constant for alpha channel, as wgpu does
+            BlendFactor::Constant => Gl::CONSTANT_COLOR,
+            BlendFactor::OneMinusConstant => Gl::ONE_MINUS_CONSTANT_COLOR,
+        }
+    }
+}
+
+impl WebGl2From<BlendOperation> for u32 {
+    fn from(val: BlendOperation) -> Self {
+        match val {
+            BlendOperation::Add => Gl::FUNC_ADD,
+            BlendOperation::Subtract => Gl::FUNC_SUBTRACT,
+            BlendOperation::ReverseSubtract => Gl::FUNC_REVERSE_SUBTRACT,
+            BlendOperation::Min => Gl::MIN,
+            BlendOperation::Max => Gl::MAX,
+        }
+    }
+}
+
+impl WebGl2From<PrimitiveTopology> for u32 {
+    fn from(val: PrimitiveTopology) -> Self {
+        match val {
+            PrimitiveTopology::PointList => Gl::POINTS,
+            PrimitiveTopology::LineList => Gl::LINES,
+            PrimitiveTopology::LineStrip => Gl::LINE_STRIP,
+            PrimitiveTopology::TriangleList => Gl::TRIANGLES,
+            PrimitiveTopology::TriangleStrip => Gl::TRIANGLE_STRIP,
+        }
+    }
+}
+
+/// index type and its size in bytes
+impl WebGl2From<IndexFormat> for (u32, i32) {
+    fn from(val: IndexFormat) -> Self {
+        match val {
+            IndexFormat::Uint16 => (Gl::UNSIGNED_SHORT, 2),
+            IndexFormat::Uint32 => (Gl::UNSIGNED_INT, 4),
+        }
+    }
+}
+
+impl WebGl2From<TextureViewDimension> for u32 {
+    fn from(val: TextureViewDimension) -> Self {
+        match val {
+            TextureViewDimension::D1 => Gl::TEXTURE_2D,
+            TextureViewDimension::D2 => Gl::TEXTURE_2D,
+            TextureViewDimension::D2Array => Gl::TEXTURE_2D_ARRAY,
+            TextureViewDimension::Cube => Gl::TEXTURE_CUBE_MAP,
+            TextureViewDimension::CubeArray => {
+                warn!("CubeArray textures are unsupported, using Cube instead");
+                Gl::TEXTURE_CUBE_MAP
+            }
+            TextureViewDimension::D3 => Gl::TEXTURE_3D,
+        }
+    }
+}
+
+/// Cube map faces in layer order used by bevy / wgpu
+pub const CUBE_MAP_FACES: [u32; 6] = [
+    Gl::TEXTURE_CUBE_MAP_POSITIVE_X,
+    Gl::TEXTURE_CUBE_MAP_NEGATIVE_X,
+    Gl::TEXTURE_CUBE_MAP_POSITIVE_Y,
+    Gl::TEXTURE_CUBE_MAP_NEGATIVE_Y,
+    Gl::TEXTURE_CUBE_MAP_POSITIVE_Z,
+    Gl::TEXTURE_CUBE_MAP_NEGATIVE_Z,
+];
+
+/// View dimension of texture created from given descriptor, unless another one is
+/// requested with `WebGL2RenderResourceContext::create_texture_with_view_dimension`.
+///
+/// `TextureDescriptor` doesn't carry view dimension, so 2D textures with more than
+/// one layer are created as 2D arrays
+pub fn default_view_dimension(descriptor: &TextureDescriptor) -> TextureViewDimension {
+    match descriptor.dimension {
+        TextureDimension::D3 => TextureViewDimension::D3,
+        TextureDimension::D2 if descriptor.size.depth > 1 => TextureViewDimension::D2Array,
+        _ => TextureViewDimension::D2,
+    }
+}
+
+/// Whether texture target stores its layers / slices as a third dimension
+/// (uploaded with `texSubImage3D`, attached with `framebufferTextureLayer`)
+pub fn is_3d_target(target: u32) -> bool {
+    matches!(target, Gl::TEXTURE_2D_ARRAY | Gl::TEXTURE_3D)
+}
+
+/// Target of single 2D image of texture: cube map face for given layer,
+/// or texture target itself
+pub fn image_target(target: u32, layer: u32) -> u32 {
+    match target {
+        Gl::TEXTURE_CUBE_MAP => CUBE_MAP_FACES[layer as usize],
+        _ => target,
+    }
+}
+
+/// Formats which are both color-renderable and filterable in WebGL2 core,
+/// required by `generateMipmap`
+pub fn supports_mipmap_generation(format: TextureFormat) -> bool {
+    matches!(
+        format,
+        TextureFormat::R8Unorm
+            | TextureFormat::Rg8Unorm
+            | TextureFormat::Rgba8Unorm
+            | TextureFormat::Rgba8UnormSrgb
+            | TextureFormat::Bgra8Unorm
+            | TextureFormat::Bgra8UnormSrgb
+            | TextureFormat::Rgb10a2Unorm
+    )
+}
+
+/// Framebuffer attachment point and `blitFramebuffer` mask for given format
+pub fn attachment_for_format(format: TextureFormat) -> (u32, u32) {
+    match format {
+        TextureFormat::Depth32Float => (Gl::DEPTH_ATTACHMENT, Gl::DEPTH_BUFFER_BIT),
+        TextureFormat::Depth24Plus | TextureFormat::Depth24PlusStencil8 => (
+            Gl::DEPTH_STENCIL_ATTACHMENT,
+            Gl::DEPTH_BUFFER_BIT | Gl::STENCIL_BUFFER_BIT,
+        ),
+        _ => (Gl::COLOR_ATTACHMENT0, Gl::COLOR_BUFFER_BIT),
+    }
+}
+
+/// Formats which can be attached as color target in WebGL2 core
+/// (float formats require `EXT_color_buffer_float`)
+pub fn is_color_renderable(format: TextureFormat) -> bool {
+    !matches!(
+        format,
+        TextureFormat::R8Snorm
+            | TextureFormat::Rg8Snorm
+            | TextureFormat::Rgba8Snorm
+            | TextureFormat::R16Float
+            | TextureFormat::Rg16Float
+            | TextureFormat::Rgba16Float
+            | TextureFormat::R32Float
+            | TextureFormat::Rg32Float
+            | TextureFormat::Rgba32Float
+            | TextureFormat::Rg11b10Float
+            | TextureFormat::Depth32Float
+            | TextureFormat::Depth24Plus
+            | TextureFormat::Depth24PlusStencil8
+    )
+}
+
+/// Whether `blitFramebuffer` can copy between given formats: depth / stencil
+/// formats have to match exactly, color formats have to be renderable and
+/// of the same kind (normalized / float, signed or unsigned integer)
+pub fn can_blit(src: TextureFormat, dst: TextureFormat) -> bool {
+    if src == dst {
+        return true;
+    }
+    if !is_color_renderable(src) || !is_color_renderable(dst) {
+        return false;
+    }
+    let (_, src_format, src_type) = src.webgl2_into();
+    let (_, dst_format, dst_type) = dst.webgl2_into();
+    let is_integer = |format: u32| {
+        matches!(
+            format,
+            Gl::RED_INTEGER | Gl::RG_INTEGER | Gl::RGB_INTEGER | Gl::RGBA_INTEGER
+        )
+    };
+    let is_signed = |type_: u32| matches!(type_, Gl::BYTE | Gl::SHORT | Gl::INT);
+    match (is_integer(src_format), is_integer(dst_format)) {
+        (false, false) => true,
+        (true, true) => is_signed(src_type) == is_signed(dst_type),
+        _ => false,
+    }
+}
+
+// compressed formats are defined by extensions, not by WebGl2RenderingContext
+const COMPRESSED_RGBA_S3TC_DXT1_EXT: u32 = 0x83F1;
+const COMPRESSED_RGBA_S3TC_DXT3_EXT: u32 = 0x83F2;
+const COMPRESSED_RGBA_S3TC_DXT5_EXT: u32 = 0x83F3;
+const COMPRESSED_SRGB_ALPHA_S3TC_DXT1_EXT: u32 = 0x8C4D;
+const COMPRESSED_SRGB_ALPHA_S3TC_DXT3_EXT: u32 = 0x8C4E;
+const COMPRESSED_SRGB_ALPHA_S3TC_DXT5_EXT: u32 = 0x8C4F;
+const COMPRESSED_RGB8_ETC2: u32 = 0x9274;
+const COMPRESSED_SRGB8_ETC2: u32 = 0x9275;
+const COMPRESSED_RGB8_PUNCHTHROUGH_ALPHA1_ETC2: u32 = 0x9276;
+const COMPRESSED_SRGB8_PUNCHTHROUGH_ALPHA1_ETC2: u32 = 0x9277;
+const COMPRESSED_RGBA8_ETC2_EAC: u32 = 0x9278;
+const COMPRESSED_SRGB8_ALPHA8_ETC2_EAC: u32 = 0x9279;
+const COMPRESSED_RGBA_ASTC_4X4_KHR: u32 = 0x93B0;
+const COMPRESSED_SRGB8_ALPHA8_ASTC_4X4_KHR: u32 = 0x93D0;
+
+/// Block-compressed texture formats available through WebGL extensions.
+///
+/// `TextureFormat` has no compressed variants, so textures in these formats
+/// are created with `WebGL2RenderResourceContext::create_compressed_texture`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CompressedTextureFormat {
+    Bc1RgbaUnorm,
+    Bc1RgbaUnormSrgb,
+    Bc2RgbaUnorm,
+    Bc2RgbaUnormSrgb,
+    Bc3RgbaUnorm,
+    Bc3RgbaUnormSrgb,
+    Etc2Rgb8Unorm,
+    Etc2Rgb8UnormSrgb,
+    Etc2Rgb8A1Unorm,
+    Etc2Rgb8A1UnormSrgb,
+    Etc2Rgba8Unorm,
+    Etc2Rgba8UnormSrgb,
+    Astc4x4RgbaUnorm,
+    Astc4x4RgbaUnormSrgb,
+}
+
+impl CompressedTextureFormat {
+    pub const ALL: [CompressedTextureFormat; 14] = [
+        CompressedTextureFormat::Bc1RgbaUnorm,
+        CompressedTextureFormat::Bc1RgbaUnormSrgb,
+        CompressedTextureFormat::Bc2RgbaUnorm,
+        CompressedTextureFormat::Bc2RgbaUnormSrgb,
+        CompressedTextureFormat::Bc3RgbaUnorm,
+        CompressedTextureFormat::Bc3RgbaUnormSrgb,
+        CompressedTextureFormat::Etc2Rgb8Unorm,
+        CompressedTextureFormat::Etc2Rgb8UnormSrgb,
+        CompressedTextureFormat::Etc2Rgb8A1Unorm,
+        CompressedTextureFormat::Etc2Rgb8A1UnormSrgb,
+        CompressedTextureFormat::Etc2Rgba8Unorm,
+        CompressedTextureFormat::Etc2Rgba8UnormSrgb,
+        CompressedTextureFormat::Astc4x4RgbaUnorm,
+        CompressedTextureFormat::Astc4x4RgbaUnormSrgb,
+    ];
+
+    /// WebGL extension which has to be enabled to use the format
+    pub fn extension(self) -> &'static str {
+        match self {
+            CompressedTextureFormat::Bc1RgbaUnorm
+            | CompressedTextureFormat::Bc2RgbaUnorm
+            | CompressedTextureFormat::Bc3RgbaUnorm => "WEBGL_compressed_texture_s3tc",
+            CompressedTextureFormat::Bc1RgbaUnormSrgb
+            | CompressedTextureFormat::Bc2RgbaUnormSrgb
+            | CompressedTextureFormat::Bc3RgbaUnormSrgb => "WEBGL_compressed_texture_s3tc_srgb",
+            CompressedTextureFormat::Etc2Rgb8Unorm
+            | CompressedTextureFormat::Etc2Rgb8UnormSrgb
+            | CompressedTextureFormat::Etc2Rgb8A1Unorm
+            | CompressedTextureFormat::Etc2Rgb8A1UnormSrgb
+            | CompressedTextureFormat::Etc2Rgba8Unorm
+            | CompressedTextureFormat::Etc2Rgba8UnormSrgb => "WEBGL_compressed_texture_etc",
+            CompressedTextureFormat::Astc4x4RgbaUnorm
+            | CompressedTextureFormat::Astc4x4RgbaUnormSrgb => "WEBGL_compressed_texture_astc",
+        }
+    }
+
+    /// (block width, block height, bytes per block)
+    pub fn block_info(self) -> (u32, u32, u32) {
+        match self {
+            CompressedTextureFormat::Bc1RgbaUnorm
+            | CompressedTextureFormat::Bc1RgbaUnormSrgb
+            | CompressedTextureFormat::Etc2Rgb8Unorm
+            | CompressedTextureFormat::Etc2Rgb8UnormSrgb
+            | CompressedTextureFormat::Etc2Rgb8A1Unorm
+            | CompressedTextureFormat::Etc2Rgb8A1UnormSrgb => (4, 4, 8),
+            _ => (4, 4, 16),
+        }
+    }
+
+    /// Size in bytes of single image (mip level / cube face) with given dimensions
+    pub fn image_size(self, width: u32, height: u32) -> u32 {
+        let (block_width, block_height, block_size) = self.block_info();
+        ((width + block_width - 1) / block_width)
+            * ((height + block_height - 1) / block_height)
+            * block_size
+    }
+}
+
+impl WebGl2From<CompressedTextureFormat> for u32 {
+    fn from(val: CompressedTextureFormat) -> Self {
+        match val {
+            CompressedTextureFormat::Bc1RgbaUnorm => COMPRESSED_RGBA_S3TC_DXT1_EXT,
+            CompressedTextureFormat::Bc1RgbaUnormSrgb => COMPRESSED_SRGB_ALPHA_S3TC_DXT1_EXT,
+            CompressedTextureFormat::Bc2RgbaUnorm => COMPRESSED_RGBA_S3TC_DXT3_EXT,
+            CompressedTextureFormat::Bc2RgbaUnormSrgb => COMPRESSED_SRGB_ALPHA_S3TC_DXT3_EXT,
+            CompressedTextureFormat::Bc3RgbaUnorm => COMPRESSED_RGBA_S3TC_DXT5_EXT,
+            CompressedTextureFormat::Bc3RgbaUnormSrgb => COMPRESSED_SRGB_ALPHA_S3TC_DXT5_EXT,
+            CompressedTextureFormat::Etc2Rgb8Unorm => COMPRESSED_RGB8_ETC2,
+            CompressedTextureFormat::Etc2Rgb8UnormSrgb => COMPRESSED_SRGB8_ETC2,
+            CompressedTextureFormat::Etc2Rgb8A1Unorm => COMPRESSED_RGB8_PUNCHTHROUGH_ALPHA1_ETC2,
+            CompressedTextureFormat::Etc2Rgb8A1UnormSrgb => {
+                COMPRESSED_SRGB8_PUNCHTHROUGH_ALPHA1_ETC2
+            }
+            CompressedTextureFormat::Etc2Rgba8Unorm => COMPRESSED_RGBA8_ETC2_EAC,
+            CompressedTextureFormat::Etc2Rgba8UnormSrgb => COMPRESSED_SRGB8_ALPHA8_ETC2_EAC,
+            CompressedTextureFormat::Astc4x4RgbaUnorm => COMPRESSED_RGBA_ASTC_4X4_KHR,
+            CompressedTextureFormat::Astc4x4RgbaUnormSrgb => COMPRESSED_SRGB8_ALPHA8_ASTC_4X4_KHR,
+        }
+    }
+}
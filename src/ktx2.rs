@@ -0,0 +1,591 @@
+use crate::converters::CompressedTextureFormat;
+use crate::renderer::{WebGL2RenderContext, WebGL2RenderResourceContext};
+use anyhow::bail;
+use bevy::app::Events;
+use bevy::asset::{
+    AssetEvent, AssetLoader, AssetPath, AssetServer, Assets, HandleId, HandleUntyped, LoadContext,
+    LoadedAsset,
+};
+use bevy::ecs::world::World;
+use bevy::log::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy::render::{
+    renderer::{
+        BufferInfo, BufferUsage, RenderContext, RenderResourceContext, RenderResourceId, TextureId,
+    },
+    texture::{
+        Extent3d, FilterMode, Texture, TextureDescriptor, TextureDimension, TextureFormat,
+        TextureUsage, TextureViewDimension, TEXTURE_ASSET_INDEX,
+    },
+};
+use bevy::utils::{BoxedFuture, HashMap, HashSet};
+
+#[cfg(test)]
+mod tests;
+
+/// Label of `Ktx2Texture` sub-asset loaded together with placeholder `Texture`
+pub const KTX2_LABEL: &str = "ktx2";
+
+const KTX2_IDENTIFIER: [u8; 12] = [
+    0xAB, b'K', b'T', b'X', b' ', b'2', b'0', 0xBB, b'\r', b'\n', 0x1A, b'\n',
+];
+const KHR_DF_TRANSFER_SRGB: u8 = 2;
+
+// VkFormat values of formats which can be uploaded without transcoding
+const VK_FORMAT_UNDEFINED: u32 = 0;
+const VK_FORMAT_R8G8B8A8_UNORM: u32 = 37;
+const VK_FORMAT_R8G8B8A8_SRGB: u32 = 43;
+const VK_FORMAT_BC1_RGB_UNORM_BLOCK: u32 = 131;
+const VK_FORMAT_BC1_RGB_SRGB_BLOCK: u32 = 132;
+const VK_FORMAT_BC1_RGBA_UNORM_BLOCK: u32 = 133;
+const VK_FORMAT_BC1_RGBA_SRGB_BLOCK: u32 = 134;
+const VK_FORMAT_BC2_UNORM_BLOCK: u32 = 135;
+const VK_FORMAT_BC2_SRGB_BLOCK: u32 = 136;
+const VK_FORMAT_BC3_UNORM_BLOCK: u32 = 137;
+const VK_FORMAT_BC3_SRGB_BLOCK: u32 = 138;
+const VK_FORMAT_ETC2_R8G8B8_UNORM_BLOCK: u32 = 147;
+const VK_FORMAT_ETC2_R8G8B8_SRGB_BLOCK: u32 = 148;
+const VK_FORMAT_ETC2_R8G8B8A1_UNORM_BLOCK: u32 = 149;
+const VK_FORMAT_ETC2_R8G8B8A1_SRGB_BLOCK: u32 = 150;
+const VK_FORMAT_ETC2_R8G8B8A8_UNORM_BLOCK: u32 = 151;
+const VK_FORMAT_ETC2_R8G8B8A8_SRGB_BLOCK: u32 = 152;
+const VK_FORMAT_ASTC_4X4_UNORM_BLOCK: u32 = 157;
+const VK_FORMAT_ASTC_4X4_SRGB_BLOCK: u32 = 158;
+
+/// Pixel data layout of uploaded texture
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Payload {
+    Compressed(CompressedTextureFormat),
+    Rgba8,
+}
+
+/// Contents of `.ktx2` file.
+///
+/// GPU texture is created when WebGL2 context is known, Basis Universal payloads
+/// are transcoded at that point to a compressed format supported by the context.
+#[derive(Debug, TypeUuid)]
+#[uuid = "6b2b0e6c-93a8-4c61-8f0e-4a3f4f6f1d2e"]
+pub struct Ktx2Texture {
+    pub vk_format: u32,
+    pub width: u32,
+    pub height: u32,
+    pub faces: u32,
+    pub levels: u32,
+    pub srgb: bool,
+    /// (offset, length) of each mip level in `data`
+    pub level_ranges: Vec<(usize, usize)>,
+    /// whole file, Basis Universal transcoder reads it as a container
+    pub data: Vec<u8>,
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    let mut value = [0; 4];
+    value.copy_from_slice(&bytes[offset..offset + 4]);
+    u32::from_le_bytes(value)
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> u64 {
+    let mut value = [0; 8];
+    value.copy_from_slice(&bytes[offset..offset + 8]);
+    u64::from_le_bytes(value)
+}
+
+fn is_srgb_vk_format(vk_format: u32) -> bool {
+    matches!(
+        vk_format,
+        VK_FORMAT_R8G8B8A8_SRGB
+            | VK_FORMAT_BC1_RGB_SRGB_BLOCK
+            | VK_FORMAT_BC1_RGBA_SRGB_BLOCK
+            | VK_FORMAT_BC2_SRGB_BLOCK
+            | VK_FORMAT_BC3_SRGB_BLOCK
+            | VK_FORMAT_ETC2_R8G8B8_SRGB_BLOCK
+            | VK_FORMAT_ETC2_R8G8B8A1_SRGB_BLOCK
+            | VK_FORMAT_ETC2_R8G8B8A8_SRGB_BLOCK
+            | VK_FORMAT_ASTC_4X4_SRGB_BLOCK
+    )
+}
+
+impl Ktx2Texture {
+    pub fn parse(bytes: &[u8]) -> Result<Self, anyhow::Error> {
+        if bytes.len() < 80 || bytes[0..12] != KTX2_IDENTIFIER {
+            bail!("not a KTX2 file");
+        }
+        let vk_format = read_u32(bytes, 12);
+        let width = read_u32(bytes, 20);
+        let height = read_u32(bytes, 24).max(1);
+        let depth = read_u32(bytes, 28);
+        let layers = read_u32(bytes, 32);
+        let faces = read_u32(bytes, 36);
+        let levels = read_u32(bytes, 40).max(1);
+        let supercompression_scheme = read_u32(bytes, 44);
+        let dfd_offset = read_u32(bytes, 48) as usize;
+
+        if depth > 0 || layers > 1 {
+            bail!("3D and array KTX2 textures are not supported");
+        }
+        if faces != 1 && faces != 6 {
+            bail!("invalid face count: {}", faces);
+        }
+        if vk_format != VK_FORMAT_UNDEFINED && supercompression_scheme != 0 {
+            bail!(
+                "supercompression scheme {} is supported only for Basis Universal payloads",
+                supercompression_scheme
+            );
+        }
+
+        let level_index_end = (levels as usize)
+            .checked_mul(24)
+            .and_then(|size| size.checked_add(80));
+        // files without DFD have zero offset
+        let dfd_end = match dfd_offset {
+            0 => Some(0),
+            _ => dfd_offset.checked_add(16),
+        };
+        match (level_index_end, dfd_end) {
+            (Some(level_index_end), Some(dfd_end))
+                if bytes.len() >= level_index_end && bytes.len() >= dfd_end => {}
+            _ => bail!("truncated KTX2 file"),
+        }
+        let mut level_ranges = Vec::with_capacity(levels as usize);
+        for level in 0..levels as usize {
+            let offset = read_u64(bytes, 80 + level * 24);
+            let length = read_u64(bytes, 80 + level * 24 + 8);
+            match offset.checked_add(length) {
+                Some(end) if end <= bytes.len() as u64 => {
+                    level_ranges.push((offset as usize, length as usize))
+                }
+                _ => bail!("truncated KTX2 file"),
+            }
+        }
+        // transfer function of the first basic data format descriptor block
+        let srgb = match dfd_offset {
+            0 => is_srgb_vk_format(vk_format),
+            _ => bytes[dfd_offset + 14] == KHR_DF_TRANSFER_SRGB,
+        };
+
+        Ok(Ktx2Texture {
+            vk_format,
+            width,
+            height,
+            faces,
+            levels,
+            srgb,
+            level_ranges,
+            data: bytes.to_vec(),
+        })
+    }
+
+    fn level_size(&self, level: u32) -> Extent3d {
+        Extent3d::new(
+            (self.width >> level).max(1),
+            (self.height >> level).max(1),
+            self.faces,
+        )
+    }
+
+    /// Payload of file which doesn't need transcoding
+    fn direct_payload(&self) -> Result<Payload, String> {
+        use CompressedTextureFormat::*;
+        let format = match self.vk_format {
+            VK_FORMAT_R8G8B8A8_UNORM | VK_FORMAT_R8G8B8A8_SRGB => return Ok(Payload::Rgba8),
+            // BC1 RGB blocks are decoded the same way as RGBA ones
+            VK_FORMAT_BC1_RGB_UNORM_BLOCK | VK_FORMAT_BC1_RGBA_UNORM_BLOCK => Bc1RgbaUnorm,
+            VK_FORMAT_BC1_RGB_SRGB_BLOCK | VK_FORMAT_BC1_RGBA_SRGB_BLOCK => Bc1RgbaUnormSrgb,
+            VK_FORMAT_BC2_UNORM_BLOCK => Bc2RgbaUnorm,
+            VK_FORMAT_BC2_SRGB_BLOCK => Bc2RgbaUnormSrgb,
+            VK_FORMAT_BC3_UNORM_BLOCK => Bc3RgbaUnorm,
+            VK_FORMAT_BC3_SRGB_BLOCK => Bc3RgbaUnormSrgb,
+            VK_FORMAT_ETC2_R8G8B8_UNORM_BLOCK => Etc2Rgb8Unorm,
+            VK_FORMAT_ETC2_R8G8B8_SRGB_BLOCK => Etc2Rgb8UnormSrgb,
+            VK_FORMAT_ETC2_R8G8B8A1_UNORM_BLOCK => Etc2Rgb8A1Unorm,
+            VK_FORMAT_ETC2_R8G8B8A1_SRGB_BLOCK => Etc2Rgb8A1UnormSrgb,
+            VK_FORMAT_ETC2_R8G8B8A8_UNORM_BLOCK => Etc2Rgba8Unorm,
+            VK_FORMAT_ETC2_R8G8B8A8_SRGB_BLOCK => Etc2Rgba8UnormSrgb,
+            VK_FORMAT_ASTC_4X4_UNORM_BLOCK => Astc4x4RgbaUnorm,
+            VK_FORMAT_ASTC_4X4_SRGB_BLOCK => Astc4x4RgbaUnormSrgb,
+            vk_format => return Err(format!("unsupported VkFormat: {}", vk_format)),
+        };
+        Ok(Payload::Compressed(format))
+    }
+
+    /// Returns payload format and data of each mip level (faces stored one after another)
+    fn transcode(
+        &self,
+        supported_formats: &HashSet<CompressedTextureFormat>,
+    ) -> Result<(Payload, Vec<Vec<u8>>), String> {
+        if self.vk_format == VK_FORMAT_UNDEFINED {
+            return basis::transcode(self, |has_alpha| {
+                basis_payload(supported_formats, has_alpha, self.srgb)
+            });
+        }
+        let payload = self.direct_payload()?;
+        if let Payload::Compressed(format) = payload {
+            if !supported_formats.contains(&format) {
+                return Err(format!("{:?} is not supported by WebGL2 context", format));
+            }
+        }
+        let levels = self
+            .level_ranges
+            .iter()
+            .map(|(offset, length)| self.data[*offset..offset + length].to_vec())
+            .collect();
+        Ok((payload, levels))
+    }
+}
+
+/// Best format for Basis Universal transcoding supported by context, RGBA8 if none is
+fn basis_payload(
+    supported_formats: &HashSet<CompressedTextureFormat>,
+    has_alpha: bool,
+    srgb: bool,
+) -> Payload {
+    use CompressedTextureFormat::*;
+    let candidates: [(CompressedTextureFormat, CompressedTextureFormat); 3] = if has_alpha {
+        [
+            (Astc4x4RgbaUnorm, Astc4x4RgbaUnormSrgb),
+            (Bc3RgbaUnorm, Bc3RgbaUnormSrgb),
+            (Etc2Rgba8Unorm, Etc2Rgba8UnormSrgb),
+        ]
+    } else {
+        [
+            (Astc4x4RgbaUnorm, Astc4x4RgbaUnormSrgb),
+            (Bc1RgbaUnorm, Bc1RgbaUnormSrgb),
+            (Etc2Rgb8Unorm, Etc2Rgb8UnormSrgb),
+        ]
+    };
+    candidates
+        .iter()
+        .map(|(linear, srgb_format)| if srgb { *srgb_format } else { *linear })
+        .find(|format| supported_formats.contains(format))
+        .map(Payload::Compressed)
+        .unwrap_or(Payload::Rgba8)
+}
+
+#[cfg(target_arch = "wasm32")]
+mod basis {
+    use super::{Ktx2Texture, Payload};
+    use crate::converters::CompressedTextureFormat;
+    use wasm_bindgen::prelude::*;
+
+    // `KTX2File` of Basis Universal transcoder (basis_transcoder.js), its module
+    // has to be initialized and exposed as `BASIS_MODULE` global by the page
+    #[wasm_bindgen]
+    extern "C" {
+        #[wasm_bindgen(js_namespace = BASIS_MODULE)]
+        type KTX2File;
+
+        #[wasm_bindgen(constructor, js_namespace = BASIS_MODULE)]
+        fn new(data: &js_sys::Uint8Array) -> KTX2File;
+
+        #[wasm_bindgen(method, js_name = isValid)]
+        fn is_valid(this: &KTX2File) -> bool;
+
+        #[wasm_bindgen(method, js_name = getHasAlpha)]
+        fn has_alpha(this: &KTX2File) -> bool;
+
+        #[wasm_bindgen(method, js_name = startTranscoding)]
+        fn start_transcoding(this: &KTX2File) -> bool;
+
+        #[wasm_bindgen(method, js_name = getImageTranscodedSizeInBytes)]
+        fn image_transcoded_size(
+            this: &KTX2File,
+            level: u32,
+            layer: u32,
+            face: u32,
+            format: u32,
+        ) -> u32;
+
+        #[wasm_bindgen(method, js_name = transcodeImage)]
+        #[allow(clippy::too_many_arguments)]
+        fn transcode_image(
+            this: &KTX2File,
+            dst: &js_sys::Uint8Array,
+            level: u32,
+            layer: u32,
+            face: u32,
+            format: u32,
+            get_alpha_for_opaque_formats: u32,
+            channel0: i32,
+            channel1: i32,
+        ) -> u32;
+
+        #[wasm_bindgen(method)]
+        fn close(this: &KTX2File);
+
+        #[wasm_bindgen(method)]
+        fn delete(this: &KTX2File);
+    }
+
+    /// basis_universal `transcoder_texture_format` value
+    fn transcoder_format(payload: Payload) -> u32 {
+        use CompressedTextureFormat::*;
+        match payload {
+            Payload::Compressed(Etc2Rgb8Unorm) | Payload::Compressed(Etc2Rgb8UnormSrgb) => 0,
+            Payload::Compressed(Etc2Rgba8Unorm) | Payload::Compressed(Etc2Rgba8UnormSrgb) => 1,
+            Payload::Compressed(Bc1RgbaUnorm) | Payload::Compressed(Bc1RgbaUnormSrgb) => 2,
+            Payload::Compressed(Bc3RgbaUnorm) | Payload::Compressed(Bc3RgbaUnormSrgb) => 3,
+            Payload::Compressed(Astc4x4RgbaUnorm) | Payload::Compressed(Astc4x4RgbaUnormSrgb) => 10,
+            _ => 13,
+        }
+    }
+
+    fn transcode_file(
+        file: &KTX2File,
+        ktx2: &Ktx2Texture,
+        choose_payload: impl FnOnce(bool) -> Payload,
+    ) -> Result<(Payload, Vec<Vec<u8>>), String> {
+        if !file.is_valid() {
+            return Err("invalid Basis Universal KTX2 file".to_string());
+        }
+        let payload = choose_payload(file.has_alpha());
+        let format = transcoder_format(payload);
+        if !file.start_transcoding() {
+            return Err("can't start transcoding".to_string());
+        }
+        let mut levels = Vec::new();
+        for level in 0..ktx2.levels {
+            let mut level_data = Vec::new();
+            for face in 0..ktx2.faces {
+                let size = file.image_transcoded_size(level, 0, face, format);
+                let dst = js_sys::Uint8Array::new_with_length(size);
+                if file.transcode_image(&dst, level, 0, face, format, 0, -1, -1) == 0 {
+                    return Err(format!("transcoding level {} face {} failed", level, face));
+                }
+                level_data.extend(dst.to_vec());
+            }
+            levels.push(level_data);
+        }
+        Ok((payload, levels))
+    }
+
+    pub fn transcode(
+        ktx2: &Ktx2Texture,
+        choose_payload: impl FnOnce(bool) -> Payload,
+    ) -> Result<(Payload, Vec<Vec<u8>>), String> {
+        let loaded =
+            js_sys::Reflect::has(&js_sys::global(), &"BASIS_MODULE".into()).unwrap_or(false);
+        if !loaded {
+            return Err("Basis Universal transcoder (BASIS_MODULE) is not loaded".to_string());
+        }
+        let file = KTX2File::new(&js_sys::Uint8Array::from(&ktx2.data[..]));
+        let result = transcode_file(&file, ktx2, choose_payload);
+        file.close();
+        file.delete();
+        result
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod basis {
+    use super::{Ktx2Texture, Payload};
+
+    pub fn transcode(
+        _ktx2: &Ktx2Texture,
+        _choose_payload: impl FnOnce(bool) -> Payload,
+    ) -> Result<(Payload, Vec<Vec<u8>>), String> {
+        Err("Basis Universal transcoding is available only on wasm32 target".to_string())
+    }
+}
+
+/// Loads `.ktx2` files as `Texture` assets.
+///
+/// Loaded `Texture` is 1x1 white placeholder reporting extent of the file, actual GPU
+/// texture is created from `Ktx2Texture` sub-asset by `ktx2_texture_system`.
+#[derive(Default)]
+pub struct Ktx2Loader;
+
+impl AssetLoader for Ktx2Loader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let ktx2 = Ktx2Texture::parse(bytes)?;
+            let mut texture = Texture::new_fill(
+                Extent3d::new(1, 1, 1),
+                TextureDimension::D2,
+                &[255, 255, 255, 255],
+                TextureFormat::Rgba8UnormSrgb,
+            );
+            // sprites are sized by the texture, so it reports extent of the file
+            texture.size = Extent3d::new(ktx2.width, ktx2.height, 1);
+            texture.sampler.mag_filter = FilterMode::Linear;
+            texture.sampler.min_filter = FilterMode::Linear;
+            if ktx2.levels > 1 {
+                texture.sampler.mipmap_filter = FilterMode::Linear;
+            }
+            load_context.set_labeled_asset(KTX2_LABEL, LoadedAsset::new(ktx2));
+            load_context.set_default_asset(LoadedAsset::new(texture));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ktx2"]
+    }
+}
+
+fn create_texture(
+    ktx2: &Ktx2Texture,
+    render_resource_context: &WebGL2RenderResourceContext,
+) -> Result<TextureId, String> {
+    let (payload, levels) = {
+        let supported_formats = render_resource_context
+            .resources
+            .compressed_texture_formats
+            .read();
+        ktx2.transcode(&supported_formats)?
+    };
+    let descriptor = TextureDescriptor {
+        size: ktx2.level_size(0),
+        mip_level_count: levels.len() as u32,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format: if ktx2.srgb {
+            TextureFormat::Rgba8UnormSrgb
+        } else {
+            TextureFormat::Rgba8Unorm
+        },
+        usage: TextureUsage::SAMPLED | TextureUsage::COPY_DST,
+    };
+    let view_dimension = if ktx2.faces == 6 {
+        TextureViewDimension::Cube
+    } else {
+        TextureViewDimension::D2
+    };
+    let texture_id = match payload {
+        Payload::Compressed(format) => {
+            render_resource_context.create_compressed_texture(descriptor, view_dimension, format)?
+        }
+        Payload::Rgba8 => {
+            render_resource_context.create_texture_with_view_dimension(descriptor, view_dimension)
+        }
+    };
+
+    let mut render_context = WebGL2RenderContext::new(
+        render_resource_context.device.clone(),
+        render_resource_context.clone(),
+    );
+    for (level, data) in levels.iter().enumerate() {
+        let size = ktx2.level_size(level as u32);
+        let bytes_per_row = match payload {
+            Payload::Compressed(_) => 0,
+            Payload::Rgba8 => size.width * 4,
+        };
+        let buffer = render_resource_context.create_buffer_with_data(
+            BufferInfo {
+                size: data.len(),
+                buffer_usage: BufferUsage::MAP_WRITE | BufferUsage::COPY_SRC,
+                mapped_at_creation: false,
+            },
+            data,
+        );
+        render_context.copy_buffer_to_texture(
+            buffer,
+            0,
+            bytes_per_row,
+            texture_id,
+            [0, 0, 0],
+            level as u32,
+            size,
+        );
+        render_resource_context.remove_buffer(buffer);
+    }
+    Ok(texture_id)
+}
+
+/// Replaces placeholder GPU textures of loaded `.ktx2` files with textures
+/// created from their payload
+pub fn ktx2_texture_system() -> impl FnMut(&mut World) {
+    let events = Events::<AssetEvent<Texture>>::default();
+    let mut texture_event_reader = events.get_reader();
+    // textures created from payloads, removed when their asset changes
+    let mut ktx2_texture_ids: HashMap<HandleId, TextureId> = HashMap::default();
+
+    move |world| {
+        let events = {
+            let texture_events = world.get_resource::<Events<AssetEvent<Texture>>>().unwrap();
+            texture_event_reader
+                .iter(&texture_events)
+                .map(|event| match event {
+                    AssetEvent::Created { handle } | AssetEvent::Modified { handle } => {
+                        (handle.clone_weak(), false)
+                    }
+                    AssetEvent::Removed { handle } => (handle.clone_weak(), true),
+                })
+                .collect::<Vec<_>>()
+        };
+        if events.is_empty() {
+            return;
+        }
+
+        let asset_server = world.get_resource::<AssetServer>().unwrap();
+        let ktx2_textures = world.get_resource::<Assets<Ktx2Texture>>().unwrap();
+        // WebGL2 context is created with the first window
+        let render_resource_context = match world
+            .get_resource::<Box<dyn RenderResourceContext>>()
+            .and_then(|context| context.downcast_ref::<WebGL2RenderResourceContext>())
+        {
+            Some(render_resource_context) => render_resource_context,
+            None => return,
+        };
+        let remove_texture = |texture_id: TextureId| {
+            // it may have been removed by bevy together with asset resources
+            let exists = render_resource_context
+                .resources
+                .texture_descriptors
+                .read()
+                .contains_key(&texture_id);
+            if exists {
+                render_resource_context.remove_texture(texture_id);
+            }
+        };
+
+        for (handle, removed) in events {
+            if removed {
+                if let Some(texture_id) = ktx2_texture_ids.remove(&handle.id) {
+                    remove_texture(texture_id);
+                }
+                continue;
+            }
+            let path = match asset_server.get_handle_path(&handle) {
+                Some(path) => path,
+                None => continue,
+            };
+            let ktx2_handle_id: HandleId = AssetPath::new_ref(path.path(), Some(KTX2_LABEL)).into();
+            let ktx2 = match ktx2_textures.get(ktx2_handle_id) {
+                Some(ktx2) => ktx2,
+                None => continue,
+            };
+            if let Some(texture_id) = ktx2_texture_ids.remove(&handle.id) {
+                remove_texture(texture_id);
+            }
+            let texture_id = match create_texture(ktx2, render_resource_context) {
+                Ok(texture_id) => texture_id,
+                Err(err) => {
+                    warn!("can't create texture from {:?}: {}", path.path(), err);
+                    continue;
+                }
+            };
+            let handle = HandleUntyped::weak(handle.id);
+            if let Some(RenderResourceId::Texture(placeholder)) = render_resource_context
+                .get_asset_resource_untyped(handle.clone(), TEXTURE_ASSET_INDEX)
+            {
+                remove_texture(placeholder);
+            }
+            render_resource_context.set_asset_resource_untyped(
+                handle,
+                RenderResourceId::Texture(texture_id),
+                TEXTURE_ASSET_INDEX,
+            );
+            // placeholder data is uploaded later to texture of the asset resource
+            render_resource_context
+                .resources
+                .skipped_uploads
+                .write()
+                .insert(texture_id);
+            ktx2_texture_ids.insert(handle.id, texture_id);
+        }
+    }
+}
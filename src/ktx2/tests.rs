@@ -0,0 +1,154 @@
+use super::{
+    Ktx2Texture, Payload, KHR_DF_TRANSFER_SRGB, KTX2_IDENTIFIER, VK_FORMAT_R8G8B8A8_SRGB,
+    VK_FORMAT_R8G8B8A8_UNORM,
+};
+use bevy::render::texture::Extent3d;
+
+/// In-memory `.ktx2` file: header, level index, DFD and level data
+struct Ktx2File {
+    vk_format: u32,
+    width: u32,
+    height: u32,
+    depth: u32,
+    faces: u32,
+    /// transfer function of DFD block, no DFD if `None`
+    transfer_function: Option<u8>,
+    levels: Vec<Vec<u8>>,
+}
+
+impl Default for Ktx2File {
+    fn default() -> Self {
+        Ktx2File {
+            vk_format: VK_FORMAT_R8G8B8A8_UNORM,
+            width: 4,
+            height: 2,
+            depth: 0,
+            faces: 1,
+            transfer_function: Some(1),
+            levels: vec![vec![1; 4 * 2 * 4], vec![2; 2 * 4]],
+        }
+    }
+}
+
+impl Ktx2File {
+    fn bytes(&self) -> Vec<u8> {
+        let level_index_end = 80 + 24 * self.levels.len();
+        let dfd_offset = match self.transfer_function {
+            Some(_) => level_index_end,
+            None => 0,
+        };
+        let mut bytes = KTX2_IDENTIFIER.to_vec();
+        for value in [
+            self.vk_format,
+            1,
+            self.width,
+            self.height,
+            self.depth,
+            0,
+            self.faces,
+            self.levels.len() as u32,
+            0,
+            dfd_offset as u32,
+        ]
+        .iter()
+        {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        bytes.resize(80, 0);
+
+        let mut offset = level_index_end + self.transfer_function.map_or(0, |_| 16);
+        for level in self.levels.iter() {
+            let length = level.len() as u64;
+            for value in [offset as u64, length, length].iter() {
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+            offset += level.len();
+        }
+        if let Some(transfer_function) = self.transfer_function {
+            let mut dfd = [0; 16];
+            dfd[14] = transfer_function;
+            bytes.extend_from_slice(&dfd);
+        }
+        for level in self.levels.iter() {
+            bytes.extend_from_slice(level);
+        }
+        bytes
+    }
+}
+
+#[test]
+fn parses_2d_file() {
+    let file = Ktx2File::default();
+    let ktx2 = Ktx2Texture::parse(&file.bytes()).unwrap();
+    assert_eq!((ktx2.width, ktx2.height, ktx2.faces), (4, 2, 1));
+    assert_eq!(ktx2.levels, 2);
+    assert!(!ktx2.srgb);
+    // after header, level index of 2 levels and DFD
+    assert_eq!(ktx2.level_ranges, [(144, 32), (176, 8)]);
+    assert_eq!(ktx2.data[144..176], file.levels[0][..]);
+    assert_eq!(ktx2.level_size(1), Extent3d::new(2, 1, 1));
+    assert_eq!(ktx2.direct_payload(), Ok(Payload::Rgba8));
+}
+
+#[test]
+fn parses_srgb_transfer_function_from_dfd() {
+    let file = Ktx2File {
+        transfer_function: Some(KHR_DF_TRANSFER_SRGB),
+        ..Default::default()
+    };
+    assert!(Ktx2Texture::parse(&file.bytes()).unwrap().srgb);
+}
+
+#[test]
+fn parses_cube_file() {
+    let file = Ktx2File {
+        width: 2,
+        height: 2,
+        faces: 6,
+        levels: vec![vec![0; 6 * 2 * 2 * 4], vec![0; 6 * 4]],
+        ..Default::default()
+    };
+    let ktx2 = Ktx2Texture::parse(&file.bytes()).unwrap();
+    assert_eq!(ktx2.faces, 6);
+    // faces are stored one after another in each level
+    assert_eq!(ktx2.level_ranges[1].1, 6 * 4);
+    assert_eq!(ktx2.level_size(0), Extent3d::new(2, 2, 6));
+}
+
+#[test]
+fn rejects_truncated_file() {
+    let bytes = Ktx2File::default().bytes();
+    // level data
+    assert!(Ktx2Texture::parse(&bytes[..bytes.len() - 1]).is_err());
+    // level index
+    assert!(Ktx2Texture::parse(&bytes[..100]).is_err());
+    // header
+    assert!(Ktx2Texture::parse(&bytes[..79]).is_err());
+}
+
+#[test]
+fn rejects_3d_file() {
+    let file = Ktx2File {
+        depth: 2,
+        ..Default::default()
+    };
+    assert!(Ktx2Texture::parse(&file.bytes()).is_err());
+}
+
+#[test]
+fn file_without_dfd_is_srgb_if_its_format_is() {
+    let file = Ktx2File {
+        transfer_function: None,
+        ..Default::default()
+    };
+    let ktx2 = Ktx2Texture::parse(&file.bytes()).unwrap();
+    assert!(!ktx2.srgb);
+    assert_eq!(ktx2.level_ranges[0], (128, 32));
+
+    let file = Ktx2File {
+        vk_format: VK_FORMAT_R8G8B8A8_SRGB,
+        transfer_function: None,
+        ..Default::default()
+    };
+    assert!(Ktx2Texture::parse(&file.bytes()).unwrap().srgb);
+}
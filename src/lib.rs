@@ -1,6 +1,11 @@
 pub mod converters;
 mod default_plugins;
+pub mod ktx2;
 pub mod renderer;
+mod sprite_batch;
+mod webgl2_capabilities;
+mod webgl2_context_settings;
+mod webgl2_limits;
 mod webgl2_render_pass;
 mod webgl2_renderer;
 mod webgl2_resources;
@@ -8,17 +13,22 @@ use crate::renderer::WebGL2RenderResourceContext;
 use bevy::app::{prelude::*, Events};
 use bevy::window::{WindowCreated, Windows};
 pub use default_plugins::*;
+pub use sprite_batch::*;
 use std::sync::Arc;
+pub use webgl2_capabilities::*;
+pub use webgl2_context_settings::*;
+pub use webgl2_limits::*;
 pub use webgl2_render_pass::*;
 pub use webgl2_renderer::*;
 pub use webgl2_resources::*;
 
-use bevy::asset::{Assets, HandleUntyped};
+use bevy::asset::{AddAsset, Assets, HandleUntyped};
 use bevy::ecs::prelude::*;
 use bevy::ecs::{
     schedule::{StageLabel, SystemStage},
     world::World,
 };
+use bevy::log::prelude::*;
 use bevy::reflect::TypeUuid;
 use bevy::render::{
     pipeline::PipelineDescriptor,
@@ -94,6 +104,9 @@ impl bevy::prelude::Plugin for WebGL2Plugin {
                 }
             }
         }
+        app.init_resource::<WebGL2ContextSettings>()
+            .add_asset::<ktx2::Ktx2Texture>()
+            .init_asset_loader::<ktx2::Ktx2Loader>();
         let world = &mut app.world;
         let render_system = webgl2_render_system(world);
         let handle_events_system = webgl2_handle_window_created_events_system();
@@ -106,6 +119,10 @@ impl bevy::prelude::Plugin for WebGL2Plugin {
             WebGL2Stage::PreRenderResource,
             handle_events_system.exclusive_system(),
         )
+        .add_system_to_stage(
+            RenderStage::RenderResource,
+            ktx2::ktx2_texture_system().exclusive_system().at_end(),
+        )
         .add_system_to_stage(RenderStage::Render, render_system.exclusive_system())
         .add_system_to_stage(
             RenderStage::PostRender,
@@ -139,10 +156,19 @@ pub fn webgl2_handle_window_created_events_system() -> impl FnMut(&mut World) {
                 let device = &*world.get_resource::<Arc<Device>>().unwrap();
                 let winit_windows = world.get_resource::<bevy::winit::WinitWindows>().unwrap();
                 let winit_window = winit_windows.get_window(window_id).unwrap();
+                let settings = world
+                    .get_resource::<WebGL2ContextSettings>()
+                    .cloned()
+                    .unwrap_or_default();
                 let mut render_resource_context = WebGL2RenderResourceContext::new(device.clone());
-                render_resource_context.initialize(&winit_window);
+                if let Err(err) = render_resource_context.initialize(&winit_window, &settings) {
+                    error!("can't create WebGL2 context: {}", err);
+                    continue;
+                }
                 render_resource_context
             };
+            world.insert_resource(render_resource_context.limits());
+            world.insert_resource(render_resource_context.capabilities());
             world.insert_resource::<Box<dyn RenderResourceContext>>(Box::new(
                 render_resource_context,
             ));
@@ -0,0 +1,407 @@
+use std::fmt::Debug;
+
+macro_rules! gl_handles {
+    ($($name:ident),* $(,)?) => {
+        $(
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+            pub struct $name(pub u32);
+        )*
+    };
+}
+
+// opaque handles for GL objects, each backend maps them to its own objects
+gl_handles!(
+    NativeBuffer,
+    NativeTexture,
+    NativeFramebuffer,
+    NativeRenderbuffer,
+    NativeProgram,
+    NativeShader,
+    NativeVertexArray,
+    NativeUniformLocation,
+    NativeSampler,
+);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlActiveInfo {
+    pub name: String,
+    pub size: i32,
+    pub type_: u32,
+}
+
+/// Set of GL calls used by the renderer.
+///
+/// Method names and arguments follow WebGL2 API, GL objects are passed as opaque
+/// `Native*` handles, so renderer code can run against any implementation
+/// (WebGL2 context in the browser, or [`super::RecordingBackend`] in tests).
+pub trait GlBackend: Debug {
+    fn get_parameter_i32(&self, pname: u32) -> i32;
+    fn get_parameter_i32_array(&self, pname: u32) -> Vec<i32>;
+    fn get_parameter_string(&self, pname: u32) -> Option<String>;
+    /// Names of available extensions (WebGL naming)
+    fn get_supported_extensions(&self) -> Vec<String>;
+    /// Enables extension, returns `false` if it is not available
+    fn enable_extension(&self, name: &str) -> bool;
+
+    fn enable(&self, cap: u32);
+    fn disable(&self, cap: u32);
+    fn viewport(&self, x: i32, y: i32, width: i32, height: i32);
+    fn scissor(&self, x: i32, y: i32, width: i32, height: i32);
+    fn cull_face(&self, mode: u32);
+    fn front_face(&self, mode: u32);
+    fn depth_func(&self, func: u32);
+    fn depth_mask(&self, flag: bool);
+    fn stencil_func_separate(&self, face: u32, func: u32, reference: i32, mask: u32);
+    fn stencil_op_separate(&self, face: u32, fail: u32, depth_fail: u32, pass: u32);
+    fn stencil_mask_separate(&self, face: u32, mask: u32);
+    fn polygon_offset(&self, factor: f32, units: f32);
+    fn blend_func_separate(&self, src_rgb: u32, dst_rgb: u32, src_alpha: u32, dst_alpha: u32);
+    fn blend_equation_separate(&self, mode_rgb: u32, mode_alpha: u32);
+    fn blend_color(&self, r: f32, g: f32, b: f32, a: f32);
+    fn color_mask(&self, r: bool, g: bool, b: bool, a: bool);
+    // per draw buffer state, requires `OES_draw_buffers_indexed` extension to be enabled
+    fn enable_i(&self, cap: u32, index: u32);
+    fn disable_i(&self, cap: u32, index: u32);
+    fn blend_equation_separate_i(&self, buffer: u32, mode_rgb: u32, mode_alpha: u32);
+    fn blend_func_separate_i(
+        &self,
+        buffer: u32,
+        src_rgb: u32,
+        dst_rgb: u32,
+        src_alpha: u32,
+        dst_alpha: u32,
+    );
+    fn color_mask_i(&self, buffer: u32, r: bool, g: bool, b: bool, a: bool);
+
+    fn clear_color(&self, r: f32, g: f32, b: f32, a: f32);
+    fn clear_depth(&self, depth: f32);
+    fn clear(&self, mask: u32);
+    fn clear_bufferfv(&self, buffer: u32, draw_buffer: i32, values: &[f32]);
+    fn clear_bufferiv(&self, buffer: u32, draw_buffer: i32, values: &[i32]);
+    fn clear_bufferuiv(&self, buffer: u32, draw_buffer: i32, values: &[u32]);
+    fn clear_bufferfi(&self, buffer: u32, draw_buffer: i32, depth: f32, stencil: i32);
+
+    fn create_shader(&self, shader_type: u32) -> Option<NativeShader>;
+    fn shader_source(&self, shader: NativeShader, source: &str);
+    fn compile_shader(&self, shader: NativeShader);
+    fn get_shader_compile_status(&self, shader: NativeShader) -> bool;
+    fn get_shader_info_log(&self, shader: NativeShader) -> Option<String>;
+
+    fn create_program(&self) -> Option<NativeProgram>;
+    fn attach_shader(&self, program: NativeProgram, shader: NativeShader);
+    fn link_program(&self, program: NativeProgram);
+    fn get_program_link_status(&self, program: NativeProgram) -> bool;
+    fn get_program_info_log(&self, program: NativeProgram) -> Option<String>;
+    fn get_program_parameter_i32(&self, program: NativeProgram, pname: u32) -> i32;
+    fn use_program(&self, program: Option<NativeProgram>);
+
+    fn get_active_attrib(&self, program: NativeProgram, index: u32) -> Option<GlActiveInfo>;
+    fn get_active_uniform(&self, program: NativeProgram, index: u32) -> Option<GlActiveInfo>;
+    fn get_active_uniform_block_name(&self, program: NativeProgram, index: u32) -> Option<String>;
+    fn get_active_uniform_block_parameter_i32(
+        &self,
+        program: NativeProgram,
+        index: u32,
+        pname: u32,
+    ) -> i32;
+    fn get_attrib_location(&self, program: NativeProgram, name: &str) -> i32;
+    fn get_uniform_block_index(&self, program: NativeProgram, name: &str) -> u32;
+    fn get_uniform_location(
+        &self,
+        program: NativeProgram,
+        name: &str,
+    ) -> Option<NativeUniformLocation>;
+    fn uniform_block_binding(&self, program: NativeProgram, index: u32, binding: u32);
+    fn uniform1i(&self, location: Option<NativeUniformLocation>, value: i32);
+
+    fn create_buffer(&self) -> Option<NativeBuffer>;
+    fn delete_buffer(&self, buffer: NativeBuffer);
+    fn bind_buffer(&self, target: u32, buffer: Option<NativeBuffer>);
+    fn bind_buffer_range(
+        &self,
+        target: u32,
+        index: u32,
+        buffer: Option<NativeBuffer>,
+        offset: i32,
+        size: i32,
+    );
+    fn buffer_data_size(&self, target: u32, size: i32, usage: u32);
+    fn buffer_data_u8_slice(&self, target: u32, data: &[u8], usage: u32);
+    fn buffer_sub_data_u8_slice(&self, target: u32, offset: i32, data: &[u8]);
+    fn get_buffer_sub_data(&self, target: u32, offset: i32, data: &mut [u8]);
+    fn copy_buffer_sub_data(
+        &self,
+        read_target: u32,
+        write_target: u32,
+        read_offset: i32,
+        write_offset: i32,
+        size: i32,
+    );
+
+    fn create_texture(&self) -> Option<NativeTexture>;
+    fn delete_texture(&self, texture: NativeTexture);
+    fn active_texture(&self, unit: u32);
+    fn bind_texture(&self, target: u32, texture: Option<NativeTexture>);
+    fn tex_parameteri(&self, target: u32, pname: u32, param: i32);
+    fn pixel_storei(&self, pname: u32, param: i32);
+    #[allow(clippy::too_many_arguments)]
+    fn tex_image_2d(
+        &self,
+        target: u32,
+        level: i32,
+        internal_format: i32,
+        width: i32,
+        height: i32,
+        border: i32,
+        format: u32,
+        type_: u32,
+        pixels: Option<&[u8]>,
+    );
+    /// Same as `tex_image_2d`, but reads pixels from buffer bound to `PIXEL_UNPACK_BUFFER`
+    #[allow(clippy::too_many_arguments)]
+    fn tex_image_2d_with_pbo_offset(
+        &self,
+        target: u32,
+        level: i32,
+        internal_format: i32,
+        width: i32,
+        height: i32,
+        border: i32,
+        format: u32,
+        type_: u32,
+        offset: i32,
+    );
+    #[allow(clippy::too_many_arguments)]
+    fn compressed_tex_image_2d(
+        &self,
+        target: u32,
+        level: i32,
+        internal_format: u32,
+        width: i32,
+        height: i32,
+        border: i32,
+        data: &[u8],
+    );
+    /// Same as `compressed_tex_image_2d`, but reads data from buffer bound to `PIXEL_UNPACK_BUFFER`
+    #[allow(clippy::too_many_arguments)]
+    fn compressed_tex_image_2d_with_pbo_offset(
+        &self,
+        target: u32,
+        level: i32,
+        internal_format: u32,
+        width: i32,
+        height: i32,
+        border: i32,
+        image_size: i32,
+        offset: i32,
+    );
+    fn tex_storage_2d(
+        &self,
+        target: u32,
+        levels: i32,
+        internal_format: u32,
+        width: i32,
+        height: i32,
+    );
+    #[allow(clippy::too_many_arguments)]
+    fn tex_sub_image_2d(
+        &self,
+        target: u32,
+        level: i32,
+        x_offset: i32,
+        y_offset: i32,
+        width: i32,
+        height: i32,
+        format: u32,
+        type_: u32,
+        pixels: &[u8],
+    );
+    /// Same as `tex_sub_image_2d`, but reads pixels from buffer bound to `PIXEL_UNPACK_BUFFER`
+    #[allow(clippy::too_many_arguments)]
+    fn tex_sub_image_2d_with_pbo_offset(
+        &self,
+        target: u32,
+        level: i32,
+        x_offset: i32,
+        y_offset: i32,
+        width: i32,
+        height: i32,
+        format: u32,
+        type_: u32,
+        offset: i32,
+    );
+    /// Copies pixels from framebuffer bound to `READ_FRAMEBUFFER`
+    #[allow(clippy::too_many_arguments)]
+    fn copy_tex_sub_image_2d(
+        &self,
+        target: u32,
+        level: i32,
+        x_offset: i32,
+        y_offset: i32,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+    );
+    fn tex_storage_3d(
+        &self,
+        target: u32,
+        levels: i32,
+        internal_format: u32,
+        width: i32,
+        height: i32,
+        depth: i32,
+    );
+    #[allow(clippy::too_many_arguments)]
+    fn tex_sub_image_3d(
+        &self,
+        target: u32,
+        level: i32,
+        x_offset: i32,
+        y_offset: i32,
+        z_offset: i32,
+        width: i32,
+        height: i32,
+        depth: i32,
+        format: u32,
+        type_: u32,
+        pixels: &[u8],
+    );
+    /// Same as `tex_sub_image_3d`, but reads pixels from buffer bound to `PIXEL_UNPACK_BUFFER`
+    #[allow(clippy::too_many_arguments)]
+    fn tex_sub_image_3d_with_pbo_offset(
+        &self,
+        target: u32,
+        level: i32,
+        x_offset: i32,
+        y_offset: i32,
+        z_offset: i32,
+        width: i32,
+        height: i32,
+        depth: i32,
+        format: u32,
+        type_: u32,
+        offset: i32,
+    );
+    /// Copies pixels from framebuffer bound to `READ_FRAMEBUFFER` into single layer
+    #[allow(clippy::too_many_arguments)]
+    fn copy_tex_sub_image_3d(
+        &self,
+        target: u32,
+        level: i32,
+        x_offset: i32,
+        y_offset: i32,
+        z_offset: i32,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+    );
+    fn generate_mipmap(&self, target: u32);
+
+    fn create_sampler(&self) -> Option<NativeSampler>;
+    fn delete_sampler(&self, sampler: NativeSampler);
+    fn bind_sampler(&self, unit: u32, sampler: Option<NativeSampler>);
+    fn sampler_parameteri(&self, sampler: NativeSampler, pname: u32, param: i32);
+    fn sampler_parameterf(&self, sampler: NativeSampler, pname: u32, param: f32);
+
+    fn create_framebuffer(&self) -> Option<NativeFramebuffer>;
+    fn delete_framebuffer(&self, framebuffer: NativeFramebuffer);
+    fn bind_framebuffer(&self, target: u32, framebuffer: Option<NativeFramebuffer>);
+    fn framebuffer_texture_2d(
+        &self,
+        target: u32,
+        attachment: u32,
+        texture_target: u32,
+        texture: Option<NativeTexture>,
+        level: i32,
+    );
+    fn framebuffer_texture_layer(
+        &self,
+        target: u32,
+        attachment: u32,
+        texture: Option<NativeTexture>,
+        level: i32,
+        layer: i32,
+    );
+    fn check_framebuffer_status(&self, target: u32) -> u32;
+    fn create_renderbuffer(&self) -> Option<NativeRenderbuffer>;
+    fn delete_renderbuffer(&self, renderbuffer: NativeRenderbuffer);
+    fn bind_renderbuffer(&self, target: u32, renderbuffer: Option<NativeRenderbuffer>);
+    fn renderbuffer_storage_multisample(
+        &self,
+        target: u32,
+        samples: i32,
+        internal_format: u32,
+        width: i32,
+        height: i32,
+    );
+    fn framebuffer_renderbuffer(
+        &self,
+        target: u32,
+        attachment: u32,
+        renderbuffer_target: u32,
+        renderbuffer: Option<NativeRenderbuffer>,
+    );
+    fn draw_buffers(&self, buffers: &[u32]);
+    fn read_buffer(&self, src: u32);
+    #[allow(clippy::too_many_arguments)]
+    fn blit_framebuffer(
+        &self,
+        src_x0: i32,
+        src_y0: i32,
+        src_x1: i32,
+        src_y1: i32,
+        dst_x0: i32,
+        dst_y0: i32,
+        dst_x1: i32,
+        dst_y1: i32,
+        mask: u32,
+        filter: u32,
+    );
+    /// Reads pixels into buffer bound to `PIXEL_PACK_BUFFER`
+    #[allow(clippy::too_many_arguments)]
+    fn read_pixels_with_pbo_offset(
+        &self,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        format: u32,
+        type_: u32,
+        offset: i32,
+    );
+
+    fn create_vertex_array(&self) -> Option<NativeVertexArray>;
+    fn bind_vertex_array(&self, vertex_array: Option<NativeVertexArray>);
+    fn enable_vertex_attrib_array(&self, index: u32);
+    fn vertex_attrib_pointer_f32(
+        &self,
+        index: u32,
+        size: i32,
+        type_: u32,
+        normalized: bool,
+        stride: i32,
+        offset: i32,
+    );
+    fn vertex_attrib_pointer_i32(
+        &self,
+        index: u32,
+        size: i32,
+        type_: u32,
+        stride: i32,
+        offset: i32,
+    );
+    fn vertex_attrib_divisor(&self, index: u32, divisor: u32);
+
+    fn draw_arrays(&self, mode: u32, first: i32, count: i32);
+    fn draw_arrays_instanced(&self, mode: u32, first: i32, count: i32, instance_count: i32);
+    fn draw_elements_instanced(
+        &self,
+        mode: u32,
+        count: i32,
+        type_: u32,
+        offset: i32,
+        instance_count: i32,
+    );
+}
@@ -0,0 +1,947 @@
+use super::Gl;
+use super::{
+    GlActiveInfo, GlBackend, NativeBuffer, NativeFramebuffer, NativeProgram, NativeRenderbuffer,
+    NativeSampler, NativeShader, NativeTexture, NativeUniformLocation, NativeVertexArray,
+};
+use bevy::utils::HashMap;
+use std::cell::RefCell;
+
+/// Shadow copy of GL state changed through [`GlStateCache`].
+///
+/// `None` / missing entries mean the state is unknown, so the next call
+/// setting it is always passed to the backend.
+#[derive(Debug, Default)]
+struct GlState {
+    capabilities: HashMap<u32, bool>,
+    viewport: Option<[i32; 4]>,
+    scissor: Option<[i32; 4]>,
+    cull_face: Option<u32>,
+    front_face: Option<u32>,
+    depth_func: Option<u32>,
+    depth_mask: Option<bool>,
+    stencil_funcs: HashMap<u32, (u32, i32, u32)>,
+    stencil_ops: HashMap<u32, [u32; 3]>,
+    stencil_masks: HashMap<u32, u32>,
+    polygon_offset: Option<[f32; 2]>,
+    blend_func: Option<[u32; 4]>,
+    blend_equation: Option<[u32; 2]>,
+    blend_color: Option<[f32; 4]>,
+    color_mask: Option<[bool; 4]>,
+    clear_color: Option<[f32; 4]>,
+    program: Option<Option<NativeProgram>>,
+    vertex_array: Option<Option<NativeVertexArray>>,
+    buffers: HashMap<u32, Option<NativeBuffer>>,
+    buffer_ranges: HashMap<(u32, u32), (Option<NativeBuffer>, i32, i32)>,
+    active_texture: Option<u32>,
+    textures: HashMap<(u32, u32), Option<NativeTexture>>,
+    samplers: HashMap<u32, Option<NativeSampler>>,
+    framebuffers: HashMap<u32, Option<NativeFramebuffer>>,
+}
+
+/// Stores `value` as the current state, returns `true` if it differs from the previous one.
+fn update<T: PartialEq>(current: &mut Option<T>, value: T) -> bool {
+    if current.as_ref() == Some(&value) {
+        return false;
+    }
+    *current = Some(value);
+    true
+}
+
+/// Stores per-face stencil `value` for faces affected by `face` (`FRONT`, `BACK` or
+/// `FRONT_AND_BACK`), returns `true` if it differs from the previous one for any of them.
+fn update_faces<T: PartialEq + Copy>(current: &mut HashMap<u32, T>, face: u32, value: T) -> bool {
+    let faces: &[u32] = match face {
+        Gl::FRONT => &[Gl::FRONT],
+        Gl::BACK => &[Gl::BACK],
+        _ => &[Gl::FRONT, Gl::BACK],
+    };
+    let mut changed = false;
+    for face in faces {
+        if current.insert(*face, value) != Some(value) {
+            changed = true;
+        }
+    }
+    changed
+}
+
+/// `GlBackend` wrapper eliminating redundant state changes.
+///
+/// Tracks bound program, VAO, buffers, textures (per texture unit), samplers,
+/// framebuffers and fixed-function state, and skips calls which would not change
+/// anything - each WebGL call is a relatively expensive JS boundary crossing.
+/// All calls have to go through the wrapper, otherwise the shadow state gets stale.
+#[derive(Debug)]
+pub struct GlStateCache {
+    backend: Box<dyn GlBackend>,
+    state: RefCell<GlState>,
+}
+
+impl GlStateCache {
+    pub fn new(backend: Box<dyn GlBackend>) -> Self {
+        Self {
+            backend,
+            state: Default::default(),
+        }
+    }
+
+    /// Forgets cached state, should be called when GL state was changed bypassing the cache.
+    pub fn invalidate(&self) {
+        *self.state.borrow_mut() = Default::default();
+    }
+}
+
+impl GlBackend for GlStateCache {
+    fn get_parameter_i32(&self, pname: u32) -> i32 {
+        self.backend.get_parameter_i32(pname)
+    }
+
+    fn get_parameter_i32_array(&self, pname: u32) -> Vec<i32> {
+        self.backend.get_parameter_i32_array(pname)
+    }
+
+    fn get_parameter_string(&self, pname: u32) -> Option<String> {
+        self.backend.get_parameter_string(pname)
+    }
+
+    fn get_supported_extensions(&self) -> Vec<String> {
+        self.backend.get_supported_extensions()
+    }
+
+    fn enable_extension(&self, name: &str) -> bool {
+        self.backend.enable_extension(name)
+    }
+
+    fn enable(&self, cap: u32) {
+        if self.state.borrow_mut().capabilities.insert(cap, true) != Some(true) {
+            self.backend.enable(cap)
+        }
+    }
+
+    fn disable(&self, cap: u32) {
+        if self.state.borrow_mut().capabilities.insert(cap, false) != Some(false) {
+            self.backend.disable(cap)
+        }
+    }
+
+    fn viewport(&self, x: i32, y: i32, width: i32, height: i32) {
+        if update(&mut self.state.borrow_mut().viewport, [x, y, width, height]) {
+            self.backend.viewport(x, y, width, height)
+        }
+    }
+
+    fn scissor(&self, x: i32, y: i32, width: i32, height: i32) {
+        if update(&mut self.state.borrow_mut().scissor, [x, y, width, height]) {
+            self.backend.scissor(x, y, width, height)
+        }
+    }
+
+    fn cull_face(&self, mode: u32) {
+        if update(&mut self.state.borrow_mut().cull_face, mode) {
+            self.backend.cull_face(mode)
+        }
+    }
+
+    fn front_face(&self, mode: u32) {
+        if update(&mut self.state.borrow_mut().front_face, mode) {
+            self.backend.front_face(mode)
+        }
+    }
+
+    fn depth_func(&self, func: u32) {
+        if update(&mut self.state.borrow_mut().depth_func, func) {
+            self.backend.depth_func(func)
+        }
+    }
+
+    fn depth_mask(&self, flag: bool) {
+        if update(&mut self.state.borrow_mut().depth_mask, flag) {
+            self.backend.depth_mask(flag)
+        }
+    }
+
+    fn stencil_func_separate(&self, face: u32, func: u32, reference: i32, mask: u32) {
+        let value = (func, reference, mask);
+        if update_faces(&mut self.state.borrow_mut().stencil_funcs, face, value) {
+            self.backend
+                .stencil_func_separate(face, func, reference, mask)
+        }
+    }
+
+    fn stencil_op_separate(&self, face: u32, fail: u32, depth_fail: u32, pass: u32) {
+        let value = [fail, depth_fail, pass];
+        if update_faces(&mut self.state.borrow_mut().stencil_ops, face, value) {
+            self.backend
+                .stencil_op_separate(face, fail, depth_fail, pass)
+        }
+    }
+
+    fn stencil_mask_separate(&self, face: u32, mask: u32) {
+        if update_faces(&mut self.state.borrow_mut().stencil_masks, face, mask) {
+            self.backend.stencil_mask_separate(face, mask)
+        }
+    }
+
+    fn polygon_offset(&self, factor: f32, units: f32) {
+        if update(&mut self.state.borrow_mut().polygon_offset, [factor, units]) {
+            self.backend.polygon_offset(factor, units)
+        }
+    }
+
+    fn blend_func_separate(&self, src_rgb: u32, dst_rgb: u32, src_alpha: u32, dst_alpha: u32) {
+        if update(
+            &mut self.state.borrow_mut().blend_func,
+            [src_rgb, dst_rgb, src_alpha, dst_alpha],
+        ) {
+            self.backend
+                .blend_func_separate(src_rgb, dst_rgb, src_alpha, dst_alpha)
+        }
+    }
+
+    fn blend_equation_separate(&self, mode_rgb: u32, mode_alpha: u32) {
+        if update(
+            &mut self.state.borrow_mut().blend_equation,
+            [mode_rgb, mode_alpha],
+        ) {
+            self.backend.blend_equation_separate(mode_rgb, mode_alpha)
+        }
+    }
+
+    fn blend_color(&self, r: f32, g: f32, b: f32, a: f32) {
+        if update(&mut self.state.borrow_mut().blend_color, [r, g, b, a]) {
+            self.backend.blend_color(r, g, b, a)
+        }
+    }
+
+    fn color_mask(&self, r: bool, g: bool, b: bool, a: bool) {
+        if update(&mut self.state.borrow_mut().color_mask, [r, g, b, a]) {
+            self.backend.color_mask(r, g, b, a)
+        }
+    }
+
+    // indexed calls make state of draw buffers differ, so state set for all of them is unknown
+
+    fn enable_i(&self, cap: u32, index: u32) {
+        self.state.borrow_mut().capabilities.remove(&cap);
+        self.backend.enable_i(cap, index)
+    }
+
+    fn disable_i(&self, cap: u32, index: u32) {
+        self.state.borrow_mut().capabilities.remove(&cap);
+        self.backend.disable_i(cap, index)
+    }
+
+    fn blend_equation_separate_i(&self, buffer: u32, mode_rgb: u32, mode_alpha: u32) {
+        self.state.borrow_mut().blend_equation = None;
+        self.backend
+            .blend_equation_separate_i(buffer, mode_rgb, mode_alpha)
+    }
+
+    fn blend_func_separate_i(
+        &self,
+        buffer: u32,
+        src_rgb: u32,
+        dst_rgb: u32,
+        src_alpha: u32,
+        dst_alpha: u32,
+    ) {
+        self.state.borrow_mut().blend_func = None;
+        self.backend
+            .blend_func_separate_i(buffer, src_rgb, dst_rgb, src_alpha, dst_alpha)
+    }
+
+    fn color_mask_i(&self, buffer: u32, r: bool, g: bool, b: bool, a: bool) {
+        self.state.borrow_mut().color_mask = None;
+        self.backend.color_mask_i(buffer, r, g, b, a)
+    }
+
+    fn clear_color(&self, r: f32, g: f32, b: f32, a: f32) {
+        if update(&mut self.state.borrow_mut().clear_color, [r, g, b, a]) {
+            self.backend.clear_color(r, g, b, a)
+        }
+    }
+
+    fn clear_depth(&self, depth: f32) {
+        self.backend.clear_depth(depth)
+    }
+
+    fn clear(&self, mask: u32) {
+        self.backend.clear(mask)
+    }
+
+    fn clear_bufferfv(&self, buffer: u32, draw_buffer: i32, values: &[f32]) {
+        self.backend.clear_bufferfv(buffer, draw_buffer, values)
+    }
+
+    fn clear_bufferiv(&self, buffer: u32, draw_buffer: i32, values: &[i32]) {
+        self.backend.clear_bufferiv(buffer, draw_buffer, values)
+    }
+
+    fn clear_bufferuiv(&self, buffer: u32, draw_buffer: i32, values: &[u32]) {
+        self.backend.clear_bufferuiv(buffer, draw_buffer, values)
+    }
+
+    fn clear_bufferfi(&self, buffer: u32, draw_buffer: i32, depth: f32, stencil: i32) {
+        self.backend
+            .clear_bufferfi(buffer, draw_buffer, depth, stencil)
+    }
+
+    fn create_shader(&self, shader_type: u32) -> Option<NativeShader> {
+        self.backend.create_shader(shader_type)
+    }
+
+    fn shader_source(&self, shader: NativeShader, source: &str) {
+        self.backend.shader_source(shader, source)
+    }
+
+    fn compile_shader(&self, shader: NativeShader) {
+        self.backend.compile_shader(shader)
+    }
+
+    fn get_shader_compile_status(&self, shader: NativeShader) -> bool {
+        self.backend.get_shader_compile_status(shader)
+    }
+
+    fn get_shader_info_log(&self, shader: NativeShader) -> Option<String> {
+        self.backend.get_shader_info_log(shader)
+    }
+
+    fn create_program(&self) -> Option<NativeProgram> {
+        self.backend.create_program()
+    }
+
+    fn attach_shader(&self, program: NativeProgram, shader: NativeShader) {
+        self.backend.attach_shader(program, shader)
+    }
+
+    fn link_program(&self, program: NativeProgram) {
+        self.backend.link_program(program)
+    }
+
+    fn get_program_link_status(&self, program: NativeProgram) -> bool {
+        self.backend.get_program_link_status(program)
+    }
+
+    fn get_program_info_log(&self, program: NativeProgram) -> Option<String> {
+        self.backend.get_program_info_log(program)
+    }
+
+    fn get_program_parameter_i32(&self, program: NativeProgram, pname: u32) -> i32 {
+        self.backend.get_program_parameter_i32(program, pname)
+    }
+
+    fn use_program(&self, program: Option<NativeProgram>) {
+        if update(&mut self.state.borrow_mut().program, program) {
+            self.backend.use_program(program)
+        }
+    }
+
+    fn get_active_attrib(&self, program: NativeProgram, index: u32) -> Option<GlActiveInfo> {
+        self.backend.get_active_attrib(program, index)
+    }
+
+    fn get_active_uniform(&self, program: NativeProgram, index: u32) -> Option<GlActiveInfo> {
+        self.backend.get_active_uniform(program, index)
+    }
+
+    fn get_active_uniform_block_name(&self, program: NativeProgram, index: u32) -> Option<String> {
+        self.backend.get_active_uniform_block_name(program, index)
+    }
+
+    fn get_active_uniform_block_parameter_i32(
+        &self,
+        program: NativeProgram,
+        index: u32,
+        pname: u32,
+    ) -> i32 {
+        self.backend
+            .get_active_uniform_block_parameter_i32(program, index, pname)
+    }
+
+    fn get_attrib_location(&self, program: NativeProgram, name: &str) -> i32 {
+        self.backend.get_attrib_location(program, name)
+    }
+
+    fn get_uniform_block_index(&self, program: NativeProgram, name: &str) -> u32 {
+        self.backend.get_uniform_block_index(program, name)
+    }
+
+    fn get_uniform_location(
+        &self,
+        program: NativeProgram,
+        name: &str,
+    ) -> Option<NativeUniformLocation> {
+        self.backend.get_uniform_location(program, name)
+    }
+
+    fn uniform_block_binding(&self, program: NativeProgram, index: u32, binding: u32) {
+        self.backend.uniform_block_binding(program, index, binding)
+    }
+
+    fn uniform1i(&self, location: Option<NativeUniformLocation>, value: i32) {
+        self.backend.uniform1i(location, value)
+    }
+
+    fn create_buffer(&self) -> Option<NativeBuffer> {
+        self.backend.create_buffer()
+    }
+
+    fn delete_buffer(&self, buffer: NativeBuffer) {
+        let mut state = self.state.borrow_mut();
+        state.buffers.retain(|_, bound| *bound != Some(buffer));
+        state
+            .buffer_ranges
+            .retain(|_, (bound, _, _)| *bound != Some(buffer));
+        self.backend.delete_buffer(buffer)
+    }
+
+    fn bind_buffer(&self, target: u32, buffer: Option<NativeBuffer>) {
+        // ELEMENT_ARRAY_BUFFER binding is a part of VAO state, so it is not cached
+        if target == Gl::ELEMENT_ARRAY_BUFFER
+            || self.state.borrow_mut().buffers.insert(target, buffer) != Some(buffer)
+        {
+            self.backend.bind_buffer(target, buffer)
+        }
+    }
+
+    fn bind_buffer_range(
+        &self,
+        target: u32,
+        index: u32,
+        buffer: Option<NativeBuffer>,
+        offset: i32,
+        size: i32,
+    ) {
+        let mut state = self.state.borrow_mut();
+        // bindBufferRange changes generic binding point as well
+        state.buffers.insert(target, buffer);
+        let range = (buffer, offset, size);
+        if state.buffer_ranges.insert((target, index), range) != Some(range) {
+            self.backend
+                .bind_buffer_range(target, index, buffer, offset, size)
+        }
+    }
+
+    fn buffer_data_size(&self, target: u32, size: i32, usage: u32) {
+        self.backend.buffer_data_size(target, size, usage)
+    }
+
+    fn buffer_data_u8_slice(&self, target: u32, data: &[u8], usage: u32) {
+        self.backend.buffer_data_u8_slice(target, data, usage)
+    }
+
+    fn buffer_sub_data_u8_slice(&self, target: u32, offset: i32, data: &[u8]) {
+        self.backend.buffer_sub_data_u8_slice(target, offset, data)
+    }
+
+    fn get_buffer_sub_data(&self, target: u32, offset: i32, data: &mut [u8]) {
+        self.backend.get_buffer_sub_data(target, offset, data)
+    }
+
+    fn copy_buffer_sub_data(
+        &self,
+        read_target: u32,
+        write_target: u32,
+        read_offset: i32,
+        write_offset: i32,
+        size: i32,
+    ) {
+        self.backend.copy_buffer_sub_data(
+            read_target,
+            write_target,
+            read_offset,
+            write_offset,
+            size,
+        )
+    }
+
+    fn create_texture(&self) -> Option<NativeTexture> {
+        self.backend.create_texture()
+    }
+
+    fn delete_texture(&self, texture: NativeTexture) {
+        self.state
+            .borrow_mut()
+            .textures
+            .retain(|_, bound| *bound != Some(texture));
+        self.backend.delete_texture(texture)
+    }
+
+    fn active_texture(&self, unit: u32) {
+        if update(&mut self.state.borrow_mut().active_texture, unit) {
+            self.backend.active_texture(unit)
+        }
+    }
+
+    fn bind_texture(&self, target: u32, texture: Option<NativeTexture>) {
+        let mut state = self.state.borrow_mut();
+        let cached = match state.active_texture {
+            Some(unit) => state.textures.insert((unit, target), texture) == Some(texture),
+            None => false,
+        };
+        if !cached {
+            self.backend.bind_texture(target, texture)
+        }
+    }
+
+    fn tex_parameteri(&self, target: u32, pname: u32, param: i32) {
+        self.backend.tex_parameteri(target, pname, param)
+    }
+
+    fn pixel_storei(&self, pname: u32, param: i32) {
+        self.backend.pixel_storei(pname, param)
+    }
+
+    fn tex_image_2d(
+        &self,
+        target: u32,
+        level: i32,
+        internal_format: i32,
+        width: i32,
+        height: i32,
+        border: i32,
+        format: u32,
+        type_: u32,
+        pixels: Option<&[u8]>,
+    ) {
+        self.backend.tex_image_2d(
+            target,
+            level,
+            internal_format,
+            width,
+            height,
+            border,
+            format,
+            type_,
+            pixels,
+        )
+    }
+
+    fn tex_image_2d_with_pbo_offset(
+        &self,
+        target: u32,
+        level: i32,
+        internal_format: i32,
+        width: i32,
+        height: i32,
+        border: i32,
+        format: u32,
+        type_: u32,
+        offset: i32,
+    ) {
+        self.backend.tex_image_2d_with_pbo_offset(
+            target,
+            level,
+            internal_format,
+            width,
+            height,
+            border,
+            format,
+            type_,
+            offset,
+        )
+    }
+
+    fn compressed_tex_image_2d(
+        &self,
+        target: u32,
+        level: i32,
+        internal_format: u32,
+        width: i32,
+        height: i32,
+        border: i32,
+        data: &[u8],
+    ) {
+        self.backend.compressed_tex_image_2d(
+            target,
+            level,
+            internal_format,
+            width,
+            height,
+            border,
+            data,
+        )
+    }
+
+    fn compressed_tex_image_2d_with_pbo_offset(
+        &self,
+        target: u32,
+        level: i32,
+        internal_format: u32,
+        width: i32,
+        height: i32,
+        border: i32,
+        image_size: i32,
+        offset: i32,
+    ) {
+        self.backend.compressed_tex_image_2d_with_pbo_offset(
+            target,
+            level,
+            internal_format,
+            width,
+            height,
+            border,
+            image_size,
+            offset,
+        )
+    }
+
+    fn tex_storage_2d(
+        &self,
+        target: u32,
+        levels: i32,
+        internal_format: u32,
+        width: i32,
+        height: i32,
+    ) {
+        self.backend
+            .tex_storage_2d(target, levels, internal_format, width, height)
+    }
+
+    fn tex_sub_image_2d(
+        &self,
+        target: u32,
+        level: i32,
+        x_offset: i32,
+        y_offset: i32,
+        width: i32,
+        height: i32,
+        format: u32,
+        type_: u32,
+        pixels: &[u8],
+    ) {
+        self.backend.tex_sub_image_2d(
+            target, level, x_offset, y_offset, width, height, format, type_, pixels,
+        )
+    }
+
+    fn tex_sub_image_2d_with_pbo_offset(
+        &self,
+        target: u32,
+        level: i32,
+        x_offset: i32,
+        y_offset: i32,
+        width: i32,
+        height: i32,
+        format: u32,
+        type_: u32,
+        offset: i32,
+    ) {
+        self.backend.tex_sub_image_2d_with_pbo_offset(
+            target, level, x_offset, y_offset, width, height, format, type_, offset,
+        )
+    }
+
+    fn copy_tex_sub_image_2d(
+        &self,
+        target: u32,
+        level: i32,
+        x_offset: i32,
+        y_offset: i32,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+    ) {
+        self.backend
+            .copy_tex_sub_image_2d(target, level, x_offset, y_offset, x, y, width, height)
+    }
+
+    fn tex_storage_3d(
+        &self,
+        target: u32,
+        levels: i32,
+        internal_format: u32,
+        width: i32,
+        height: i32,
+        depth: i32,
+    ) {
+        self.backend
+            .tex_storage_3d(target, levels, internal_format, width, height, depth)
+    }
+
+    fn tex_sub_image_3d(
+        &self,
+        target: u32,
+        level: i32,
+        x_offset: i32,
+        y_offset: i32,
+        z_offset: i32,
+        width: i32,
+        height: i32,
+        depth: i32,
+        format: u32,
+        type_: u32,
+        pixels: &[u8],
+    ) {
+        self.backend.tex_sub_image_3d(
+            target, level, x_offset, y_offset, z_offset, width, height, depth, format, type_,
+            pixels,
+        )
+    }
+
+    fn tex_sub_image_3d_with_pbo_offset(
+        &self,
+        target: u32,
+        level: i32,
+        x_offset: i32,
+        y_offset: i32,
+        z_offset: i32,
+        width: i32,
+        height: i32,
+        depth: i32,
+        format: u32,
+        type_: u32,
+        offset: i32,
+    ) {
+        self.backend.tex_sub_image_3d_with_pbo_offset(
+            target, level, x_offset, y_offset, z_offset, width, height, depth, format, type_,
+            offset,
+        )
+    }
+
+    fn copy_tex_sub_image_3d(
+        &self,
+        target: u32,
+        level: i32,
+        x_offset: i32,
+        y_offset: i32,
+        z_offset: i32,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+    ) {
+        self.backend.copy_tex_sub_image_3d(
+            target, level, x_offset, y_offset, z_offset, x, y, width, height,
+        )
+    }
+
+    fn generate_mipmap(&self, target: u32) {
+        self.backend.generate_mipmap(target)
+    }
+
+    fn create_sampler(&self) -> Option<NativeSampler> {
+        self.backend.create_sampler()
+    }
+
+    fn delete_sampler(&self, sampler: NativeSampler) {
+        self.state
+            .borrow_mut()
+            .samplers
+            .retain(|_, bound| *bound != Some(sampler));
+        self.backend.delete_sampler(sampler)
+    }
+
+    fn bind_sampler(&self, unit: u32, sampler: Option<NativeSampler>) {
+        if self.state.borrow_mut().samplers.insert(unit, sampler) != Some(sampler) {
+            self.backend.bind_sampler(unit, sampler)
+        }
+    }
+
+    fn sampler_parameteri(&self, sampler: NativeSampler, pname: u32, param: i32) {
+        self.backend.sampler_parameteri(sampler, pname, param)
+    }
+
+    fn sampler_parameterf(&self, sampler: NativeSampler, pname: u32, param: f32) {
+        self.backend.sampler_parameterf(sampler, pname, param)
+    }
+
+    fn create_framebuffer(&self) -> Option<NativeFramebuffer> {
+        self.backend.create_framebuffer()
+    }
+
+    fn delete_framebuffer(&self, framebuffer: NativeFramebuffer) {
+        self.state
+            .borrow_mut()
+            .framebuffers
+            .retain(|_, bound| *bound != Some(framebuffer));
+        self.backend.delete_framebuffer(framebuffer)
+    }
+
+    fn bind_framebuffer(&self, target: u32, framebuffer: Option<NativeFramebuffer>) {
+        let mut state = self.state.borrow_mut();
+        // FRAMEBUFFER target binds both READ_FRAMEBUFFER and DRAW_FRAMEBUFFER
+        let changed = if target == Gl::FRAMEBUFFER {
+            let read = state.framebuffers.insert(Gl::READ_FRAMEBUFFER, framebuffer);
+            let draw = state.framebuffers.insert(Gl::DRAW_FRAMEBUFFER, framebuffer);
+            read != Some(framebuffer) || draw != Some(framebuffer)
+        } else {
+            state.framebuffers.insert(target, framebuffer) != Some(framebuffer)
+        };
+        if changed {
+            self.backend.bind_framebuffer(target, framebuffer)
+        }
+    }
+
+    fn framebuffer_texture_2d(
+        &self,
+        target: u32,
+        attachment: u32,
+        texture_target: u32,
+        texture: Option<NativeTexture>,
+        level: i32,
+    ) {
+        self.backend
+            .framebuffer_texture_2d(target, attachment, texture_target, texture, level)
+    }
+
+    fn framebuffer_texture_layer(
+        &self,
+        target: u32,
+        attachment: u32,
+        texture: Option<NativeTexture>,
+        level: i32,
+        layer: i32,
+    ) {
+        self.backend
+            .framebuffer_texture_layer(target, attachment, texture, level, layer)
+    }
+
+    fn check_framebuffer_status(&self, target: u32) -> u32 {
+        self.backend.check_framebuffer_status(target)
+    }
+
+    fn create_renderbuffer(&self) -> Option<NativeRenderbuffer> {
+        self.backend.create_renderbuffer()
+    }
+
+    fn delete_renderbuffer(&self, renderbuffer: NativeRenderbuffer) {
+        self.backend.delete_renderbuffer(renderbuffer)
+    }
+
+    fn bind_renderbuffer(&self, target: u32, renderbuffer: Option<NativeRenderbuffer>) {
+        self.backend.bind_renderbuffer(target, renderbuffer)
+    }
+
+    fn renderbuffer_storage_multisample(
+        &self,
+        target: u32,
+        samples: i32,
+        internal_format: u32,
+        width: i32,
+        height: i32,
+    ) {
+        self.backend.renderbuffer_storage_multisample(
+            target,
+            samples,
+            internal_format,
+            width,
+            height,
+        )
+    }
+
+    fn framebuffer_renderbuffer(
+        &self,
+        target: u32,
+        attachment: u32,
+        renderbuffer_target: u32,
+        renderbuffer: Option<NativeRenderbuffer>,
+    ) {
+        self.backend
+            .framebuffer_renderbuffer(target, attachment, renderbuffer_target, renderbuffer)
+    }
+
+    fn draw_buffers(&self, buffers: &[u32]) {
+        self.backend.draw_buffers(buffers)
+    }
+
+    fn read_buffer(&self, src: u32) {
+        self.backend.read_buffer(src)
+    }
+
+    fn blit_framebuffer(
+        &self,
+        src_x0: i32,
+        src_y0: i32,
+        src_x1: i32,
+        src_y1: i32,
+        dst_x0: i32,
+        dst_y0: i32,
+        dst_x1: i32,
+        dst_y1: i32,
+        mask: u32,
+        filter: u32,
+    ) {
+        self.backend.blit_framebuffer(
+            src_x0, src_y0, src_x1, src_y1, dst_x0, dst_y0, dst_x1, dst_y1, mask, filter,
+        )
+    }
+
+    fn read_pixels_with_pbo_offset(
+        &self,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        format: u32,
+        type_: u32,
+        offset: i32,
+    ) {
+        self.backend
+            .read_pixels_with_pbo_offset(x, y, width, height, format, type_, offset)
+    }
+
+    fn create_vertex_array(&self) -> Option<NativeVertexArray> {
+        self.backend.create_vertex_array()
+    }
+
+    fn bind_vertex_array(&self, vertex_array: Option<NativeVertexArray>) {
+        if update(&mut self.state.borrow_mut().vertex_array, vertex_array) {
+            self.backend.bind_vertex_array(vertex_array)
+        }
+    }
+
+    fn enable_vertex_attrib_array(&self, index: u32) {
+        self.backend.enable_vertex_attrib_array(index)
+    }
+
+    fn vertex_attrib_pointer_f32(
+        &self,
+        index: u32,
+        size: i32,
+        type_: u32,
+        normalized: bool,
+        stride: i32,
+        offset: i32,
+    ) {
+        self.backend
+            .vertex_attrib_pointer_f32(index, size, type_, normalized, stride, offset)
+    }
+
+    fn vertex_attrib_pointer_i32(
+        &self,
+        index: u32,
+        size: i32,
+        type_: u32,
+        stride: i32,
+        offset: i32,
+    ) {
+        self.backend
+            .vertex_attrib_pointer_i32(index, size, type_, stride, offset)
+    }
+
+    fn vertex_attrib_divisor(&self, index: u32, divisor: u32) {
+        self.backend.vertex_attrib_divisor(index, divisor)
+    }
+
+    fn draw_arrays(&self, mode: u32, first: i32, count: i32) {
+        self.backend.draw_arrays(mode, first, count)
+    }
+
+    fn draw_arrays_instanced(&self, mode: u32, first: i32, count: i32, instance_count: i32) {
+        self.backend
+            .draw_arrays_instanced(mode, first, count, instance_count)
+    }
+
+    fn draw_elements_instanced(
+        &self,
+        mode: u32,
+        count: i32,
+        type_: u32,
+        offset: i32,
+        instance_count: i32,
+    ) {
+        self.backend
+            .draw_elements_instanced(mode, count, type_, offset, instance_count)
+    }
+}
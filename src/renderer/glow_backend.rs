@@ -0,0 +1,1085 @@
+use super::{
+    Gl, GlActiveInfo, GlBackend, NativeBuffer, NativeFramebuffer, NativeProgram,
+    NativeRenderbuffer, NativeSampler, NativeShader, NativeTexture, NativeUniformLocation,
+    NativeVertexArray,
+};
+use glow::{HasContext, PixelPackData, PixelUnpackData};
+use glutin::{dpi::PhysicalSize, ContextBuilder, GlProfile, GlRequest, PossiblyCurrent};
+use std::num::NonZeroU32;
+
+/// `GlBackend` implementation driving a native OpenGL (ES) context through `glow`
+pub struct GlowBackend {
+    gl: glow::Context,
+    // keeps headless context alive (and current) as long as backend exists
+    _context: Option<glutin::Context<PossiblyCurrent>>,
+}
+
+impl GlowBackend {
+    pub fn new(gl: glow::Context) -> Self {
+        GlowBackend { gl, _context: None }
+    }
+
+    /// Creates offscreen context with OSMesa (e.g. Mesa's llvmpipe).
+    ///
+    /// OSMesa provides desktop OpenGL only, so 3.3 core profile is requested -
+    /// `#version 300 es` shaders are accepted through `ARB_ES3_compatibility`
+    pub fn headless(width: u32, height: u32) -> Result<Self, String> {
+        use glutin::platform::unix::HeadlessContextExt;
+
+        let context = ContextBuilder::new()
+            .with_gl(GlRequest::GlThenGles {
+                opengl_version: (3, 3),
+                opengles_version: (3, 0),
+            })
+            .with_gl_profile(GlProfile::Core)
+            .build_osmesa(PhysicalSize::new(width, height))
+            .map_err(|err| format!("can't create headless context: {:?}", err))?;
+        let context = unsafe { context.make_current() }
+            .map_err(|(_, err)| format!("can't make context current: {:?}", err))?;
+        let gl = unsafe {
+            glow::Context::from_loader_function(|name| context.get_proc_address(name) as *const _)
+        };
+        Ok(GlowBackend {
+            gl,
+            _context: Some(context),
+        })
+    }
+
+    pub fn context(&self) -> &glow::Context {
+        &self.gl
+    }
+}
+
+impl std::fmt::Debug for GlowBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("GlowBackend")
+    }
+}
+
+macro_rules! glow_handle {
+    ($fn_name:ident, $handle:ident) => {
+        fn $fn_name(handle: $handle) -> glow::$handle {
+            glow::$handle(NonZeroU32::new(handle.0).expect("valid GL object"))
+        }
+    };
+}
+
+glow_handle!(buffer, NativeBuffer);
+glow_handle!(texture, NativeTexture);
+glow_handle!(framebuffer, NativeFramebuffer);
+glow_handle!(renderbuffer, NativeRenderbuffer);
+glow_handle!(program, NativeProgram);
+glow_handle!(shader, NativeShader);
+glow_handle!(vertex_array, NativeVertexArray);
+glow_handle!(sampler, NativeSampler);
+
+impl GlBackend for GlowBackend {
+    fn get_parameter_i32(&self, pname: u32) -> i32 {
+        unsafe { self.gl.get_parameter_i32(pname) }
+    }
+
+    fn get_parameter_i32_array(&self, pname: u32) -> Vec<i32> {
+        let len = match pname {
+            Gl::VIEWPORT | Gl::SCISSOR_BOX => 4,
+            Gl::MAX_VIEWPORT_DIMS => 2,
+            _ => 1,
+        };
+        let mut value = vec![0; len];
+        unsafe { self.gl.get_parameter_i32_slice(pname, &mut value) };
+        value
+    }
+
+    fn get_parameter_string(&self, pname: u32) -> Option<String> {
+        Some(unsafe { self.gl.get_parameter_string(pname) })
+    }
+
+    fn get_supported_extensions(&self) -> Vec<String> {
+        let native = self.gl.supported_extensions();
+        // WebGL names are native names without `GL_` prefix, apart from
+        // compressed texture extensions
+        let mut extensions: Vec<String> = native
+            .iter()
+            .map(|name| name.trim_start_matches("GL_").to_string())
+            .collect();
+        let aliases = [
+            (
+                "WEBGL_compressed_texture_s3tc",
+                "GL_EXT_texture_compression_s3tc",
+            ),
+            (
+                "WEBGL_compressed_texture_s3tc_srgb",
+                "GL_EXT_texture_compression_s3tc_srgb",
+            ),
+            (
+                "WEBGL_compressed_texture_astc",
+                "GL_KHR_texture_compression_astc_ldr",
+            ),
+        ];
+        for (webgl_name, native_name) in aliases.iter() {
+            if native.contains(*native_name) {
+                extensions.push(webgl_name.to_string());
+            }
+        }
+        // ETC2 is part of core OpenGL ES 3.0
+        extensions.push("WEBGL_compressed_texture_etc".to_string());
+        extensions
+    }
+
+    fn enable_extension(&self, name: &str) -> bool {
+        // native extensions are always enabled
+        self.get_supported_extensions()
+            .iter()
+            .any(|extension| extension == name)
+    }
+
+    fn enable(&self, cap: u32) {
+        unsafe { self.gl.enable(cap) }
+    }
+
+    fn disable(&self, cap: u32) {
+        unsafe { self.gl.disable(cap) }
+    }
+
+    fn viewport(&self, x: i32, y: i32, width: i32, height: i32) {
+        unsafe { self.gl.viewport(x, y, width, height) }
+    }
+
+    fn scissor(&self, x: i32, y: i32, width: i32, height: i32) {
+        unsafe { self.gl.scissor(x, y, width, height) }
+    }
+
+    fn cull_face(&self, mode: u32) {
+        unsafe { self.gl.cull_face(mode) }
+    }
+
+    fn front_face(&self, mode: u32) {
+        unsafe { self.gl.front_face(mode) }
+    }
+
+    fn depth_func(&self, func: u32) {
+        unsafe { self.gl.depth_func(func) }
+    }
+
+    fn depth_mask(&self, flag: bool) {
+        unsafe { self.gl.depth_mask(flag) }
+    }
+
+    fn stencil_func_separate(&self, face: u32, func: u32, reference: i32, mask: u32) {
+        unsafe { self.gl.stencil_func_separate(face, func, reference, mask) }
+    }
+
+    fn stencil_op_separate(&self, face: u32, fail: u32, depth_fail: u32, pass: u32) {
+        unsafe { self.gl.stencil_op_separate(face, fail, depth_fail, pass) }
+    }
+
+    fn stencil_mask_separate(&self, face: u32, mask: u32) {
+        unsafe { self.gl.stencil_mask_separate(face, mask) }
+    }
+
+    fn polygon_offset(&self, factor: f32, units: f32) {
+        unsafe { self.gl.polygon_offset(factor, units) }
+    }
+
+    fn blend_func_separate(&self, src_rgb: u32, dst_rgb: u32, src_alpha: u32, dst_alpha: u32) {
+        unsafe {
+            self.gl
+                .blend_func_separate(src_rgb, dst_rgb, src_alpha, dst_alpha)
+        }
+    }
+
+    fn blend_equation_separate(&self, mode_rgb: u32, mode_alpha: u32) {
+        unsafe { self.gl.blend_equation_separate(mode_rgb, mode_alpha) }
+    }
+
+    fn blend_color(&self, r: f32, g: f32, b: f32, a: f32) {
+        unsafe { self.gl.blend_color(r, g, b, a) }
+    }
+
+    fn color_mask(&self, r: bool, g: bool, b: bool, a: bool) {
+        unsafe { self.gl.color_mask(r, g, b, a) }
+    }
+
+    fn enable_i(&self, cap: u32, index: u32) {
+        unsafe { self.gl.enable_draw_buffer(cap, index) }
+    }
+
+    fn disable_i(&self, cap: u32, index: u32) {
+        unsafe { self.gl.disable_draw_buffer(cap, index) }
+    }
+
+    fn blend_equation_separate_i(&self, buffer: u32, mode_rgb: u32, mode_alpha: u32) {
+        unsafe {
+            self.gl
+                .blend_equation_separate_draw_buffer(buffer, mode_rgb, mode_alpha)
+        }
+    }
+
+    fn blend_func_separate_i(
+        &self,
+        buffer: u32,
+        src_rgb: u32,
+        dst_rgb: u32,
+        src_alpha: u32,
+        dst_alpha: u32,
+    ) {
+        unsafe {
+            self.gl
+                .blend_func_separate_draw_buffer(buffer, src_rgb, dst_rgb, src_alpha, dst_alpha)
+        }
+    }
+
+    fn color_mask_i(&self, buffer: u32, r: bool, g: bool, b: bool, a: bool) {
+        unsafe { self.gl.color_mask_draw_buffer(buffer, r, g, b, a) }
+    }
+
+    fn clear_color(&self, r: f32, g: f32, b: f32, a: f32) {
+        unsafe { self.gl.clear_color(r, g, b, a) }
+    }
+
+    fn clear_depth(&self, depth: f32) {
+        unsafe { self.gl.clear_depth_f32(depth) }
+    }
+
+    fn clear(&self, mask: u32) {
+        unsafe { self.gl.clear(mask) }
+    }
+
+    fn clear_bufferfv(&self, buffer: u32, draw_buffer: i32, values: &[f32]) {
+        unsafe {
+            self.gl
+                .clear_buffer_f32_slice(buffer, draw_buffer as u32, values)
+        }
+    }
+
+    fn clear_bufferiv(&self, buffer: u32, draw_buffer: i32, values: &[i32]) {
+        unsafe {
+            self.gl
+                .clear_buffer_i32_slice(buffer, draw_buffer as u32, values)
+        }
+    }
+
+    fn clear_bufferuiv(&self, buffer: u32, draw_buffer: i32, values: &[u32]) {
+        unsafe {
+            self.gl
+                .clear_buffer_u32_slice(buffer, draw_buffer as u32, values)
+        }
+    }
+
+    fn clear_bufferfi(&self, buffer: u32, draw_buffer: i32, depth: f32, stencil: i32) {
+        unsafe {
+            self.gl
+                .clear_buffer_depth_stencil(buffer, draw_buffer as u32, depth, stencil)
+        }
+    }
+
+    fn create_shader(&self, shader_type: u32) -> Option<NativeShader> {
+        unsafe { self.gl.create_shader(shader_type) }
+            .ok()
+            .map(|shader| NativeShader(shader.0.get()))
+    }
+
+    fn shader_source(&self, native_shader: NativeShader, source: &str) {
+        unsafe { self.gl.shader_source(shader(native_shader), source) }
+    }
+
+    fn compile_shader(&self, native_shader: NativeShader) {
+        unsafe { self.gl.compile_shader(shader(native_shader)) }
+    }
+
+    fn get_shader_compile_status(&self, native_shader: NativeShader) -> bool {
+        unsafe { self.gl.get_shader_compile_status(shader(native_shader)) }
+    }
+
+    fn get_shader_info_log(&self, native_shader: NativeShader) -> Option<String> {
+        Some(unsafe { self.gl.get_shader_info_log(shader(native_shader)) })
+    }
+
+    fn create_program(&self) -> Option<NativeProgram> {
+        unsafe { self.gl.create_program() }
+            .ok()
+            .map(|program| NativeProgram(program.0.get()))
+    }
+
+    fn attach_shader(&self, native_program: NativeProgram, native_shader: NativeShader) {
+        unsafe {
+            self.gl
+                .attach_shader(program(native_program), shader(native_shader))
+        }
+    }
+
+    fn link_program(&self, native_program: NativeProgram) {
+        unsafe { self.gl.link_program(program(native_program)) }
+    }
+
+    fn get_program_link_status(&self, native_program: NativeProgram) -> bool {
+        unsafe { self.gl.get_program_link_status(program(native_program)) }
+    }
+
+    fn get_program_info_log(&self, native_program: NativeProgram) -> Option<String> {
+        Some(unsafe { self.gl.get_program_info_log(program(native_program)) })
+    }
+
+    fn get_program_parameter_i32(&self, native_program: NativeProgram, pname: u32) -> i32 {
+        unsafe {
+            self.gl
+                .get_program_parameter_i32(program(native_program), pname)
+        }
+    }
+
+    fn use_program(&self, native_program: Option<NativeProgram>) {
+        unsafe { self.gl.use_program(native_program.map(program)) }
+    }
+
+    fn get_active_attrib(&self, native_program: NativeProgram, index: u32) -> Option<GlActiveInfo> {
+        unsafe { self.gl.get_active_attribute(program(native_program), index) }.map(|info| {
+            GlActiveInfo {
+                name: info.name,
+                size: info.size,
+                type_: info.atype,
+            }
+        })
+    }
+
+    fn get_active_uniform(
+        &self,
+        native_program: NativeProgram,
+        index: u32,
+    ) -> Option<GlActiveInfo> {
+        unsafe { self.gl.get_active_uniform(program(native_program), index) }.map(|info| {
+            GlActiveInfo {
+                name: info.name,
+                size: info.size,
+                type_: info.utype,
+            }
+        })
+    }
+
+    fn get_active_uniform_block_name(
+        &self,
+        native_program: NativeProgram,
+        index: u32,
+    ) -> Option<String> {
+        Some(unsafe {
+            self.gl
+                .get_active_uniform_block_name(program(native_program), index)
+        })
+    }
+
+    fn get_active_uniform_block_parameter_i32(
+        &self,
+        native_program: NativeProgram,
+        index: u32,
+        pname: u32,
+    ) -> i32 {
+        unsafe {
+            self.gl
+                .get_active_uniform_block_parameter_i32(program(native_program), index, pname)
+        }
+    }
+
+    fn get_attrib_location(&self, native_program: NativeProgram, name: &str) -> i32 {
+        unsafe { self.gl.get_attrib_location(program(native_program), name) }
+            .map(|location| location as i32)
+            .unwrap_or(-1)
+    }
+
+    fn get_uniform_block_index(&self, native_program: NativeProgram, name: &str) -> u32 {
+        unsafe {
+            self.gl
+                .get_uniform_block_index(program(native_program), name)
+        }
+        .unwrap_or(Gl::INVALID_INDEX)
+    }
+
+    fn get_uniform_location(
+        &self,
+        native_program: NativeProgram,
+        name: &str,
+    ) -> Option<NativeUniformLocation> {
+        unsafe { self.gl.get_uniform_location(program(native_program), name) }
+            .map(|location| NativeUniformLocation(location.0))
+    }
+
+    fn uniform_block_binding(&self, native_program: NativeProgram, index: u32, binding: u32) {
+        unsafe {
+            self.gl
+                .uniform_block_binding(program(native_program), index, binding)
+        }
+    }
+
+    fn uniform1i(&self, location: Option<NativeUniformLocation>, value: i32) {
+        let location = location.map(|location| glow::NativeUniformLocation(location.0));
+        unsafe { self.gl.uniform_1_i32(location.as_ref(), value) }
+    }
+
+    fn create_buffer(&self) -> Option<NativeBuffer> {
+        unsafe { self.gl.create_buffer() }
+            .ok()
+            .map(|buffer| NativeBuffer(buffer.0.get()))
+    }
+
+    fn delete_buffer(&self, native_buffer: NativeBuffer) {
+        unsafe { self.gl.delete_buffer(buffer(native_buffer)) }
+    }
+
+    fn bind_buffer(&self, target: u32, native_buffer: Option<NativeBuffer>) {
+        unsafe { self.gl.bind_buffer(target, native_buffer.map(buffer)) }
+    }
+
+    fn bind_buffer_range(
+        &self,
+        target: u32,
+        index: u32,
+        native_buffer: Option<NativeBuffer>,
+        offset: i32,
+        size: i32,
+    ) {
+        unsafe {
+            self.gl
+                .bind_buffer_range(target, index, native_buffer.map(buffer), offset, size)
+        }
+    }
+
+    fn buffer_data_size(&self, target: u32, size: i32, usage: u32) {
+        unsafe { self.gl.buffer_data_size(target, size, usage) }
+    }
+
+    fn buffer_data_u8_slice(&self, target: u32, data: &[u8], usage: u32) {
+        unsafe { self.gl.buffer_data_u8_slice(target, data, usage) }
+    }
+
+    fn buffer_sub_data_u8_slice(&self, target: u32, offset: i32, data: &[u8]) {
+        unsafe { self.gl.buffer_sub_data_u8_slice(target, offset, data) }
+    }
+
+    fn get_buffer_sub_data(&self, target: u32, offset: i32, data: &mut [u8]) {
+        unsafe { self.gl.get_buffer_sub_data(target, offset, data) }
+    }
+
+    fn copy_buffer_sub_data(
+        &self,
+        read_target: u32,
+        write_target: u32,
+        read_offset: i32,
+        write_offset: i32,
+        size: i32,
+    ) {
+        unsafe {
+            self.gl
+                .copy_buffer_sub_data(read_target, write_target, read_offset, write_offset, size)
+        }
+    }
+
+    fn create_texture(&self) -> Option<NativeTexture> {
+        unsafe { self.gl.create_texture() }
+            .ok()
+            .map(|texture| NativeTexture(texture.0.get()))
+    }
+
+    fn delete_texture(&self, native_texture: NativeTexture) {
+        unsafe { self.gl.delete_texture(texture(native_texture)) }
+    }
+
+    fn active_texture(&self, unit: u32) {
+        unsafe { self.gl.active_texture(unit) }
+    }
+
+    fn bind_texture(&self, target: u32, native_texture: Option<NativeTexture>) {
+        unsafe { self.gl.bind_texture(target, native_texture.map(texture)) }
+    }
+
+    fn tex_parameteri(&self, target: u32, pname: u32, param: i32) {
+        unsafe { self.gl.tex_parameter_i32(target, pname, param) }
+    }
+
+    fn pixel_storei(&self, pname: u32, param: i32) {
+        unsafe { self.gl.pixel_store_i32(pname, param) }
+    }
+
+    fn tex_image_2d(
+        &self,
+        target: u32,
+        level: i32,
+        internal_format: i32,
+        width: i32,
+        height: i32,
+        border: i32,
+        format: u32,
+        type_: u32,
+        pixels: Option<&[u8]>,
+    ) {
+        unsafe {
+            self.gl.tex_image_2d(
+                target,
+                level,
+                internal_format,
+                width,
+                height,
+                border,
+                format,
+                type_,
+                pixels,
+            )
+        }
+    }
+
+    fn tex_image_2d_with_pbo_offset(
+        &self,
+        target: u32,
+        level: i32,
+        internal_format: i32,
+        width: i32,
+        height: i32,
+        border: i32,
+        format: u32,
+        type_: u32,
+        offset: i32,
+    ) {
+        // glow can't pass buffer offset to texImage2D, so image is specified
+        // first and filled from PIXEL_UNPACK_BUFFER with texSubImage2D
+        unsafe {
+            let unpack_buffer = self.gl.get_parameter_i32(Gl::PIXEL_UNPACK_BUFFER_BINDING);
+            self.gl.bind_buffer(Gl::PIXEL_UNPACK_BUFFER, None);
+            self.gl.tex_image_2d(
+                target,
+                level,
+                internal_format,
+                width,
+                height,
+                border,
+                format,
+                type_,
+                None,
+            );
+            self.gl.bind_buffer(
+                Gl::PIXEL_UNPACK_BUFFER,
+                NonZeroU32::new(unpack_buffer as u32).map(glow::NativeBuffer),
+            );
+            self.gl.tex_sub_image_2d(
+                target,
+                level,
+                0,
+                0,
+                width,
+                height,
+                format,
+                type_,
+                PixelUnpackData::BufferOffset(offset as u32),
+            );
+        }
+    }
+
+    fn compressed_tex_image_2d(
+        &self,
+        target: u32,
+        level: i32,
+        internal_format: u32,
+        width: i32,
+        height: i32,
+        border: i32,
+        data: &[u8],
+    ) {
+        unsafe {
+            self.gl.compressed_tex_image_2d(
+                target,
+                level,
+                internal_format as i32,
+                width,
+                height,
+                border,
+                data.len() as i32,
+                data,
+            )
+        }
+    }
+
+    fn compressed_tex_image_2d_with_pbo_offset(
+        &self,
+        target: u32,
+        level: i32,
+        internal_format: u32,
+        width: i32,
+        height: i32,
+        border: i32,
+        image_size: i32,
+        offset: i32,
+    ) {
+        // glow can't pass buffer offset to compressedTexImage2D,
+        // so data is read back from PIXEL_UNPACK_BUFFER first
+        let mut data = vec![0; image_size as usize];
+        unsafe {
+            self.gl
+                .get_buffer_sub_data(Gl::PIXEL_UNPACK_BUFFER, offset, &mut data);
+            let unpack_buffer = self.gl.get_parameter_i32(Gl::PIXEL_UNPACK_BUFFER_BINDING);
+            self.gl.bind_buffer(Gl::PIXEL_UNPACK_BUFFER, None);
+            self.gl.compressed_tex_image_2d(
+                target,
+                level,
+                internal_format as i32,
+                width,
+                height,
+                border,
+                image_size,
+                &data,
+            );
+            self.gl.bind_buffer(
+                Gl::PIXEL_UNPACK_BUFFER,
+                NonZeroU32::new(unpack_buffer as u32).map(glow::NativeBuffer),
+            );
+        }
+    }
+
+    fn tex_storage_2d(
+        &self,
+        target: u32,
+        levels: i32,
+        internal_format: u32,
+        width: i32,
+        height: i32,
+    ) {
+        unsafe {
+            self.gl
+                .tex_storage_2d(target, levels, internal_format, width, height)
+        }
+    }
+
+    fn tex_sub_image_2d(
+        &self,
+        target: u32,
+        level: i32,
+        x_offset: i32,
+        y_offset: i32,
+        width: i32,
+        height: i32,
+        format: u32,
+        type_: u32,
+        pixels: &[u8],
+    ) {
+        unsafe {
+            self.gl.tex_sub_image_2d(
+                target,
+                level,
+                x_offset,
+                y_offset,
+                width,
+                height,
+                format,
+                type_,
+                PixelUnpackData::Slice(pixels),
+            )
+        }
+    }
+
+    fn tex_sub_image_2d_with_pbo_offset(
+        &self,
+        target: u32,
+        level: i32,
+        x_offset: i32,
+        y_offset: i32,
+        width: i32,
+        height: i32,
+        format: u32,
+        type_: u32,
+        offset: i32,
+    ) {
+        unsafe {
+            self.gl.tex_sub_image_2d(
+                target,
+                level,
+                x_offset,
+                y_offset,
+                width,
+                height,
+                format,
+                type_,
+                PixelUnpackData::BufferOffset(offset as u32),
+            )
+        }
+    }
+
+    fn copy_tex_sub_image_2d(
+        &self,
+        target: u32,
+        level: i32,
+        x_offset: i32,
+        y_offset: i32,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+    ) {
+        unsafe {
+            self.gl
+                .copy_tex_sub_image_2d(target, level, x_offset, y_offset, x, y, width, height)
+        }
+    }
+
+    fn tex_storage_3d(
+        &self,
+        target: u32,
+        levels: i32,
+        internal_format: u32,
+        width: i32,
+        height: i32,
+        depth: i32,
+    ) {
+        unsafe {
+            self.gl
+                .tex_storage_3d(target, levels, internal_format, width, height, depth)
+        }
+    }
+
+    fn tex_sub_image_3d(
+        &self,
+        target: u32,
+        level: i32,
+        x_offset: i32,
+        y_offset: i32,
+        z_offset: i32,
+        width: i32,
+        height: i32,
+        depth: i32,
+        format: u32,
+        type_: u32,
+        pixels: &[u8],
+    ) {
+        unsafe {
+            self.gl.tex_sub_image_3d(
+                target,
+                level,
+                x_offset,
+                y_offset,
+                z_offset,
+                width,
+                height,
+                depth,
+                format,
+                type_,
+                PixelUnpackData::Slice(pixels),
+            )
+        }
+    }
+
+    fn tex_sub_image_3d_with_pbo_offset(
+        &self,
+        target: u32,
+        level: i32,
+        x_offset: i32,
+        y_offset: i32,
+        z_offset: i32,
+        width: i32,
+        height: i32,
+        depth: i32,
+        format: u32,
+        type_: u32,
+        offset: i32,
+    ) {
+        unsafe {
+            self.gl.tex_sub_image_3d(
+                target,
+                level,
+                x_offset,
+                y_offset,
+                z_offset,
+                width,
+                height,
+                depth,
+                format,
+                type_,
+                PixelUnpackData::BufferOffset(offset as u32),
+            )
+        }
+    }
+
+    fn copy_tex_sub_image_3d(
+        &self,
+        target: u32,
+        level: i32,
+        x_offset: i32,
+        y_offset: i32,
+        z_offset: i32,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+    ) {
+        unsafe {
+            self.gl.copy_tex_sub_image_3d(
+                target, level, x_offset, y_offset, z_offset, x, y, width, height,
+            )
+        }
+    }
+
+    fn generate_mipmap(&self, target: u32) {
+        unsafe { self.gl.generate_mipmap(target) }
+    }
+
+    fn create_sampler(&self) -> Option<NativeSampler> {
+        unsafe { self.gl.create_sampler() }
+            .ok()
+            .map(|sampler| NativeSampler(sampler.0.get()))
+    }
+
+    fn delete_sampler(&self, native_sampler: NativeSampler) {
+        unsafe { self.gl.delete_sampler(sampler(native_sampler)) }
+    }
+
+    fn bind_sampler(&self, unit: u32, native_sampler: Option<NativeSampler>) {
+        unsafe { self.gl.bind_sampler(unit, native_sampler.map(sampler)) }
+    }
+
+    fn sampler_parameteri(&self, native_sampler: NativeSampler, pname: u32, param: i32) {
+        unsafe {
+            self.gl
+                .sampler_parameter_i32(sampler(native_sampler), pname, param)
+        }
+    }
+
+    fn sampler_parameterf(&self, native_sampler: NativeSampler, pname: u32, param: f32) {
+        unsafe {
+            self.gl
+                .sampler_parameter_f32(sampler(native_sampler), pname, param)
+        }
+    }
+
+    fn create_framebuffer(&self) -> Option<NativeFramebuffer> {
+        unsafe { self.gl.create_framebuffer() }
+            .ok()
+            .map(|framebuffer| NativeFramebuffer(framebuffer.0.get()))
+    }
+
+    fn delete_framebuffer(&self, native_framebuffer: NativeFramebuffer) {
+        unsafe { self.gl.delete_framebuffer(framebuffer(native_framebuffer)) }
+    }
+
+    fn bind_framebuffer(&self, target: u32, native_framebuffer: Option<NativeFramebuffer>) {
+        unsafe {
+            self.gl
+                .bind_framebuffer(target, native_framebuffer.map(framebuffer))
+        }
+    }
+
+    fn framebuffer_texture_2d(
+        &self,
+        target: u32,
+        attachment: u32,
+        texture_target: u32,
+        native_texture: Option<NativeTexture>,
+        level: i32,
+    ) {
+        unsafe {
+            self.gl.framebuffer_texture_2d(
+                target,
+                attachment,
+                texture_target,
+                native_texture.map(texture),
+                level,
+            )
+        }
+    }
+
+    fn framebuffer_texture_layer(
+        &self,
+        target: u32,
+        attachment: u32,
+        native_texture: Option<NativeTexture>,
+        level: i32,
+        layer: i32,
+    ) {
+        unsafe {
+            self.gl.framebuffer_texture_layer(
+                target,
+                attachment,
+                native_texture.map(texture),
+                level,
+                layer,
+            )
+        }
+    }
+
+    fn check_framebuffer_status(&self, target: u32) -> u32 {
+        unsafe { self.gl.check_framebuffer_status(target) }
+    }
+
+    fn create_renderbuffer(&self) -> Option<NativeRenderbuffer> {
+        unsafe { self.gl.create_renderbuffer() }
+            .ok()
+            .map(|renderbuffer| NativeRenderbuffer(renderbuffer.0.get()))
+    }
+
+    fn delete_renderbuffer(&self, native_renderbuffer: NativeRenderbuffer) {
+        unsafe {
+            self.gl
+                .delete_renderbuffer(renderbuffer(native_renderbuffer))
+        }
+    }
+
+    fn bind_renderbuffer(&self, target: u32, native_renderbuffer: Option<NativeRenderbuffer>) {
+        unsafe {
+            self.gl
+                .bind_renderbuffer(target, native_renderbuffer.map(renderbuffer))
+        }
+    }
+
+    fn renderbuffer_storage_multisample(
+        &self,
+        target: u32,
+        samples: i32,
+        internal_format: u32,
+        width: i32,
+        height: i32,
+    ) {
+        unsafe {
+            self.gl.renderbuffer_storage_multisample(
+                target,
+                samples,
+                internal_format,
+                width,
+                height,
+            )
+        }
+    }
+
+    fn framebuffer_renderbuffer(
+        &self,
+        target: u32,
+        attachment: u32,
+        renderbuffer_target: u32,
+        native_renderbuffer: Option<NativeRenderbuffer>,
+    ) {
+        unsafe {
+            self.gl.framebuffer_renderbuffer(
+                target,
+                attachment,
+                renderbuffer_target,
+                native_renderbuffer.map(renderbuffer),
+            )
+        }
+    }
+
+    fn draw_buffers(&self, buffers: &[u32]) {
+        unsafe { self.gl.draw_buffers(buffers) }
+    }
+
+    fn blit_framebuffer(
+        &self,
+        src_x0: i32,
+        src_y0: i32,
+        src_x1: i32,
+        src_y1: i32,
+        dst_x0: i32,
+        dst_y0: i32,
+        dst_x1: i32,
+        dst_y1: i32,
+        mask: u32,
+        filter: u32,
+    ) {
+        unsafe {
+            self.gl.blit_framebuffer(
+                src_x0, src_y0, src_x1, src_y1, dst_x0, dst_y0, dst_x1, dst_y1, mask, filter,
+            )
+        }
+    }
+
+    fn read_buffer(&self, src: u32) {
+        unsafe { self.gl.read_buffer(src) }
+    }
+
+    fn read_pixels_with_pbo_offset(
+        &self,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        format: u32,
+        type_: u32,
+        offset: i32,
+    ) {
+        unsafe {
+            self.gl.read_pixels(
+                x,
+                y,
+                width,
+                height,
+                format,
+                type_,
+                PixelPackData::BufferOffset(offset as u32),
+            )
+        }
+    }
+
+    fn create_vertex_array(&self) -> Option<NativeVertexArray> {
+        unsafe { self.gl.create_vertex_array() }
+            .ok()
+            .map(|vertex_array| NativeVertexArray(vertex_array.0.get()))
+    }
+
+    fn bind_vertex_array(&self, native_vertex_array: Option<NativeVertexArray>) {
+        unsafe {
+            self.gl
+                .bind_vertex_array(native_vertex_array.map(vertex_array))
+        }
+    }
+
+    fn enable_vertex_attrib_array(&self, index: u32) {
+        unsafe { self.gl.enable_vertex_attrib_array(index) }
+    }
+
+    fn vertex_attrib_pointer_f32(
+        &self,
+        index: u32,
+        size: i32,
+        type_: u32,
+        normalized: bool,
+        stride: i32,
+        offset: i32,
+    ) {
+        unsafe {
+            self.gl
+                .vertex_attrib_pointer_f32(index, size, type_, normalized, stride, offset)
+        }
+    }
+
+    fn vertex_attrib_pointer_i32(
+        &self,
+        index: u32,
+        size: i32,
+        type_: u32,
+        stride: i32,
+        offset: i32,
+    ) {
+        unsafe {
+            self.gl
+                .vertex_attrib_pointer_i32(index, size, type_, stride, offset)
+        }
+    }
+
+    fn vertex_attrib_divisor(&self, index: u32, divisor: u32) {
+        unsafe { self.gl.vertex_attrib_divisor(index, divisor) }
+    }
+
+    fn draw_arrays(&self, mode: u32, first: i32, count: i32) {
+        unsafe { self.gl.draw_arrays(mode, first, count) }
+    }
+
+    fn draw_arrays_instanced(&self, mode: u32, first: i32, count: i32, instance_count: i32) {
+        unsafe {
+            self.gl
+                .draw_arrays_instanced(mode, first, count, instance_count)
+        }
+    }
+
+    fn draw_elements_instanced(
+        &self,
+        mode: u32,
+        count: i32,
+        type_: u32,
+        offset: i32,
+        instance_count: i32,
+    ) {
+        unsafe {
+            self.gl
+                .draw_elements_instanced(mode, count, type_, offset, instance_count)
+        }
+    }
+}
@@ -1,18 +1,29 @@
+mod gl_backend;
+mod gl_state_cache;
+#[cfg(all(feature = "native", not(target_arch = "wasm32")))]
+mod glow_backend;
+mod recording_backend;
+#[cfg(test)]
+mod tests;
 mod webgl2_render_context;
 //mod webgl2_render_graph_executor;
 mod utils;
 mod webgl2_render_resource_context;
+mod webgl_backend;
 
+pub use gl_backend::*;
+pub use gl_state_cache::*;
+#[cfg(all(feature = "native", not(target_arch = "wasm32")))]
+pub use glow_backend::*;
+pub use recording_backend::*;
 pub use webgl2_render_context::*;
 //pub use webgl2_render_graph_executor::*;
 pub use webgl2_render_resource_context::*;
+pub use webgl_backend::*;
 
 pub use js_sys;
 pub use wasm_bindgen::JsCast;
-pub use web_sys::{
-    WebGl2RenderingContext, WebGlBuffer, WebGlFramebuffer, WebGlProgram, WebGlShader, WebGlSync,
-    WebGlTexture, WebGlUniformLocation, WebGlVertexArrayObject,
-};
+pub use web_sys::WebGl2RenderingContext;
 
 pub type Gl = WebGl2RenderingContext;
 
@@ -0,0 +1,974 @@
+use super::Gl;
+use super::{
+    GlActiveInfo, GlBackend, NativeBuffer, NativeFramebuffer, NativeProgram, NativeRenderbuffer,
+    NativeSampler, NativeShader, NativeTexture, NativeUniformLocation, NativeVertexArray,
+};
+use bevy::utils::HashMap;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GlCall {
+    pub name: &'static str,
+    pub args: Vec<String>,
+}
+
+impl std::fmt::Display for GlCall {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}({})", self.name, self.args.join(", "))
+    }
+}
+
+macro_rules! record {
+    ($self:ident . $name:ident ( $( $arg:expr ),* $(,)? )) => {
+        $self.calls.borrow_mut().push(GlCall {
+            name: stringify!($name),
+            args: vec![$( format!("{:?}", $arg) ),*],
+        })
+    };
+}
+
+/// In-memory `GlBackend` logging every call with its arguments.
+///
+/// Objects are allocated from a sequence, queries return values configured
+/// with `set_parameter` / `set_parameter_string` / `set_program_parameter` /
+/// `set_attrib_location` / `set_active_*` (or "empty" defaults), so renderer code
+/// can be exercised without a browser.
+///
+/// Clones share recorded calls and configuration, so a test can keep one clone
+/// and hand the other one to [`crate::Device::set_context`].
+#[derive(Debug, Default, Clone)]
+pub struct RecordingBackend {
+    calls: Rc<RefCell<Vec<GlCall>>>,
+    object_id_seq: Rc<Cell<u32>>,
+    parameters: Rc<RefCell<HashMap<u32, Vec<i32>>>>,
+    string_parameters: Rc<RefCell<HashMap<u32, String>>>,
+    program_parameters: Rc<RefCell<HashMap<u32, i32>>>,
+    attrib_locations: Rc<RefCell<HashMap<String, i32>>>,
+    active_attribs: Rc<RefCell<Vec<GlActiveInfo>>>,
+    active_uniforms: Rc<RefCell<Vec<GlActiveInfo>>>,
+    uniform_blocks: Rc<RefCell<Vec<(String, i32)>>>,
+    extensions: Rc<RefCell<Vec<String>>>,
+    framebuffer_status: Rc<Cell<Option<u32>>>,
+}
+
+fn active_infos(infos: &[(&str, u32)]) -> Vec<GlActiveInfo> {
+    infos
+        .iter()
+        .map(|(name, type_)| GlActiveInfo {
+            name: name.to_string(),
+            size: 1,
+            type_: *type_,
+        })
+        .collect()
+}
+
+impl RecordingBackend {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn calls(&self) -> Vec<GlCall> {
+        self.calls.borrow().clone()
+    }
+
+    pub fn take_calls(&self) -> Vec<GlCall> {
+        std::mem::take(&mut *self.calls.borrow_mut())
+    }
+
+    pub fn calls_named(&self, name: &str) -> Vec<GlCall> {
+        self.calls
+            .borrow()
+            .iter()
+            .filter(|call| call.name == name)
+            .cloned()
+            .collect()
+    }
+
+    pub fn set_parameter(&self, pname: u32, value: &[i32]) {
+        self.parameters.borrow_mut().insert(pname, value.to_vec());
+    }
+
+    pub fn set_parameter_string(&self, pname: u32, value: &str) {
+        self.string_parameters
+            .borrow_mut()
+            .insert(pname, value.to_string());
+    }
+
+    pub fn set_program_parameter(&self, pname: u32, value: i32) {
+        self.program_parameters.borrow_mut().insert(pname, value);
+    }
+
+    pub fn set_attrib_location(&self, name: &str, location: i32) {
+        self.attrib_locations
+            .borrow_mut()
+            .insert(name.to_string(), location);
+    }
+
+    /// Sets active attributes (name, type) reported for every program
+    pub fn set_active_attribs(&self, attribs: &[(&str, u32)]) {
+        *self.active_attribs.borrow_mut() = active_infos(attribs);
+    }
+
+    /// Sets active non-block uniforms (name, type) reported for every program
+    pub fn set_active_uniforms(&self, uniforms: &[(&str, u32)]) {
+        *self.active_uniforms.borrow_mut() = active_infos(uniforms);
+    }
+
+    /// Sets active uniform blocks (name, data size) reported for every program
+    pub fn set_uniform_blocks(&self, blocks: &[(&str, i32)]) {
+        *self.uniform_blocks.borrow_mut() = blocks
+            .iter()
+            .map(|(name, size)| (name.to_string(), *size))
+            .collect();
+    }
+
+    pub fn set_extensions(&self, extensions: &[&str]) {
+        *self.extensions.borrow_mut() = extensions.iter().map(|name| name.to_string()).collect();
+    }
+
+    /// Sets status returned by `check_framebuffer_status` (`FRAMEBUFFER_COMPLETE` by default)
+    pub fn set_framebuffer_status(&self, status: u32) {
+        self.framebuffer_status.set(Some(status));
+    }
+
+    fn next_id(&self) -> u32 {
+        let id = self.object_id_seq.get() + 1;
+        self.object_id_seq.set(id);
+        id
+    }
+}
+
+impl GlBackend for RecordingBackend {
+    fn get_parameter_i32(&self, pname: u32) -> i32 {
+        record!(self.get_parameter_i32(pname));
+        self.parameters
+            .borrow()
+            .get(&pname)
+            .and_then(|value| value.first().cloned())
+            .unwrap_or(0)
+    }
+
+    fn get_parameter_i32_array(&self, pname: u32) -> Vec<i32> {
+        record!(self.get_parameter_i32_array(pname));
+        self.parameters
+            .borrow()
+            .get(&pname)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn get_parameter_string(&self, pname: u32) -> Option<String> {
+        record!(self.get_parameter_string(pname));
+        self.string_parameters.borrow().get(&pname).cloned()
+    }
+
+    fn get_supported_extensions(&self) -> Vec<String> {
+        record!(self.get_supported_extensions());
+        self.extensions.borrow().clone()
+    }
+
+    fn enable_extension(&self, name: &str) -> bool {
+        record!(self.enable_extension(name));
+        self.extensions
+            .borrow()
+            .iter()
+            .any(|extension| extension == name)
+    }
+
+    fn enable(&self, cap: u32) {
+        record!(self.enable(cap));
+    }
+
+    fn disable(&self, cap: u32) {
+        record!(self.disable(cap));
+    }
+
+    fn viewport(&self, x: i32, y: i32, width: i32, height: i32) {
+        record!(self.viewport(x, y, width, height));
+    }
+
+    fn scissor(&self, x: i32, y: i32, width: i32, height: i32) {
+        record!(self.scissor(x, y, width, height));
+    }
+
+    fn cull_face(&self, mode: u32) {
+        record!(self.cull_face(mode));
+    }
+
+    fn front_face(&self, mode: u32) {
+        record!(self.front_face(mode));
+    }
+
+    fn depth_func(&self, func: u32) {
+        record!(self.depth_func(func));
+    }
+
+    fn depth_mask(&self, flag: bool) {
+        record!(self.depth_mask(flag));
+    }
+
+    fn stencil_func_separate(&self, face: u32, func: u32, reference: i32, mask: u32) {
+        record!(self.stencil_func_separate(face, func, reference, mask));
+    }
+
+    fn stencil_op_separate(&self, face: u32, fail: u32, depth_fail: u32, pass: u32) {
+        record!(self.stencil_op_separate(face, fail, depth_fail, pass));
+    }
+
+    fn stencil_mask_separate(&self, face: u32, mask: u32) {
+        record!(self.stencil_mask_separate(face, mask));
+    }
+
+    fn polygon_offset(&self, factor: f32, units: f32) {
+        record!(self.polygon_offset(factor, units));
+    }
+
+    fn blend_func_separate(&self, src_rgb: u32, dst_rgb: u32, src_alpha: u32, dst_alpha: u32) {
+        record!(self.blend_func_separate(src_rgb, dst_rgb, src_alpha, dst_alpha));
+    }
+
+    fn blend_equation_separate(&self, mode_rgb: u32, mode_alpha: u32) {
+        record!(self.blend_equation_separate(mode_rgb, mode_alpha));
+    }
+
+    fn blend_color(&self, r: f32, g: f32, b: f32, a: f32) {
+        record!(self.blend_color(r, g, b, a));
+    }
+
+    fn color_mask(&self, r: bool, g: bool, b: bool, a: bool) {
+        record!(self.color_mask(r, g, b, a));
+    }
+
+    fn enable_i(&self, cap: u32, index: u32) {
+        record!(self.enable_i(cap, index));
+    }
+
+    fn disable_i(&self, cap: u32, index: u32) {
+        record!(self.disable_i(cap, index));
+    }
+
+    fn blend_equation_separate_i(&self, buffer: u32, mode_rgb: u32, mode_alpha: u32) {
+        record!(self.blend_equation_separate_i(buffer, mode_rgb, mode_alpha));
+    }
+
+    fn blend_func_separate_i(
+        &self,
+        buffer: u32,
+        src_rgb: u32,
+        dst_rgb: u32,
+        src_alpha: u32,
+        dst_alpha: u32,
+    ) {
+        record!(self.blend_func_separate_i(buffer, src_rgb, dst_rgb, src_alpha, dst_alpha));
+    }
+
+    fn color_mask_i(&self, buffer: u32, r: bool, g: bool, b: bool, a: bool) {
+        record!(self.color_mask_i(buffer, r, g, b, a));
+    }
+
+    fn clear_color(&self, r: f32, g: f32, b: f32, a: f32) {
+        record!(self.clear_color(r, g, b, a));
+    }
+
+    fn clear_depth(&self, depth: f32) {
+        record!(self.clear_depth(depth));
+    }
+
+    fn clear(&self, mask: u32) {
+        record!(self.clear(mask));
+    }
+
+    fn clear_bufferfv(&self, buffer: u32, draw_buffer: i32, values: &[f32]) {
+        record!(self.clear_bufferfv(buffer, draw_buffer, values));
+    }
+
+    fn clear_bufferiv(&self, buffer: u32, draw_buffer: i32, values: &[i32]) {
+        record!(self.clear_bufferiv(buffer, draw_buffer, values));
+    }
+
+    fn clear_bufferuiv(&self, buffer: u32, draw_buffer: i32, values: &[u32]) {
+        record!(self.clear_bufferuiv(buffer, draw_buffer, values));
+    }
+
+    fn clear_bufferfi(&self, buffer: u32, draw_buffer: i32, depth: f32, stencil: i32) {
+        record!(self.clear_bufferfi(buffer, draw_buffer, depth, stencil));
+    }
+
+    fn create_shader(&self, shader_type: u32) -> Option<NativeShader> {
+        let shader = NativeShader(self.next_id());
+        record!(self.create_shader(shader_type, shader));
+        Some(shader)
+    }
+
+    fn shader_source(&self, shader: NativeShader, source: &str) {
+        record!(self.shader_source(shader, source));
+    }
+
+    fn compile_shader(&self, shader: NativeShader) {
+        record!(self.compile_shader(shader));
+    }
+
+    fn get_shader_compile_status(&self, shader: NativeShader) -> bool {
+        record!(self.get_shader_compile_status(shader));
+        true
+    }
+
+    fn get_shader_info_log(&self, shader: NativeShader) -> Option<String> {
+        record!(self.get_shader_info_log(shader));
+        None
+    }
+
+    fn create_program(&self) -> Option<NativeProgram> {
+        let program = NativeProgram(self.next_id());
+        record!(self.create_program(program));
+        Some(program)
+    }
+
+    fn attach_shader(&self, program: NativeProgram, shader: NativeShader) {
+        record!(self.attach_shader(program, shader));
+    }
+
+    fn link_program(&self, program: NativeProgram) {
+        record!(self.link_program(program));
+    }
+
+    fn get_program_link_status(&self, program: NativeProgram) -> bool {
+        record!(self.get_program_link_status(program));
+        true
+    }
+
+    fn get_program_info_log(&self, program: NativeProgram) -> Option<String> {
+        record!(self.get_program_info_log(program));
+        None
+    }
+
+    fn get_program_parameter_i32(&self, program: NativeProgram, pname: u32) -> i32 {
+        record!(self.get_program_parameter_i32(program, pname));
+        let configured = self.program_parameters.borrow().get(&pname).cloned();
+        configured.unwrap_or_else(|| match pname {
+            Gl::ACTIVE_ATTRIBUTES => self.active_attribs.borrow().len() as i32,
+            Gl::ACTIVE_UNIFORMS => self.active_uniforms.borrow().len() as i32,
+            Gl::ACTIVE_UNIFORM_BLOCKS => self.uniform_blocks.borrow().len() as i32,
+            _ => 0,
+        })
+    }
+
+    fn use_program(&self, program: Option<NativeProgram>) {
+        record!(self.use_program(program));
+    }
+
+    fn get_active_attrib(&self, program: NativeProgram, index: u32) -> Option<GlActiveInfo> {
+        record!(self.get_active_attrib(program, index));
+        self.active_attribs.borrow().get(index as usize).cloned()
+    }
+
+    fn get_active_uniform(&self, program: NativeProgram, index: u32) -> Option<GlActiveInfo> {
+        record!(self.get_active_uniform(program, index));
+        self.active_uniforms.borrow().get(index as usize).cloned()
+    }
+
+    fn get_active_uniform_block_name(&self, program: NativeProgram, index: u32) -> Option<String> {
+        record!(self.get_active_uniform_block_name(program, index));
+        self.uniform_blocks
+            .borrow()
+            .get(index as usize)
+            .map(|(name, _)| name.clone())
+    }
+
+    fn get_active_uniform_block_parameter_i32(
+        &self,
+        program: NativeProgram,
+        index: u32,
+        pname: u32,
+    ) -> i32 {
+        record!(self.get_active_uniform_block_parameter_i32(program, index, pname));
+        match (self.uniform_blocks.borrow().get(index as usize), pname) {
+            (Some((_, size)), Gl::UNIFORM_BLOCK_DATA_SIZE) => *size,
+            _ => 0,
+        }
+    }
+
+    fn get_attrib_location(&self, program: NativeProgram, name: &str) -> i32 {
+        record!(self.get_attrib_location(program, name));
+        self.attrib_locations
+            .borrow()
+            .get(name)
+            .cloned()
+            .unwrap_or(-1)
+    }
+
+    fn get_uniform_block_index(&self, program: NativeProgram, name: &str) -> u32 {
+        record!(self.get_uniform_block_index(program, name));
+        self.uniform_blocks
+            .borrow()
+            .iter()
+            .position(|(block, _)| block == name)
+            .map_or(Gl::INVALID_INDEX, |index| index as u32)
+    }
+
+    fn get_uniform_location(
+        &self,
+        program: NativeProgram,
+        name: &str,
+    ) -> Option<NativeUniformLocation> {
+        // only active (non-block) uniforms have locations
+        let is_active = self
+            .active_uniforms
+            .borrow()
+            .iter()
+            .any(|uniform| uniform.name == name);
+        let location = if is_active {
+            Some(NativeUniformLocation(self.next_id()))
+        } else {
+            None
+        };
+        record!(self.get_uniform_location(program, name, location));
+        location
+    }
+
+    fn uniform_block_binding(&self, program: NativeProgram, index: u32, binding: u32) {
+        record!(self.uniform_block_binding(program, index, binding));
+    }
+
+    fn uniform1i(&self, location: Option<NativeUniformLocation>, value: i32) {
+        record!(self.uniform1i(location, value));
+    }
+
+    fn create_buffer(&self) -> Option<NativeBuffer> {
+        let buffer = NativeBuffer(self.next_id());
+        record!(self.create_buffer(buffer));
+        Some(buffer)
+    }
+
+    fn delete_buffer(&self, buffer: NativeBuffer) {
+        record!(self.delete_buffer(buffer));
+    }
+
+    fn bind_buffer(&self, target: u32, buffer: Option<NativeBuffer>) {
+        record!(self.bind_buffer(target, buffer));
+    }
+
+    fn bind_buffer_range(
+        &self,
+        target: u32,
+        index: u32,
+        buffer: Option<NativeBuffer>,
+        offset: i32,
+        size: i32,
+    ) {
+        record!(self.bind_buffer_range(target, index, buffer, offset, size));
+    }
+
+    fn buffer_data_size(&self, target: u32, size: i32, usage: u32) {
+        record!(self.buffer_data_size(target, size, usage));
+    }
+
+    fn buffer_data_u8_slice(&self, target: u32, data: &[u8], usage: u32) {
+        record!(self.buffer_data_u8_slice(target, data.len(), usage));
+    }
+
+    fn buffer_sub_data_u8_slice(&self, target: u32, offset: i32, data: &[u8]) {
+        record!(self.buffer_sub_data_u8_slice(target, offset, data.len()));
+    }
+
+    fn get_buffer_sub_data(&self, target: u32, offset: i32, data: &mut [u8]) {
+        record!(self.get_buffer_sub_data(target, offset, data.len()));
+    }
+
+    fn copy_buffer_sub_data(
+        &self,
+        read_target: u32,
+        write_target: u32,
+        read_offset: i32,
+        write_offset: i32,
+        size: i32,
+    ) {
+        record!(self.copy_buffer_sub_data(
+            read_target,
+            write_target,
+            read_offset,
+            write_offset,
+            size
+        ));
+    }
+
+    fn create_texture(&self) -> Option<NativeTexture> {
+        let texture = NativeTexture(self.next_id());
+        record!(self.create_texture(texture));
+        Some(texture)
+    }
+
+    fn delete_texture(&self, texture: NativeTexture) {
+        record!(self.delete_texture(texture));
+    }
+
+    fn active_texture(&self, unit: u32) {
+        record!(self.active_texture(unit));
+    }
+
+    fn bind_texture(&self, target: u32, texture: Option<NativeTexture>) {
+        record!(self.bind_texture(target, texture));
+    }
+
+    fn tex_parameteri(&self, target: u32, pname: u32, param: i32) {
+        record!(self.tex_parameteri(target, pname, param));
+    }
+
+    fn pixel_storei(&self, pname: u32, param: i32) {
+        record!(self.pixel_storei(pname, param));
+    }
+
+    fn tex_image_2d(
+        &self,
+        target: u32,
+        level: i32,
+        internal_format: i32,
+        width: i32,
+        height: i32,
+        border: i32,
+        format: u32,
+        type_: u32,
+        pixels: Option<&[u8]>,
+    ) {
+        record!(self.tex_image_2d(
+            target,
+            level,
+            internal_format,
+            width,
+            height,
+            border,
+            format,
+            type_,
+            pixels.map(|pixels| pixels.len())
+        ));
+    }
+
+    fn tex_image_2d_with_pbo_offset(
+        &self,
+        target: u32,
+        level: i32,
+        internal_format: i32,
+        width: i32,
+        height: i32,
+        border: i32,
+        format: u32,
+        type_: u32,
+        offset: i32,
+    ) {
+        record!(self.tex_image_2d_with_pbo_offset(
+            target,
+            level,
+            internal_format,
+            width,
+            height,
+            border,
+            format,
+            type_,
+            offset
+        ));
+    }
+
+    fn compressed_tex_image_2d(
+        &self,
+        target: u32,
+        level: i32,
+        internal_format: u32,
+        width: i32,
+        height: i32,
+        border: i32,
+        data: &[u8],
+    ) {
+        record!(self.compressed_tex_image_2d(
+            target,
+            level,
+            internal_format,
+            width,
+            height,
+            border,
+            data.len()
+        ));
+    }
+
+    fn compressed_tex_image_2d_with_pbo_offset(
+        &self,
+        target: u32,
+        level: i32,
+        internal_format: u32,
+        width: i32,
+        height: i32,
+        border: i32,
+        image_size: i32,
+        offset: i32,
+    ) {
+        record!(self.compressed_tex_image_2d_with_pbo_offset(
+            target,
+            level,
+            internal_format,
+            width,
+            height,
+            border,
+            image_size,
+            offset
+        ));
+    }
+
+    fn tex_storage_2d(
+        &self,
+        target: u32,
+        levels: i32,
+        internal_format: u32,
+        width: i32,
+        height: i32,
+    ) {
+        record!(self.tex_storage_2d(target, levels, internal_format, width, height));
+    }
+
+    fn tex_sub_image_2d(
+        &self,
+        target: u32,
+        level: i32,
+        x_offset: i32,
+        y_offset: i32,
+        width: i32,
+        height: i32,
+        format: u32,
+        type_: u32,
+        pixels: &[u8],
+    ) {
+        record!(self.tex_sub_image_2d(
+            target,
+            level,
+            x_offset,
+            y_offset,
+            width,
+            height,
+            format,
+            type_,
+            pixels.len()
+        ));
+    }
+
+    fn tex_sub_image_2d_with_pbo_offset(
+        &self,
+        target: u32,
+        level: i32,
+        x_offset: i32,
+        y_offset: i32,
+        width: i32,
+        height: i32,
+        format: u32,
+        type_: u32,
+        offset: i32,
+    ) {
+        record!(self.tex_sub_image_2d_with_pbo_offset(
+            target, level, x_offset, y_offset, width, height, format, type_, offset
+        ));
+    }
+
+    fn copy_tex_sub_image_2d(
+        &self,
+        target: u32,
+        level: i32,
+        x_offset: i32,
+        y_offset: i32,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+    ) {
+        record!(self.copy_tex_sub_image_2d(target, level, x_offset, y_offset, x, y, width, height));
+    }
+
+    fn tex_storage_3d(
+        &self,
+        target: u32,
+        levels: i32,
+        internal_format: u32,
+        width: i32,
+        height: i32,
+        depth: i32,
+    ) {
+        record!(self.tex_storage_3d(target, levels, internal_format, width, height, depth));
+    }
+
+    fn tex_sub_image_3d(
+        &self,
+        target: u32,
+        level: i32,
+        x_offset: i32,
+        y_offset: i32,
+        z_offset: i32,
+        width: i32,
+        height: i32,
+        depth: i32,
+        format: u32,
+        type_: u32,
+        pixels: &[u8],
+    ) {
+        record!(self.tex_sub_image_3d(
+            target,
+            level,
+            x_offset,
+            y_offset,
+            z_offset,
+            width,
+            height,
+            depth,
+            format,
+            type_,
+            pixels.len()
+        ));
+    }
+
+    fn tex_sub_image_3d_with_pbo_offset(
+        &self,
+        target: u32,
+        level: i32,
+        x_offset: i32,
+        y_offset: i32,
+        z_offset: i32,
+        width: i32,
+        height: i32,
+        depth: i32,
+        format: u32,
+        type_: u32,
+        offset: i32,
+    ) {
+        record!(self.tex_sub_image_3d_with_pbo_offset(
+            target, level, x_offset, y_offset, z_offset, width, height, depth, format, type_,
+            offset
+        ));
+    }
+
+    fn copy_tex_sub_image_3d(
+        &self,
+        target: u32,
+        level: i32,
+        x_offset: i32,
+        y_offset: i32,
+        z_offset: i32,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+    ) {
+        record!(self.copy_tex_sub_image_3d(
+            target, level, x_offset, y_offset, z_offset, x, y, width, height
+        ));
+    }
+
+    fn generate_mipmap(&self, target: u32) {
+        record!(self.generate_mipmap(target));
+    }
+
+    fn create_sampler(&self) -> Option<NativeSampler> {
+        let sampler = NativeSampler(self.next_id());
+        record!(self.create_sampler(sampler));
+        Some(sampler)
+    }
+
+    fn delete_sampler(&self, sampler: NativeSampler) {
+        record!(self.delete_sampler(sampler));
+    }
+
+    fn bind_sampler(&self, unit: u32, sampler: Option<NativeSampler>) {
+        record!(self.bind_sampler(unit, sampler));
+    }
+
+    fn sampler_parameteri(&self, sampler: NativeSampler, pname: u32, param: i32) {
+        record!(self.sampler_parameteri(sampler, pname, param));
+    }
+
+    fn sampler_parameterf(&self, sampler: NativeSampler, pname: u32, param: f32) {
+        record!(self.sampler_parameterf(sampler, pname, param));
+    }
+
+    fn create_framebuffer(&self) -> Option<NativeFramebuffer> {
+        let framebuffer = NativeFramebuffer(self.next_id());
+        record!(self.create_framebuffer(framebuffer));
+        Some(framebuffer)
+    }
+
+    fn delete_framebuffer(&self, framebuffer: NativeFramebuffer) {
+        record!(self.delete_framebuffer(framebuffer));
+    }
+
+    fn bind_framebuffer(&self, target: u32, framebuffer: Option<NativeFramebuffer>) {
+        record!(self.bind_framebuffer(target, framebuffer));
+    }
+
+    fn framebuffer_texture_2d(
+        &self,
+        target: u32,
+        attachment: u32,
+        texture_target: u32,
+        texture: Option<NativeTexture>,
+        level: i32,
+    ) {
+        record!(self.framebuffer_texture_2d(target, attachment, texture_target, texture, level));
+    }
+
+    fn framebuffer_texture_layer(
+        &self,
+        target: u32,
+        attachment: u32,
+        texture: Option<NativeTexture>,
+        level: i32,
+        layer: i32,
+    ) {
+        record!(self.framebuffer_texture_layer(target, attachment, texture, level, layer));
+    }
+
+    fn check_framebuffer_status(&self, target: u32) -> u32 {
+        record!(self.check_framebuffer_status(target));
+        self.framebuffer_status
+            .get()
+            .unwrap_or(Gl::FRAMEBUFFER_COMPLETE)
+    }
+
+    fn create_renderbuffer(&self) -> Option<NativeRenderbuffer> {
+        let renderbuffer = NativeRenderbuffer(self.next_id());
+        record!(self.create_renderbuffer(renderbuffer));
+        Some(renderbuffer)
+    }
+
+    fn delete_renderbuffer(&self, renderbuffer: NativeRenderbuffer) {
+        record!(self.delete_renderbuffer(renderbuffer));
+    }
+
+    fn bind_renderbuffer(&self, target: u32, renderbuffer: Option<NativeRenderbuffer>) {
+        record!(self.bind_renderbuffer(target, renderbuffer));
+    }
+
+    fn renderbuffer_storage_multisample(
+        &self,
+        target: u32,
+        samples: i32,
+        internal_format: u32,
+        width: i32,
+        height: i32,
+    ) {
+        record!(self.renderbuffer_storage_multisample(
+            target,
+            samples,
+            internal_format,
+            width,
+            height
+        ));
+    }
+
+    fn framebuffer_renderbuffer(
+        &self,
+        target: u32,
+        attachment: u32,
+        renderbuffer_target: u32,
+        renderbuffer: Option<NativeRenderbuffer>,
+    ) {
+        record!(self.framebuffer_renderbuffer(
+            target,
+            attachment,
+            renderbuffer_target,
+            renderbuffer
+        ));
+    }
+
+    fn draw_buffers(&self, buffers: &[u32]) {
+        record!(self.draw_buffers(buffers));
+    }
+
+    fn read_buffer(&self, src: u32) {
+        record!(self.read_buffer(src));
+    }
+
+    fn blit_framebuffer(
+        &self,
+        src_x0: i32,
+        src_y0: i32,
+        src_x1: i32,
+        src_y1: i32,
+        dst_x0: i32,
+        dst_y0: i32,
+        dst_x1: i32,
+        dst_y1: i32,
+        mask: u32,
+        filter: u32,
+    ) {
+        record!(self.blit_framebuffer(
+            src_x0, src_y0, src_x1, src_y1, dst_x0, dst_y0, dst_x1, dst_y1, mask, filter
+        ));
+    }
+
+    fn read_pixels_with_pbo_offset(
+        &self,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        format: u32,
+        type_: u32,
+        offset: i32,
+    ) {
+        record!(self.read_pixels_with_pbo_offset(x, y, width, height, format, type_, offset));
+    }
+
+    fn create_vertex_array(&self) -> Option<NativeVertexArray> {
+        let vertex_array = NativeVertexArray(self.next_id());
+        record!(self.create_vertex_array(vertex_array));
+        Some(vertex_array)
+    }
+
+    fn bind_vertex_array(&self, vertex_array: Option<NativeVertexArray>) {
+        record!(self.bind_vertex_array(vertex_array));
+    }
+
+    fn enable_vertex_attrib_array(&self, index: u32) {
+        record!(self.enable_vertex_attrib_array(index));
+    }
+
+    fn vertex_attrib_pointer_f32(
+        &self,
+        index: u32,
+        size: i32,
+        type_: u32,
+        normalized: bool,
+        stride: i32,
+        offset: i32,
+    ) {
+        record!(self.vertex_attrib_pointer_f32(index, size, type_, normalized, stride, offset));
+    }
+
+    fn vertex_attrib_pointer_i32(
+        &self,
+        index: u32,
+        size: i32,
+        type_: u32,
+        stride: i32,
+        offset: i32,
+    ) {
+        record!(self.vertex_attrib_pointer_i32(index, size, type_, stride, offset));
+    }
+
+    fn vertex_attrib_divisor(&self, index: u32, divisor: u32) {
+        record!(self.vertex_attrib_divisor(index, divisor));
+    }
+
+    fn draw_arrays(&self, mode: u32, first: i32, count: i32) {
+        record!(self.draw_arrays(mode, first, count));
+    }
+
+    fn draw_arrays_instanced(&self, mode: u32, first: i32, count: i32, instance_count: i32) {
+        record!(self.draw_arrays_instanced(mode, first, count, instance_count));
+    }
+
+    fn draw_elements_instanced(
+        &self,
+        mode: u32,
+        count: i32,
+        type_: u32,
+        offset: i32,
+        instance_count: i32,
+    ) {
+        record!(self.draw_elements_instanced(mode, count, type_, offset, instance_count));
+    }
+}
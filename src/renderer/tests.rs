@@ -0,0 +1,1759 @@
+use super::{
+    compile_shader, link_program, Gl, GlBackend, GlCall, GlStateCache, NativeBuffer, NativeProgram,
+    NativeTexture, RecordingBackend, WebGL2RenderContext, WebGL2RenderResourceContext,
+};
+use crate::{
+    converters::{CompressedTextureFormat, CUBE_MAP_FACES},
+    Buffer, Device, GlSpriteBatchPipeline, SpriteBatchKind, WebGL2Pipeline,
+    SPRITE_BIND_GROUP_INDEX,
+};
+use bevy::app::App;
+use bevy::asset::{AddAsset, AssetPlugin, Assets, Handle, HandleUntyped};
+use bevy::core::CorePlugin;
+use bevy::reflect::TypeUuid;
+use bevy::render::{
+    color::Color,
+    pass::{
+        LoadOp, Operations, PassDescriptor, RenderPass, RenderPassColorAttachment,
+        RenderPassDepthStencilAttachment, TextureAttachment,
+    },
+    pipeline::{
+        BlendComponent, BlendFactor, BlendOperation, BlendState, ColorTargetState, ColorWrite,
+        CompareFunction, DepthBiasState, DepthStencilState, IndexFormat, InputStepMode,
+        PipelineDescriptor, PipelineLayout, StencilFaceState, StencilOperation, StencilState,
+    },
+    renderer::{
+        BindGroup, BufferId, BufferInfo, BufferUsage, RenderContext, RenderResourceBinding,
+        RenderResourceBindings, RenderResourceContext, TextureId,
+    },
+    shader::{Shader, ShaderStage, ShaderStages},
+    texture::{
+        Extent3d, FilterMode, SamplerDescriptor, TextureDescriptor, TextureDimension,
+        TextureFormat, TextureViewDimension,
+    },
+};
+use std::sync::Arc;
+
+const VERTEX_SHADER: &str = r#"#version 300 es
+in vec3 Vertex_Position;
+layout(std140) uniform CameraViewProj {  // set = 0, binding = 0
+    mat4 ViewProj;
+};
+layout(std140) uniform Transform {  // set = 2, binding = 0
+    mat4 Model;
+};
+void main() {
+    gl_Position = ViewProj * Model * vec4(Vertex_Position, 1.0);
+}
+"#;
+
+const FRAGMENT_SHADER: &str = r#"#version 300 es
+precision highp float;
+out vec4 o_Target;
+uniform sampler2D ColorMaterial_texture;  // set = 1, binding = 1
+void main() {
+    o_Target = texture(ColorMaterial_texture, vec2(0.0));
+}
+"#;
+
+const SPRITE_VERTEX_SHADER: &str = r#"#version 300 es
+in vec3 Vertex_Position;
+layout(std140) uniform CameraViewProj {  // set = 0, binding = 0
+    mat4 ViewProj;
+};
+layout(std140) uniform Transform {  // set = 2, binding = 0
+    mat4 Model;
+};
+layout(std140) uniform Sprite {  // set = 2, binding = 1
+    vec2 size;
+    uint flip;
+};
+void main() {
+    gl_Position = ViewProj * Model * vec4(Vertex_Position * vec3(size, 1.0), 1.0);
+}
+"#;
+
+const PIPELINE_HANDLE: HandleUntyped =
+    HandleUntyped::weak_from_u64(PipelineDescriptor::TYPE_UUID, 1);
+
+fn render_context() -> (WebGL2RenderContext, RecordingBackend) {
+    let recorder = RecordingBackend::new();
+    // reported by the program created for `VERTEX_SHADER` / `FRAGMENT_SHADER`
+    recorder.set_active_attribs(&[("Vertex_Position", Gl::FLOAT_VEC3)]);
+    recorder.set_attrib_location("Vertex_Position", 0);
+    recorder.set_active_uniforms(&[("ColorMaterial_texture", Gl::SAMPLER_2D)]);
+    recorder.set_uniform_blocks(&[("CameraViewProj", 64), ("Transform", 64)]);
+
+    let device = Arc::new(Device::default());
+    let mut resource_context = WebGL2RenderResourceContext::new(device.clone());
+    resource_context.initialize_with_backend(800, 600, Box::new(recorder.clone()));
+    recorder.take_calls();
+    (WebGL2RenderContext::new(device, resource_context), recorder)
+}
+
+fn shader_assets() -> App {
+    let mut app = App::new();
+    app.add_plugin(CorePlugin::default())
+        .add_plugin(AssetPlugin::default())
+        .add_asset::<Shader>();
+    app
+}
+
+fn create_pipeline(context: &WebGL2RenderContext) -> (Handle<PipelineDescriptor>, PipelineLayout) {
+    create_pipeline_with_vertex_shader(context, VERTEX_SHADER)
+}
+
+fn create_pipeline_with_vertex_shader(
+    context: &WebGL2RenderContext,
+    vertex_shader: &str,
+) -> (Handle<PipelineDescriptor>, PipelineLayout) {
+    let mut app = shader_assets();
+    let mut shaders = app.world.get_resource_mut::<Assets<Shader>>().unwrap();
+    let shader_stages = ShaderStages {
+        vertex: shaders.add(Shader::from_glsl(ShaderStage::Vertex, vertex_shader)),
+        fragment: Some(shaders.add(Shader::from_glsl(ShaderStage::Fragment, FRAGMENT_SHADER))),
+    };
+    let resource_context = &context.render_resource_context;
+    let layout = resource_context.reflect_pipeline_layout(&shaders, &shader_stages, true);
+    let mut descriptor = PipelineDescriptor::default_config(shader_stages);
+    descriptor.layout = Some(layout.clone());
+    let handle = PIPELINE_HANDLE.typed();
+    resource_context.create_render_pipeline(handle.clone(), &descriptor, &shaders);
+    (handle, layout)
+}
+
+fn texture(context: &WebGL2RenderContext, format: TextureFormat) -> TextureId {
+    context
+        .render_resource_context
+        .create_texture(TextureDescriptor {
+            size: Extent3d::new(64, 32, 1),
+            format,
+            ..Default::default()
+        })
+}
+
+fn offscreen_pass(color: TextureId, depth: Option<TextureId>) -> PassDescriptor {
+    PassDescriptor {
+        color_attachments: vec![RenderPassColorAttachment {
+            attachment: TextureAttachment::Id(color),
+            resolve_target: None,
+            ops: Operations {
+                load: LoadOp::Clear(Color::rgba(0.0, 0.0, 1.0, 1.0)),
+                store: true,
+            },
+        }],
+        depth_stencil_attachment: depth.map(|depth| RenderPassDepthStencilAttachment {
+            attachment: TextureAttachment::Id(depth),
+            depth_ops: Some(Operations {
+                load: LoadOp::Clear(1.0),
+                store: true,
+            }),
+            stencil_ops: None,
+        }),
+        sample_count: 1,
+    }
+}
+
+#[test]
+fn recording_backend_records_calls_and_allocates_objects() {
+    let gl = RecordingBackend::new();
+    gl.set_parameter(Gl::MAX_TEXTURE_SIZE, &[4096]);
+    assert_eq!(gl.get_parameter_i32(Gl::MAX_TEXTURE_SIZE), 4096);
+    // parameters which weren't set are reported as 0
+    assert_eq!(gl.get_parameter_i32(Gl::MAX_SAMPLES), 0);
+
+    let first = gl.create_buffer().unwrap();
+    let second = gl.create_buffer().unwrap();
+    assert_ne!(first, second);
+    gl.bind_buffer(Gl::ARRAY_BUFFER, Some(second));
+
+    let calls = gl.take_calls();
+    assert_eq!(calls.len(), 5);
+    assert_eq!(
+        calls[4].to_string(),
+        format!("bind_buffer({}, Some({:?}))", Gl::ARRAY_BUFFER, second)
+    );
+    assert!(gl.calls().is_empty());
+}
+
+#[test]
+fn capabilities_are_queried_when_context_is_initialized() {
+    let recorder = RecordingBackend::new();
+    recorder.set_extensions(&["WEBGL_debug_renderer_info", "EXT_color_buffer_float"]);
+    recorder.set_parameter(Gl::MAX_TEXTURE_SIZE, &[8192]);
+    recorder.set_parameter_string(Gl::RENDERER, "WebKit WebGL");
+    // UNMASKED_RENDERER_WEBGL
+    recorder.set_parameter_string(0x9246, "Mesa Intel(R) UHD Graphics");
+
+    let device = Arc::new(Device::default());
+    let mut resource_context = WebGL2RenderResourceContext::new(device);
+    resource_context.initialize_with_backend(800, 600, Box::new(recorder.clone()));
+    let capabilities = resource_context.capabilities();
+
+    assert_eq!(capabilities.renderer, "Mesa Intel(R) UHD Graphics");
+    assert!(capabilities.supports_extension("EXT_color_buffer_float"));
+    assert!(capabilities.float_render_target);
+    // covered by EXT_color_buffer_float
+    assert!(capabilities.half_float_render_target);
+    assert!(!capabilities.draw_buffers_indexed);
+    assert_eq!(capabilities.limits.max_texture_size, 8192);
+    // not reported by the context, WebGL2 minimum is kept
+    assert_eq!(capabilities.limits.max_samples, 4);
+    assert_eq!(
+        recorder
+            .calls_named("enable_extension")
+            .into_iter()
+            .map(|call| call.args[0].clone())
+            .collect::<Vec<_>>(),
+        [
+            "\"WEBGL_debug_renderer_info\"",
+            "\"EXT_color_buffer_float\""
+        ]
+    );
+}
+
+#[test]
+fn shaders_are_linked_with_bind_groups_from_binding_comments() {
+    let gl = RecordingBackend::new();
+    let vertex = compile_shader(&gl, Gl::VERTEX_SHADER, VERTEX_SHADER).unwrap();
+    let fragment = compile_shader(&gl, Gl::FRAGMENT_SHADER, FRAGMENT_SHADER).unwrap();
+    let program = link_program(&gl, &[vertex, fragment]).unwrap();
+
+    assert_eq!(program.bind_groups.get("CameraViewProj"), Some(&(0, 0)));
+    assert_eq!(program.bind_groups.get("Transform"), Some(&(2, 0)));
+    assert_eq!(
+        program.bind_groups.get("ColorMaterial_texture"),
+        Some(&(1, 1))
+    );
+    assert_eq!(gl.calls_named("compile_shader").len(), 2);
+    assert_eq!(gl.calls_named("attach_shader").len(), 2);
+    assert_eq!(gl.calls_named("link_program").len(), 1);
+}
+
+fn state_cache() -> (GlStateCache, RecordingBackend) {
+    let recorder = RecordingBackend::new();
+    (GlStateCache::new(Box::new(recorder.clone())), recorder)
+}
+
+fn call_names(recorder: &RecordingBackend) -> Vec<&'static str> {
+    recorder
+        .take_calls()
+        .into_iter()
+        .map(|call| call.name)
+        .collect()
+}
+
+#[test]
+fn state_cache_drops_redundant_calls() {
+    let (gl, recorder) = state_cache();
+    for _ in 0..2 {
+        gl.use_program(Some(NativeProgram(1)));
+        gl.bind_buffer(Gl::ARRAY_BUFFER, Some(NativeBuffer(2)));
+        gl.active_texture(Gl::TEXTURE0);
+        gl.bind_texture(Gl::TEXTURE_2D, Some(NativeTexture(3)));
+        gl.enable(Gl::BLEND);
+        gl.blend_func_separate(Gl::ONE, Gl::ZERO, Gl::ONE, Gl::ZERO);
+    }
+    assert_eq!(
+        call_names(&recorder),
+        [
+            "use_program",
+            "bind_buffer",
+            "active_texture",
+            "bind_texture",
+            "enable",
+            "blend_func_separate"
+        ]
+    );
+
+    // changed state is passed through
+    gl.bind_texture(Gl::TEXTURE_2D, Some(NativeTexture(4)));
+    gl.disable(Gl::BLEND);
+    gl.blend_func_separate(Gl::ONE, Gl::ONE, Gl::ONE, Gl::ONE);
+    assert_eq!(
+        call_names(&recorder),
+        ["bind_texture", "disable", "blend_func_separate"]
+    );
+    // element array buffer binding is a part of VAO state
+    gl.bind_buffer(Gl::ELEMENT_ARRAY_BUFFER, Some(NativeBuffer(2)));
+    gl.bind_buffer(Gl::ELEMENT_ARRAY_BUFFER, Some(NativeBuffer(2)));
+    assert_eq!(call_names(&recorder), ["bind_buffer", "bind_buffer"]);
+}
+
+#[test]
+fn state_cache_forgets_bindings_of_deleted_objects() {
+    let (gl, recorder) = state_cache();
+    gl.bind_buffer(Gl::UNIFORM_BUFFER, Some(NativeBuffer(1)));
+    gl.active_texture(Gl::TEXTURE0);
+    gl.bind_texture(Gl::TEXTURE_2D, Some(NativeTexture(2)));
+    gl.delete_buffer(NativeBuffer(1));
+    gl.delete_texture(NativeTexture(2));
+    recorder.take_calls();
+
+    // ids can be reused by new objects, so they have to be bound again
+    gl.bind_buffer(Gl::UNIFORM_BUFFER, Some(NativeBuffer(1)));
+    gl.bind_texture(Gl::TEXTURE_2D, Some(NativeTexture(2)));
+    assert_eq!(call_names(&recorder), ["bind_buffer", "bind_texture"]);
+}
+
+#[test]
+fn indexed_blend_calls_invalidate_cached_blend_state() {
+    let (gl, recorder) = state_cache();
+    gl.enable(Gl::BLEND);
+    gl.blend_func_separate(Gl::ONE, Gl::ZERO, Gl::ONE, Gl::ZERO);
+    gl.blend_equation_separate(Gl::FUNC_ADD, Gl::FUNC_ADD);
+    gl.disable_i(Gl::BLEND, 1);
+    gl.blend_func_separate_i(1, Gl::ONE, Gl::ONE, Gl::ONE, Gl::ONE);
+    gl.blend_equation_separate_i(1, Gl::MAX, Gl::MAX);
+    recorder.take_calls();
+
+    // state of draw buffer 1 differs, so setting it for all buffers isn't redundant
+    gl.enable(Gl::BLEND);
+    gl.blend_func_separate(Gl::ONE, Gl::ZERO, Gl::ONE, Gl::ZERO);
+    gl.blend_equation_separate(Gl::FUNC_ADD, Gl::FUNC_ADD);
+    assert_eq!(
+        call_names(&recorder),
+        ["enable", "blend_func_separate", "blend_equation_separate"]
+    );
+}
+
+#[test]
+fn create_render_pipeline_binds_uniform_blocks_and_textures() {
+    let (context, recorder) = render_context();
+    let (_, layout) = create_pipeline(&context);
+
+    let group_indices: Vec<_> = layout.bind_groups.iter().map(|g| g.index).collect();
+    assert_eq!(group_indices, vec![0, 1, 2]);
+
+    // (block index, binding point) - CameraViewProj in group 0, Transform in group 2
+    let block_bindings: Vec<_> = recorder
+        .calls_named("uniform_block_binding")
+        .into_iter()
+        .map(|call| call.args[1..].to_vec())
+        .collect();
+    assert_eq!(block_bindings, vec![vec!["0", "0"], vec!["1", "1"]]);
+
+    // texture of group 1 gets the first texture unit
+    let texture_units = recorder.calls_named("uniform1i");
+    assert_eq!(texture_units.len(), 1);
+    assert_eq!(texture_units[0].args[1], "0");
+
+    assert_eq!(recorder.calls_named("create_vertex_array").len(), 1);
+}
+
+#[test]
+fn set_bind_group_binds_uniform_buffer_range() {
+    let (mut context, recorder) = render_context();
+    let (pipeline, layout) = create_pipeline(&context);
+    let descriptor = layout.bind_groups.iter().find(|g| g.index == 2).unwrap();
+
+    let buffer = context.render_resource_context.create_buffer_with_data(
+        BufferInfo {
+            size: 128,
+            buffer_usage: BufferUsage::UNIFORM | BufferUsage::COPY_DST,
+            ..Default::default()
+        },
+        &[0; 128],
+    );
+    let bind_group = BindGroup::build().add_buffer(0, buffer, 64..128).finish();
+    context
+        .render_resource_context
+        .create_bind_group(descriptor.id, &bind_group);
+    recorder.take_calls();
+
+    let pass_descriptor = PassDescriptor {
+        color_attachments: vec![RenderPassColorAttachment {
+            attachment: TextureAttachment::Id(context.render_resource_context.swapchain_texture),
+            resolve_target: None,
+            ops: Operations {
+                load: LoadOp::Load,
+                store: true,
+            },
+        }],
+        depth_stencil_attachment: None,
+        sample_count: 1,
+    };
+    context.begin_pass(
+        &pass_descriptor,
+        &RenderResourceBindings::default(),
+        &mut |pass| {
+            pass.set_pipeline(&pipeline);
+            pass.set_bind_group(2, descriptor.id, bind_group.id, None);
+        },
+    );
+
+    // Transform block got binding point 1 when the pipeline was created
+    let ranges = recorder.calls_named("bind_buffer_range");
+    assert_eq!(ranges.len(), 1);
+    assert_eq!(ranges[0].args[0], Gl::UNIFORM_BUFFER.to_string());
+    assert_eq!(ranges[0].args[1], "1");
+    assert_eq!(ranges[0].args[3..], ["64", "64"]);
+}
+
+#[test]
+fn bindings_exceeding_binding_points_and_texture_units_are_skipped() {
+    let (mut context, recorder) = render_context();
+    {
+        let mut capabilities = context
+            .render_resource_context
+            .resources
+            .capabilities
+            .write();
+        capabilities.limits.max_uniform_buffer_bindings = 1;
+        capabilities.limits.max_texture_units = 0;
+    }
+    let (pipeline, layout) = create_pipeline(&context);
+    // only CameraViewProj got binding point, ColorMaterial_texture has no texture unit
+    assert_eq!(recorder.calls_named("uniform_block_binding").len(), 1);
+    assert!(recorder.calls_named("uniform1i").is_empty());
+
+    let descriptor = layout.bind_groups.iter().find(|g| g.index == 2).unwrap();
+    let buffer = context.render_resource_context.create_buffer(BufferInfo {
+        size: 64,
+        buffer_usage: BufferUsage::UNIFORM | BufferUsage::COPY_DST,
+        ..Default::default()
+    });
+    let bind_group = BindGroup::build().add_buffer(0, buffer, 0..64).finish();
+    context
+        .render_resource_context
+        .create_bind_group(descriptor.id, &bind_group);
+    recorder.take_calls();
+    let color = texture(&context, TextureFormat::Rgba8Unorm);
+    context.begin_pass(
+        &offscreen_pass(color, None),
+        &RenderResourceBindings::default(),
+        &mut |pass| {
+            pass.set_pipeline(&pipeline);
+            pass.set_bind_group(2, descriptor.id, bind_group.id, None);
+        },
+    );
+    assert!(recorder.calls_named("bind_buffer_range").is_empty());
+}
+
+/// Draws with vertex buffer bound at offset 24 to `Vertex_Position` (stride 12) with given
+/// step mode and 16 bit index buffer bound at offset 8, returns offsets of its attribute
+/// pointer and recorded draw calls
+fn draw_vertices(
+    step_mode: InputStepMode,
+    draw: impl FnOnce(&mut dyn RenderPass),
+) -> (Vec<String>, Vec<GlCall>) {
+    let (mut context, recorder) = render_context();
+    let (pipeline, _) = create_pipeline(&context);
+    {
+        let mut pipelines = context.render_resource_context.resources.pipelines.write();
+        let descriptor = &mut pipelines
+            .get_mut(&pipeline)
+            .unwrap()
+            .vertex_buffer_descriptors[0];
+        // reflected layout has no strides, bevy's `PipelineCompiler` sets them from the mesh
+        descriptor.stride = 12;
+        descriptor.step_mode = step_mode;
+    }
+    let vertex_buffer = context.render_resource_context.create_buffer(BufferInfo {
+        size: 256,
+        buffer_usage: BufferUsage::VERTEX,
+        ..Default::default()
+    });
+    let index_buffer = context.render_resource_context.create_buffer(BufferInfo {
+        size: 64,
+        buffer_usage: BufferUsage::INDEX,
+        ..Default::default()
+    });
+    let color = texture(&context, TextureFormat::Rgba8Unorm);
+    recorder.take_calls();
+
+    let mut draw = Some(draw);
+    context.begin_pass(
+        &offscreen_pass(color, None),
+        &RenderResourceBindings::default(),
+        &mut |pass| {
+            pass.set_pipeline(&pipeline);
+            pass.set_vertex_buffer(0, vertex_buffer, 24);
+            pass.set_index_buffer(index_buffer, 8, IndexFormat::Uint16);
+            (draw.take().unwrap())(pass);
+        },
+    );
+    let offsets = recorder
+        .calls_named("vertex_attrib_pointer_f32")
+        .into_iter()
+        .map(|call| call.args[5].clone())
+        .collect();
+    let draws = recorder
+        .calls()
+        .into_iter()
+        .filter(|call| call.name.starts_with("draw_"))
+        .collect();
+    (offsets, draws)
+}
+
+#[test]
+fn base_vertex_offsets_vertex_attribute_pointers() {
+    let (offsets, draws) = draw_vertices(InputStepMode::Vertex, |pass| {
+        pass.draw_indexed(0..3, 5, 0..1);
+        pass.draw_indexed(3..6, -2, 0..1);
+    });
+    // 24 + 5 * 12, 24 - 2 * 12
+    assert_eq!(offsets, ["84", "0"]);
+    // offsets of first indices: 8 + 0 * 2, 8 + 3 * 2
+    assert_eq!(draws[0].args[3], "8");
+    assert_eq!(draws[1].args[3], "14");
+}
+
+#[test]
+fn base_vertex_pointing_before_vertex_buffer_is_rejected() {
+    let (offsets, draws) = draw_vertices(InputStepMode::Vertex, |pass| {
+        pass.draw_indexed(0..3, -3, 0..1);
+    });
+    assert!(offsets.is_empty());
+    assert!(draws.is_empty());
+}
+
+#[test]
+fn first_instance_offsets_instance_attribute_pointers() {
+    let (offsets, draws) = draw_vertices(InputStepMode::Instance, |pass| {
+        pass.draw(0..3, 2..4);
+        // base vertex doesn't move per-instance attributes
+        pass.draw_indexed(0..3, -3, 1..2);
+    });
+    // 24 + 2 * 12, 24 + 1 * 12
+    assert_eq!(offsets, ["48", "36"]);
+    assert_eq!(draws[0].name, "draw_arrays_instanced");
+    assert_eq!(draws[0].args[1..], ["0", "3", "2"]);
+    assert_eq!(draws[1].name, "draw_elements_instanced");
+    assert_eq!(draws[1].args[4], "1");
+}
+
+#[test]
+fn vertex_buffers_are_bound_by_slot_with_instance_divisors() {
+    let (mut context, recorder) = render_context();
+    recorder.set_active_attribs(&[
+        ("Vertex_Position", Gl::FLOAT_VEC3),
+        ("I_Transform", Gl::FLOAT_VEC4),
+    ]);
+    recorder.set_attrib_location("I_Transform", 1);
+    let (pipeline, _) = create_pipeline(&context);
+    {
+        let mut pipelines = context.render_resource_context.resources.pipelines.write();
+        let descriptors = &mut pipelines
+            .get_mut(&pipeline)
+            .unwrap()
+            .vertex_buffer_descriptors;
+        assert_eq!(descriptors.len(), 2);
+        descriptors[0].stride = 12;
+        descriptors[1].stride = 16;
+        descriptors[1].step_mode = InputStepMode::Instance;
+    }
+    let buffers = [0, 1]
+        .iter()
+        .map(|_| {
+            context.render_resource_context.create_buffer(BufferInfo {
+                size: 256,
+                buffer_usage: BufferUsage::VERTEX,
+                ..Default::default()
+            })
+        })
+        .collect::<Vec<_>>();
+    let native_buffers = buffers
+        .iter()
+        .map(|id| {
+            let gl_buffers = context.render_resource_context.resources.buffers.read();
+            match &gl_buffers.get(id).unwrap().buffer {
+                Buffer::WebGlBuffer(buffer) => format!("Some({:?})", buffer),
+                Buffer::Data(_) => unreachable!(),
+            }
+        })
+        .collect::<Vec<_>>();
+    let color = texture(&context, TextureFormat::Rgba8Unorm);
+    recorder.take_calls();
+
+    context.begin_pass(
+        &offscreen_pass(color, None),
+        &RenderResourceBindings::default(),
+        &mut |pass| {
+            pass.set_pipeline(&pipeline);
+            // bound in reverse order, slots decide which attributes use them
+            pass.set_vertex_buffer(1, buffers[1], 32);
+            pass.set_vertex_buffer(0, buffers[0], 0);
+            pass.draw(0..3, 0..100);
+        },
+    );
+    let calls = recorder
+        .calls()
+        .into_iter()
+        .filter(|call| {
+            (call.name == "bind_buffer" && call.args[0] == Gl::ARRAY_BUFFER.to_string())
+                || call.name == "vertex_attrib_pointer_f32"
+                || call.name == "vertex_attrib_divisor"
+        })
+        .map(|call| call.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        calls,
+        [
+            format!("bind_buffer({}, {})", Gl::ARRAY_BUFFER, native_buffers[0]),
+            format!(
+                "vertex_attrib_pointer_f32(0, 3, {}, false, 12, 0)",
+                Gl::FLOAT
+            ),
+            "vertex_attrib_divisor(0, 0)".to_string(),
+            format!("bind_buffer({}, {})", Gl::ARRAY_BUFFER, native_buffers[1]),
+            format!(
+                "vertex_attrib_pointer_f32(1, 4, {}, false, 16, 32)",
+                Gl::FLOAT
+            ),
+            "vertex_attrib_divisor(1, 1)".to_string(),
+        ]
+    );
+    assert_eq!(
+        recorder.calls_named("draw_arrays_instanced")[0].args[3],
+        "100"
+    );
+}
+
+/// Sets pipeline with state changed by `set_state` in offscreen pass, followed by `in_pass`,
+/// returns calls recorded from `set_pipeline` on
+fn set_pipeline_calls(
+    set_state: impl FnOnce(&WebGL2RenderResourceContext, &mut WebGL2Pipeline),
+    in_pass: impl FnOnce(&mut dyn RenderPass),
+) -> Vec<String> {
+    let (mut context, recorder) = render_context();
+    let (pipeline, _) = create_pipeline(&context);
+    set_state(
+        &context.render_resource_context,
+        context
+            .render_resource_context
+            .resources
+            .pipelines
+            .write()
+            .get_mut(&pipeline)
+            .unwrap(),
+    );
+    let color = texture(&context, TextureFormat::Rgba8Unorm);
+    let depth = texture(&context, TextureFormat::Depth24PlusStencil8);
+
+    let mut in_pass = Some(in_pass);
+    context.begin_pass(
+        &offscreen_pass(color, Some(depth)),
+        &RenderResourceBindings::default(),
+        &mut |pass| {
+            recorder.take_calls();
+            pass.set_pipeline(&pipeline);
+            (in_pass.take().unwrap())(pass);
+        },
+    );
+    recorder
+        .take_calls()
+        .into_iter()
+        .map(|call| call.to_string())
+        .collect()
+}
+
+#[test]
+fn stencil_and_depth_state_is_set_from_pipeline() {
+    let calls = set_pipeline_calls(
+        |_, pipeline| {
+            pipeline.depth_stencil = Some(DepthStencilState {
+                format: TextureFormat::Depth24PlusStencil8,
+                depth_write_enabled: false,
+                depth_compare: CompareFunction::LessEqual,
+                stencil: StencilState {
+                    front: StencilFaceState {
+                        compare: CompareFunction::Equal,
+                        fail_op: StencilOperation::Keep,
+                        depth_fail_op: StencilOperation::Zero,
+                        pass_op: StencilOperation::Replace,
+                    },
+                    back: StencilFaceState::IGNORE,
+                    read_mask: 0xff,
+                    write_mask: 0x0f,
+                },
+                bias: DepthBiasState {
+                    constant: 2,
+                    slope_scale: 1.5,
+                    clamp: 0.0,
+                },
+            })
+        },
+        |pass| pass.set_stencil_reference(5),
+    );
+    let stencil_calls = calls
+        .into_iter()
+        .filter(|call| call.starts_with("stencil_") || call.starts_with("polygon_offset"))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        stencil_calls,
+        [
+            format!(
+                "stencil_func_separate({}, {}, 0, 255)",
+                Gl::FRONT,
+                Gl::EQUAL
+            ),
+            format!(
+                "stencil_func_separate({}, {}, 0, 255)",
+                Gl::BACK,
+                Gl::ALWAYS
+            ),
+            format!(
+                "stencil_op_separate({}, {}, {}, {})",
+                Gl::FRONT,
+                Gl::KEEP,
+                Gl::ZERO,
+                Gl::REPLACE
+            ),
+            format!(
+                "stencil_op_separate({}, {}, {}, {})",
+                Gl::BACK,
+                Gl::KEEP,
+                Gl::KEEP,
+                Gl::KEEP
+            ),
+            format!("stencil_mask_separate({}, 15)", Gl::FRONT_AND_BACK),
+            "polygon_offset(1.5, 2.0)".to_string(),
+            // reference set after the pipeline
+            format!(
+                "stencil_func_separate({}, {}, 5, 255)",
+                Gl::FRONT,
+                Gl::EQUAL
+            ),
+            format!(
+                "stencil_func_separate({}, {}, 5, 255)",
+                Gl::BACK,
+                Gl::ALWAYS
+            ),
+        ]
+    );
+}
+
+/// Additive blending of first target, alpha-only writes without blending to the second one
+fn additive_and_alpha_mask_targets() -> Vec<ColorTargetState> {
+    let additive = |operation| BlendComponent {
+        src_factor: BlendFactor::Constant,
+        dst_factor: BlendFactor::One,
+        operation,
+    };
+    vec![
+        ColorTargetState {
+            format: TextureFormat::Rgba8Unorm,
+            blend: Some(BlendState {
+                color: additive(BlendOperation::Add),
+                alpha: additive(BlendOperation::Max),
+            }),
+            write_mask: ColorWrite::ALL,
+        },
+        ColorTargetState {
+            format: TextureFormat::Rgba8Unorm,
+            blend: None,
+            write_mask: ColorWrite::ALPHA,
+        },
+    ]
+}
+
+/// Blend state and write mask calls of pipeline with `additive_and_alpha_mask_targets`
+fn blend_calls(draw_buffers_indexed: bool) -> Vec<String> {
+    set_pipeline_calls(
+        |context, pipeline| {
+            context.resources.capabilities.write().draw_buffers_indexed = draw_buffers_indexed;
+            context.set_blend_constant(Color::rgba_linear(0.5, 0.25, 0.0, 1.0));
+            pipeline.color_target_states = additive_and_alpha_mask_targets();
+        },
+        |_| {},
+    )
+    .into_iter()
+    .filter(|call| {
+        call.starts_with("blend_")
+            || call.starts_with("color_mask")
+            || call.starts_with(&format!("enable_i({}", Gl::BLEND))
+            || call.starts_with(&format!("disable_i({}", Gl::BLEND))
+    })
+    .collect()
+}
+
+#[test]
+fn color_targets_get_own_blend_state_with_draw_buffers_indexed() {
+    assert_eq!(
+        blend_calls(true),
+        [
+            format!("enable_i({}, 0)", Gl::BLEND),
+            format!(
+                "blend_func_separate_i(0, {}, {}, {}, {})",
+                Gl::CONSTANT_COLOR,
+                Gl::ONE,
+                Gl::CONSTANT_COLOR,
+                Gl::ONE
+            ),
+            format!(
+                "blend_equation_separate_i(0, {}, {})",
+                Gl::FUNC_ADD,
+                Gl::MAX
+            ),
+            "color_mask_i(0, true, true, true, true)".to_string(),
+            format!("disable_i({}, 1)", Gl::BLEND),
+            "color_mask_i(1, false, false, false, true)".to_string(),
+            "blend_color(0.5, 0.25, 0.0, 1.0)".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn color_targets_share_first_blend_state_without_draw_buffers_indexed() {
+    assert_eq!(
+        blend_calls(false),
+        [
+            format!(
+                "blend_func_separate({}, {}, {}, {})",
+                Gl::CONSTANT_COLOR,
+                Gl::ONE,
+                Gl::CONSTANT_COLOR,
+                Gl::ONE
+            ),
+            format!("blend_equation_separate({}, {})", Gl::FUNC_ADD, Gl::MAX),
+            // write mask of the first target, already set by `begin_pass`
+            "blend_color(0.5, 0.25, 0.0, 1.0)".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn begin_pass_attaches_and_clears_offscreen_targets() {
+    let (mut context, recorder) = render_context();
+    let color = texture(&context, TextureFormat::Rgba8Unorm);
+    let depth = texture(&context, TextureFormat::Depth32Float);
+    recorder.take_calls();
+
+    let pass_descriptor = offscreen_pass(color, Some(depth));
+    context.begin_pass(
+        &pass_descriptor,
+        &RenderResourceBindings::default(),
+        &mut |_| {},
+    );
+
+    assert_eq!(recorder.calls_named("create_framebuffer").len(), 1);
+    let attachments: Vec<_> = recorder
+        .calls_named("framebuffer_texture_2d")
+        .into_iter()
+        .map(|call| call.args[1].clone())
+        .collect();
+    assert_eq!(
+        attachments,
+        vec![
+            Gl::COLOR_ATTACHMENT0.to_string(),
+            Gl::DEPTH_ATTACHMENT.to_string()
+        ]
+    );
+    assert_eq!(
+        recorder.calls_named("viewport")[0].args,
+        ["0", "0", "64", "32"]
+    );
+
+    let clears: Vec<_> = recorder
+        .calls_named("clear_bufferfv")
+        .into_iter()
+        .map(|call| call.args)
+        .collect();
+    assert_eq!(
+        clears,
+        vec![
+            vec![
+                Gl::COLOR.to_string(),
+                "0".to_string(),
+                "[0.0, 0.0, 1.0, 1.0]".to_string()
+            ],
+            vec![Gl::DEPTH.to_string(), "0".to_string(), "[1.0]".to_string()],
+        ]
+    );
+}
+
+/// Clears `format` attachment to `color`, returns name and values of recorded `clearBuffer*` call
+fn clear_color(format: TextureFormat, color: Color) -> (String, String) {
+    let (mut context, recorder) = render_context();
+    let attachment = texture(&context, format);
+    let mut pass_descriptor = offscreen_pass(attachment, None);
+    pass_descriptor.color_attachments[0].ops.load = LoadOp::Clear(color);
+    recorder.take_calls();
+    context.begin_pass(
+        &pass_descriptor,
+        &RenderResourceBindings::default(),
+        &mut |_| {},
+    );
+    let clear = recorder
+        .take_calls()
+        .into_iter()
+        .find(|call| call.name.starts_with("clear_buffer"))
+        .unwrap();
+    (clear.name.to_string(), clear.args[2].clone())
+}
+
+#[test]
+fn integer_attachments_are_cleared_with_raw_components() {
+    assert_eq!(
+        clear_color(
+            TextureFormat::Rgba32Sint,
+            Color::rgba(-1.0, 2.0, 300.0, 0.5)
+        ),
+        ("clear_bufferiv".to_string(), "[-1, 2, 300, 0]".to_string())
+    );
+    assert_eq!(
+        clear_color(TextureFormat::Rgba8Uint, Color::rgba(7.0, 0.5, 1.0, 255.0)),
+        ("clear_bufferuiv".to_string(), "[7, 0, 1, 255]".to_string())
+    );
+}
+
+#[test]
+fn normalized_attachments_are_cleared_with_linear_components() {
+    assert_eq!(
+        clear_color(
+            TextureFormat::Rgba8Snorm,
+            Color::rgba_linear(-2.0, 0.5, 1.5, 1.0)
+        ),
+        (
+            "clear_bufferfv".to_string(),
+            "[-1.0, 0.5, 1.0, 1.0]".to_string()
+        )
+    );
+    // sRGB components are converted to linear ones, encoded back on write
+    let color = Color::rgba(0.5, 0.0, 1.0, 1.0);
+    assert_eq!(
+        clear_color(TextureFormat::Rgba8UnormSrgb, color),
+        (
+            "clear_bufferfv".to_string(),
+            format!("{:?}", color.as_linear_rgba_f32())
+        )
+    );
+}
+
+#[test]
+fn framebuffers_are_cached_per_attachment_set() {
+    let (mut context, recorder) = render_context();
+    let color = texture(&context, TextureFormat::Rgba8Unorm);
+    let depth = texture(&context, TextureFormat::Depth32Float);
+    let bindings = RenderResourceBindings::default();
+
+    context.begin_pass(&offscreen_pass(color, Some(depth)), &bindings, &mut |_| {});
+    context.begin_pass(&offscreen_pass(color, Some(depth)), &bindings, &mut |_| {});
+    assert_eq!(recorder.calls_named("create_framebuffer").len(), 1);
+
+    // color-only pass doesn't reuse framebuffer with depth attached
+    context.begin_pass(&offscreen_pass(color, None), &bindings, &mut |_| {});
+    assert_eq!(recorder.calls_named("create_framebuffer").len(), 2);
+
+    recorder.take_calls();
+    context.render_resource_context.remove_texture(depth);
+    assert_eq!(recorder.calls_named("delete_framebuffer").len(), 1);
+    context.render_resource_context.remove_texture(color);
+    assert_eq!(recorder.calls_named("delete_framebuffer").len(), 2);
+}
+
+#[test]
+fn depth_stencil_attachment_is_cleared_with_clear_bufferfi() {
+    let (mut context, recorder) = render_context();
+    let color = texture(&context, TextureFormat::Rgba8Unorm);
+    let depth = texture(&context, TextureFormat::Depth24PlusStencil8);
+    let mut pass_descriptor = offscreen_pass(color, Some(depth));
+    pass_descriptor
+        .depth_stencil_attachment
+        .as_mut()
+        .unwrap()
+        .stencil_ops = Some(Operations {
+        load: LoadOp::Clear(3),
+        store: true,
+    });
+    recorder.take_calls();
+
+    context.begin_pass(
+        &pass_descriptor,
+        &RenderResourceBindings::default(),
+        &mut |_| {},
+    );
+    assert_eq!(
+        recorder.calls_named("framebuffer_texture_2d")[1].args[1],
+        Gl::DEPTH_STENCIL_ATTACHMENT.to_string()
+    );
+    assert_eq!(
+        recorder.calls_named("clear_bufferfi")[0].args,
+        [
+            Gl::DEPTH_STENCIL.to_string(),
+            "0".to_string(),
+            "1.0".to_string(),
+            "3".to_string()
+        ]
+    );
+    assert_eq!(recorder.calls_named("clear_bufferfv").len(), 1);
+    assert!(recorder.calls_named("clear_bufferiv").is_empty());
+}
+
+/// Pass rendering to the canvas, clearing its depth to 1.0 and stencil to 3
+fn swapchain_pass_clearing_stencil(context: &WebGL2RenderContext) -> PassDescriptor {
+    let depth = texture(context, TextureFormat::Depth24PlusStencil8);
+    let swapchain = context.render_resource_context.swapchain_texture;
+    let mut pass_descriptor = offscreen_pass(swapchain, Some(depth));
+    pass_descriptor
+        .depth_stencil_attachment
+        .as_mut()
+        .unwrap()
+        .stencil_ops = Some(Operations {
+        load: LoadOp::Clear(3),
+        store: true,
+    });
+    pass_descriptor
+}
+
+#[test]
+fn swapchain_depth_and_stencil_are_cleared_with_clear_bufferfi() {
+    let (mut context, recorder) = render_context();
+    let pass_descriptor = swapchain_pass_clearing_stencil(&context);
+    // canvas is created with stencil buffer
+    context
+        .render_resource_context
+        .resources
+        .context_settings
+        .write()
+        .stencil = true;
+    recorder.take_calls();
+
+    context.begin_pass(
+        &pass_descriptor,
+        &RenderResourceBindings::default(),
+        &mut |_| {},
+    );
+    assert!(recorder.calls_named("create_framebuffer").is_empty());
+    assert_eq!(
+        recorder.calls_named("clear")[0].args,
+        [Gl::COLOR_BUFFER_BIT.to_string()]
+    );
+    assert_eq!(
+        recorder.calls_named("clear_bufferfi")[0].args,
+        [
+            Gl::DEPTH_STENCIL.to_string(),
+            "0".to_string(),
+            "1.0".to_string(),
+            "3".to_string()
+        ]
+    );
+}
+
+#[test]
+fn stencil_of_canvas_without_stencil_buffer_is_not_cleared() {
+    // canvas is created without stencil buffer by default
+    let (mut context, recorder) = render_context();
+    let pass_descriptor = swapchain_pass_clearing_stencil(&context);
+    recorder.take_calls();
+
+    context.begin_pass(
+        &pass_descriptor,
+        &RenderResourceBindings::default(),
+        &mut |_| {},
+    );
+    assert!(recorder.calls_named("clear_bufferfi").is_empty());
+    assert_eq!(
+        recorder.calls_named("clear_bufferfv")[0].args,
+        [Gl::DEPTH.to_string(), "0".to_string(), "[1.0]".to_string()]
+    );
+}
+
+#[test]
+fn pass_with_non_depth_depth_attachment_is_skipped() {
+    let (mut context, recorder) = render_context();
+    let color = texture(&context, TextureFormat::Rgba8Unorm);
+    let not_depth = texture(&context, TextureFormat::Rgba8Unorm);
+    recorder.take_calls();
+
+    let mut pass_run = false;
+    context.begin_pass(
+        &offscreen_pass(color, Some(not_depth)),
+        &RenderResourceBindings::default(),
+        &mut |_| pass_run = true,
+    );
+    assert!(!pass_run);
+    assert!(recorder.calls_named("create_framebuffer").is_empty());
+}
+
+#[test]
+fn incomplete_framebuffers_are_skipped_and_not_cached() {
+    let (mut context, recorder) = render_context();
+    let color = texture(&context, TextureFormat::Rgba8Unorm);
+    let bindings = RenderResourceBindings::default();
+    recorder.set_framebuffer_status(Gl::FRAMEBUFFER_INCOMPLETE_ATTACHMENT);
+    recorder.take_calls();
+
+    let mut pass_run = false;
+    context.begin_pass(&offscreen_pass(color, None), &bindings, &mut |_| {
+        pass_run = true
+    });
+    assert!(!pass_run);
+    assert!(recorder.calls_named("clear_bufferfv").is_empty());
+    let created = recorder.calls_named("create_framebuffer")[0].args[0].clone();
+    assert_eq!(
+        recorder.calls_named("delete_framebuffer")[0].args[0],
+        created
+    );
+
+    let buffer = context.render_resource_context.create_buffer(BufferInfo {
+        size: 64 * 32 * 4,
+        buffer_usage: BufferUsage::MAP_READ | BufferUsage::COPY_DST,
+        ..Default::default()
+    });
+    context.copy_texture_to_buffer(
+        color,
+        [0, 0, 0],
+        0,
+        buffer,
+        0,
+        64 * 4,
+        Extent3d::new(64, 32, 1),
+    );
+    assert!(recorder
+        .calls_named("read_pixels_with_pbo_offset")
+        .is_empty());
+    assert_eq!(recorder.calls_named("delete_framebuffer").len(), 2);
+
+    // checked again once the attachments become complete
+    recorder.set_framebuffer_status(Gl::FRAMEBUFFER_COMPLETE);
+    context.begin_pass(&offscreen_pass(color, None), &bindings, &mut |_| {
+        pass_run = true
+    });
+    assert!(pass_run);
+    assert_eq!(recorder.calls_named("create_framebuffer").len(), 3);
+    assert_eq!(recorder.calls_named("delete_framebuffer").len(), 2);
+}
+
+#[test]
+fn multisampled_pass_is_rendered_to_renderbuffer_and_resolved() {
+    let (mut context, recorder) = render_context();
+    let multisampled = context
+        .render_resource_context
+        .create_texture(TextureDescriptor {
+            size: Extent3d::new(64, 32, 1),
+            // clamped to MAX_SAMPLES
+            sample_count: 8,
+            format: TextureFormat::Rgba8Unorm,
+            ..Default::default()
+        });
+    let resolve_target = texture(&context, TextureFormat::Rgba8Unorm);
+    assert_eq!(
+        recorder.calls_named("renderbuffer_storage_multisample")[0].args,
+        [Gl::RENDERBUFFER, 4, Gl::RGBA8, 64, 32]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(recorder.calls_named("create_texture").len(), 1);
+    let resources = &context.render_resource_context.resources;
+    let renderbuffer = resources.renderbuffers.read()[&multisampled];
+    let resolve_texture = resources.textures.read()[&resolve_target];
+    let mut pass_descriptor = offscreen_pass(multisampled, None);
+    pass_descriptor.color_attachments[0].resolve_target =
+        Some(TextureAttachment::Id(resolve_target));
+    recorder.take_calls();
+
+    for _ in 0..2 {
+        context.begin_pass(
+            &pass_descriptor,
+            &RenderResourceBindings::default(),
+            &mut |_| {},
+        );
+    }
+    assert_eq!(
+        recorder.calls_named("framebuffer_renderbuffer")[0].to_string(),
+        format!(
+            "framebuffer_renderbuffer({}, {}, {}, Some({:?}))",
+            Gl::FRAMEBUFFER,
+            Gl::COLOR_ATTACHMENT0,
+            Gl::RENDERBUFFER,
+            renderbuffer
+        )
+    );
+    assert_eq!(
+        recorder.calls_named("framebuffer_texture_2d")[0].to_string(),
+        format!(
+            "framebuffer_texture_2d({}, {}, {}, Some({:?}), 0)",
+            Gl::DRAW_FRAMEBUFFER,
+            Gl::COLOR_ATTACHMENT0,
+            Gl::TEXTURE_2D,
+            resolve_texture
+        )
+    );
+    // pass and resolve framebuffers are reused by the second pass
+    assert_eq!(recorder.calls_named("create_framebuffer").len(), 2);
+    let blits = recorder.calls_named("blit_framebuffer");
+    assert_eq!(blits.len(), 2);
+    assert_eq!(
+        blits[0].to_string(),
+        format!(
+            "blit_framebuffer(0, 0, 64, 32, 0, 0, 64, 32, {}, {})",
+            Gl::COLOR_BUFFER_BIT,
+            Gl::NEAREST
+        )
+    );
+}
+
+#[test]
+fn texture_copies_reuse_framebuffers_and_unbind_destination() {
+    let (mut context, recorder) = render_context();
+    let src = texture(&context, TextureFormat::Rgba8Unorm);
+    // not color renderable, copied with copyTexSubImage2D
+    let dst = texture(&context, TextureFormat::Rgba8Snorm);
+    let size = Extent3d::new(64, 32, 1);
+
+    context.copy_texture_to_texture(src, [0, 0, 0], 0, dst, [0, 0, 0], 0, size);
+    context.copy_texture_to_texture(src, [0, 0, 0], 0, dst, [0, 0, 0], 0, size);
+    assert_eq!(recorder.calls_named("create_framebuffer").len(), 2);
+    assert!(recorder.calls_named("delete_framebuffer").is_empty());
+    assert_eq!(recorder.calls_named("check_framebuffer_status").len(), 2);
+    assert_eq!(recorder.calls_named("copy_tex_sub_image_2d").len(), 2);
+
+    let bind_texture = recorder.calls_named("bind_texture");
+    assert_eq!(bind_texture.last().unwrap().args[1], "None");
+}
+
+#[test]
+fn buffer_region_is_uploaded_with_unpack_parameters() {
+    let (mut context, recorder) = render_context();
+    let texture = texture(&context, TextureFormat::Rgba8Unorm);
+    let buffer = context.render_resource_context.create_buffer_with_data(
+        BufferInfo {
+            buffer_usage: BufferUsage::MAP_WRITE | BufferUsage::COPY_SRC,
+            ..Default::default()
+        },
+        &[0; 64 * 32 * 4],
+    );
+    recorder.take_calls();
+
+    // 16x8 region starting at 3rd pixel of 2nd row of 64 pixels wide source
+    let bytes_per_row = 64 * 4;
+    let source_offset = 2 * bytes_per_row + 3 * 4;
+    context.copy_buffer_to_texture(
+        buffer,
+        source_offset,
+        bytes_per_row as u32,
+        texture,
+        [8, 4, 0],
+        0,
+        Extent3d::new(16, 8, 1),
+    );
+    let unpack = recorder
+        .calls_named("pixel_storei")
+        .into_iter()
+        .map(|call| (call.args[0].parse().unwrap(), call.args[1].parse().unwrap()))
+        .collect::<Vec<(u32, i32)>>();
+    assert_eq!(
+        unpack,
+        [
+            (Gl::UNPACK_ALIGNMENT, 1),
+            (Gl::UNPACK_ROW_LENGTH, 64),
+            (Gl::UNPACK_SKIP_PIXELS, 3),
+            (Gl::UNPACK_SKIP_ROWS, 2),
+            (Gl::UNPACK_ALIGNMENT, 4),
+            (Gl::UNPACK_ROW_LENGTH, 0),
+            (Gl::UNPACK_SKIP_ROWS, 0),
+            (Gl::UNPACK_SKIP_PIXELS, 0),
+        ]
+    );
+    // only the region is updated, the texture isn't re-specified
+    assert!(recorder.calls_named("tex_image_2d").is_empty());
+    let upload = &recorder.calls_named("tex_sub_image_2d")[0];
+    assert_eq!(
+        upload.args[..6],
+        [Gl::TEXTURE_2D, 0, 8, 4, 16, 8]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn array_and_3d_textures_are_uploaded_with_tex_sub_image_3d() {
+    let (mut context, recorder) = render_context();
+    for (dimension, target) in [
+        (TextureDimension::D2, Gl::TEXTURE_2D_ARRAY),
+        (TextureDimension::D3, Gl::TEXTURE_3D),
+    ]
+    .iter()
+    {
+        let size = Extent3d::new(16, 8, 4);
+        let texture = context
+            .render_resource_context
+            .create_texture(TextureDescriptor {
+                size,
+                dimension: *dimension,
+                format: TextureFormat::Rgba8Unorm,
+                ..Default::default()
+            });
+        let buffer = context.render_resource_context.create_buffer_with_data(
+            BufferInfo {
+                buffer_usage: BufferUsage::MAP_WRITE | BufferUsage::COPY_SRC,
+                ..Default::default()
+            },
+            &[0; 16 * 8 * 4 * 4],
+        );
+        // storage of all layers is allocated at once
+        assert_eq!(
+            recorder.calls_named("tex_storage_3d")[0].args,
+            [*target, 1, Gl::RGBA8, 16, 8, 4]
+                .iter()
+                .map(|arg| arg.to_string())
+                .collect::<Vec<_>>()
+        );
+        assert!(recorder.calls_named("tex_image_2d").is_empty());
+        recorder.take_calls();
+
+        context.copy_buffer_to_texture(buffer, 0, 16 * 4, texture, [0, 0, 0], 0, size);
+        let calls = recorder.take_calls();
+        let upload = calls
+            .iter()
+            .find(|call| call.name == "tex_sub_image_3d")
+            .unwrap();
+        assert_eq!(
+            upload.args,
+            [
+                *target,
+                0,
+                0,
+                0,
+                0,
+                16,
+                8,
+                4,
+                Gl::RGBA,
+                Gl::UNSIGNED_BYTE,
+                16 * 8 * 4 * 4
+            ]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect::<Vec<_>>()
+        );
+        assert!(calls.iter().any(
+            |call| call.to_string() == format!("pixel_storei({}, 8)", Gl::UNPACK_IMAGE_HEIGHT)
+        ));
+    }
+}
+
+/// Binds texture and sampler of `ColorMaterial_texture` (group 1), returns recorded
+/// `create_sampler` and `bind_sampler` calls
+fn bind_texture_and_sampler(texture_format: TextureFormat) -> (Vec<String>, Vec<Vec<String>>) {
+    let (mut context, recorder) = render_context();
+    let texture_uniform_type = match texture_format {
+        TextureFormat::Rgba8Uint => Gl::UNSIGNED_INT_SAMPLER_2D,
+        _ => Gl::SAMPLER_2D,
+    };
+    recorder.set_active_uniforms(&[("ColorMaterial_texture", texture_uniform_type)]);
+    let (pipeline, layout) = create_pipeline(&context);
+    let descriptor = layout.bind_groups.iter().find(|g| g.index == 1).unwrap();
+
+    let sampled = texture(&context, texture_format);
+    let sampler = context
+        .render_resource_context
+        .create_sampler(&SamplerDescriptor {
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            ..Default::default()
+        });
+    let bind_group = BindGroup::build()
+        .add_texture(1, sampled)
+        .add_sampler(2, sampler)
+        .finish();
+    context
+        .render_resource_context
+        .create_bind_group(descriptor.id, &bind_group);
+    let samplers = recorder
+        .calls_named("create_sampler")
+        .into_iter()
+        .map(|call| call.args[0].clone())
+        .collect();
+    recorder.take_calls();
+
+    let color = texture(&context, TextureFormat::Rgba8Unorm);
+    context.begin_pass(
+        &offscreen_pass(color, None),
+        &RenderResourceBindings::default(),
+        &mut |pass| {
+            pass.set_pipeline(&pipeline);
+            pass.set_bind_group(1, descriptor.id, bind_group.id, None);
+        },
+    );
+    let bound = recorder
+        .calls_named("bind_sampler")
+        .into_iter()
+        .map(|call| call.args)
+        .collect();
+    (samplers, bound)
+}
+
+#[test]
+fn sampler_is_bound_to_texture_unit_of_its_texture() {
+    let (samplers, bound) = bind_texture_and_sampler(TextureFormat::Rgba8Unorm);
+    // filtering sampler and its NEAREST variant
+    assert_eq!(samplers.len(), 2);
+    assert_eq!(
+        bound,
+        vec![vec!["0".to_string(), format!("Some({})", samplers[0])]]
+    );
+}
+
+#[test]
+fn integer_texture_is_sampled_without_filtering() {
+    let (samplers, bound) = bind_texture_and_sampler(TextureFormat::Rgba8Uint);
+    assert_eq!(
+        bound,
+        vec![vec!["0".to_string(), format!("Some({})", samplers[1])]]
+    );
+}
+
+fn cube_descriptor(width: u32, height: u32) -> TextureDescriptor {
+    TextureDescriptor {
+        size: Extent3d::new(width, height, 6),
+        format: TextureFormat::Rgba8Unorm,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn six_layer_texture_bound_as_cube_map_is_converted() {
+    let (context, recorder) = render_context();
+    recorder.set_active_uniforms(&[("ColorMaterial_texture", Gl::SAMPLER_CUBE)]);
+    let (_, layout) = create_pipeline(&context);
+    let descriptor = layout.bind_groups.iter().find(|g| g.index == 1).unwrap();
+    let resource_context = &context.render_resource_context;
+    // `create_texture` doesn't know the view dimension, creates 2D array
+    let cube = resource_context.create_texture(cube_descriptor(16, 16));
+    assert_eq!(
+        resource_context.resources.texture_target(cube),
+        Gl::TEXTURE_2D_ARRAY
+    );
+    let array_texture = recorder.calls_named("create_texture")[0].args[0].clone();
+    recorder.take_calls();
+
+    let bind_group = BindGroup::build().add_texture(1, cube).finish();
+    resource_context.create_bind_group(descriptor.id, &bind_group);
+    assert_eq!(
+        resource_context.resources.texture_target(cube),
+        Gl::TEXTURE_CUBE_MAP
+    );
+    assert_eq!(
+        recorder.calls_named("tex_storage_2d")[0].args[0],
+        Gl::TEXTURE_CUBE_MAP.to_string()
+    );
+    let faces: Vec<_> = recorder
+        .calls_named("copy_tex_sub_image_2d")
+        .into_iter()
+        .map(|call| call.args[0].clone())
+        .collect();
+    assert_eq!(
+        faces,
+        CUBE_MAP_FACES
+            .iter()
+            .map(|face| face.to_string())
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        recorder.calls_named("delete_texture")[0].args[0],
+        array_texture
+    );
+}
+
+#[test]
+fn cube_map_without_square_layers_falls_back_to_2d_array() {
+    let (context, recorder) = render_context();
+    let resource_context = &context.render_resource_context;
+    let texture = resource_context
+        .create_texture_with_view_dimension(cube_descriptor(16, 8), TextureViewDimension::Cube);
+    assert_eq!(
+        resource_context.resources.texture_target(texture),
+        Gl::TEXTURE_2D_ARRAY
+    );
+    assert_eq!(
+        recorder.calls_named("tex_storage_3d")[0].args[0],
+        Gl::TEXTURE_2D_ARRAY.to_string()
+    );
+}
+
+#[test]
+fn cube_map_face_is_copied_to_buffer() {
+    let (mut context, recorder) = render_context();
+    let cube = context
+        .render_resource_context
+        .create_texture_with_view_dimension(cube_descriptor(16, 16), TextureViewDimension::Cube);
+    let gl_texture = recorder.calls_named("create_texture")[0].args[0].clone();
+    let buffer = context.render_resource_context.create_buffer(BufferInfo {
+        size: 16 * 16 * 4,
+        buffer_usage: BufferUsage::MAP_READ | BufferUsage::COPY_DST,
+        ..Default::default()
+    });
+    recorder.take_calls();
+
+    context.copy_texture_to_buffer(
+        cube,
+        [0, 0, 3],
+        0,
+        buffer,
+        0,
+        16 * 4,
+        Extent3d::new(16, 16, 1),
+    );
+    let attached: Vec<_> = recorder
+        .calls_named("framebuffer_texture_2d")
+        .into_iter()
+        .map(|call| call.args[2..4].to_vec())
+        .collect();
+    assert_eq!(
+        attached[0],
+        [
+            Gl::TEXTURE_CUBE_MAP_NEGATIVE_Y.to_string(),
+            format!("Some({})", gl_texture)
+        ]
+    );
+    assert_eq!(recorder.calls_named("read_pixels_with_pbo_offset").len(), 1);
+    // the reused framebuffer doesn't keep the texture attached
+    assert_eq!(attached[1][1], "None");
+}
+
+#[test]
+fn compressed_texture_fails_for_unsupported_format() {
+    let (context, recorder) = render_context();
+    let resource_context = &context.render_resource_context;
+    let descriptor = TextureDescriptor {
+        size: Extent3d::new(16, 16, 1),
+        ..Default::default()
+    };
+    let created = resource_context.create_compressed_texture(
+        descriptor,
+        TextureViewDimension::D2,
+        CompressedTextureFormat::Bc1RgbaUnorm,
+    );
+    assert!(created.is_err());
+    assert!(recorder.calls_named("create_texture").is_empty());
+
+    resource_context
+        .resources
+        .compressed_texture_formats
+        .write()
+        .insert(CompressedTextureFormat::Bc1RgbaUnorm);
+    let created = resource_context.create_compressed_texture(
+        descriptor,
+        TextureViewDimension::D2,
+        CompressedTextureFormat::Bc1RgbaUnorm,
+    );
+    assert!(created.is_ok());
+    assert_eq!(recorder.calls_named("create_texture").len(), 1);
+}
+
+#[test]
+fn texture_atlas_rects_are_stored_in_data_texture() {
+    let (context, recorder) = render_context();
+    // 3000 `Rect`s of `TextureAtlas::textures`, bound as storage buffer
+    let size = 3000 * 16;
+    context.render_resource_context.create_buffer_with_data(
+        BufferInfo {
+            size,
+            buffer_usage: BufferUsage::STORAGE | BufferUsage::COPY_DST,
+            ..Default::default()
+        },
+        &vec![0; size],
+    );
+    // rows of MAX_TEXTURE_SIZE (2048 by default) texels
+    let storage = recorder.calls_named("tex_storage_2d");
+    assert_eq!(storage.len(), 1);
+    assert_eq!(
+        storage[0].args[1..],
+        [
+            "1".to_string(),
+            Gl::RGBA32UI.to_string(),
+            "2048".to_string(),
+            "2".to_string()
+        ]
+    );
+}
+
+/// Sprite pipeline with batched variant, bind groups of sprites using one of `textures`
+struct SpriteScene {
+    pipeline: Handle<PipelineDescriptor>,
+    layout: PipelineLayout,
+    vertex_buffer: BufferId,
+    index_buffer: BufferId,
+    texture_bind_groups: Vec<BindGroup>,
+    sprite_bind_groups: Vec<BindGroup>,
+}
+
+fn sprite_scene(context: &WebGL2RenderContext, recorder: &RecordingBackend) -> SpriteScene {
+    recorder.set_uniform_blocks(&[("CameraViewProj", 64), ("Transform", 64), ("Sprite", 16)]);
+    let (pipeline, layout) = create_pipeline_with_vertex_shader(context, SPRITE_VERTEX_SHADER);
+    let resource_context = &context.render_resource_context;
+    let resources = &resource_context.resources;
+    // what `create_sprite_batch_pipelines` does for compiled variants of sprite pipelines
+    resources
+        .pipelines
+        .write()
+        .get_mut(&pipeline)
+        .unwrap()
+        .sprite_batch = Some(GlSpriteBatchPipeline {
+        kind: SpriteBatchKind::Sprite,
+        program: recorder.create_program().unwrap(),
+        vao: recorder.create_vertex_array().unwrap(),
+        instance_buffer: recorder.create_buffer().unwrap(),
+    });
+    let sprite_layout = layout.get_bind_group(SPRITE_BIND_GROUP_INDEX).unwrap();
+    resources
+        .sprite_bind_group_layouts
+        .write()
+        .insert(sprite_layout.id);
+    let texture_layout = layout.get_bind_group(1).unwrap();
+
+    let buffer = |size: usize, buffer_usage: BufferUsage| {
+        resource_context.create_buffer_with_data(
+            BufferInfo {
+                size,
+                buffer_usage,
+                ..Default::default()
+            },
+            &vec![0; size],
+        )
+    };
+    let vertex_buffer = buffer(4 * 12, BufferUsage::VERTEX);
+    let index_buffer = buffer(6 * 2, BufferUsage::INDEX);
+    let texture_bind_groups = (0..2)
+        .map(|_| {
+            let bind_group = BindGroup::build()
+                .add_texture(1, texture(context, TextureFormat::Rgba8Unorm))
+                .finish();
+            resource_context.create_bind_group(texture_layout.id, &bind_group);
+            bind_group
+        })
+        .collect();
+    // one uniform buffer with `Transform` and `Sprite` blocks of all sprites
+    let uniforms = buffer(3 * 256, BufferUsage::UNIFORM | BufferUsage::COPY_DST);
+    let sprite_bind_groups = (0..3)
+        .map(|i| {
+            let offset = i * 256;
+            let bind_group = BindGroup::build()
+                .add_buffer(0, uniforms, offset..offset + 64)
+                .add_buffer(1, uniforms, offset + 128..offset + 144)
+                .finish();
+            resource_context.create_bind_group(sprite_layout.id, &bind_group);
+            bind_group
+        })
+        .collect();
+    SpriteScene {
+        pipeline,
+        layout,
+        vertex_buffer,
+        index_buffer,
+        texture_bind_groups,
+        sprite_bind_groups,
+    }
+}
+
+/// Draws sprites using textures of given indices, returns recorded `draw_elements_instanced` calls
+fn draw_sprites(textures: &[usize]) -> (Vec<GlCall>, RecordingBackend) {
+    let (mut context, recorder) = render_context();
+    let scene = sprite_scene(&context, &recorder);
+    let texture_layout = scene.layout.get_bind_group(1).unwrap();
+    let sprite_layout = scene
+        .layout
+        .get_bind_group(SPRITE_BIND_GROUP_INDEX)
+        .unwrap();
+    let color = texture(&context, TextureFormat::Rgba8Unorm);
+    recorder.take_calls();
+
+    context.begin_pass(
+        &offscreen_pass(color, None),
+        &RenderResourceBindings::default(),
+        &mut |pass| {
+            pass.set_pipeline(&scene.pipeline);
+            pass.set_vertex_buffer(0, scene.vertex_buffer, 0);
+            pass.set_index_buffer(scene.index_buffer, 0, IndexFormat::Uint16);
+            for (sprite, texture) in textures.iter().enumerate() {
+                let texture_bind_group = &scene.texture_bind_groups[*texture];
+                pass.set_bind_group(1, texture_layout.id, texture_bind_group.id, None);
+                let sprite_bind_group = &scene.sprite_bind_groups[sprite];
+                pass.set_bind_group(2, sprite_layout.id, sprite_bind_group.id, None);
+                pass.draw_indexed(0..6, 0, 0..1);
+            }
+        },
+    );
+    (recorder.calls_named("draw_elements_instanced"), recorder)
+}
+
+#[test]
+fn sprites_sharing_texture_are_drawn_with_one_instanced_call() {
+    let (draws, recorder) = draw_sprites(&[0, 0]);
+    assert_eq!(draws.len(), 1);
+    // 6 indices of 2 sprites
+    assert_eq!(draws[0].args[1], "6");
+    assert_eq!(draws[0].args[4], "2");
+    // per-sprite uniforms are instance data, sprite bind group isn't bound
+    assert!(recorder.calls_named("bind_buffer_range").is_empty());
+    let instances = recorder.calls_named("buffer_data_u8_slice");
+    assert_eq!(
+        instances.last().unwrap().args[1],
+        (2 * SpriteBatchKind::Sprite.instance_stride()).to_string()
+    );
+}
+
+#[test]
+fn texture_change_splits_sprite_batch() {
+    let (draws, _) = draw_sprites(&[0, 1, 1]);
+    let instance_counts: Vec<_> = draws.iter().map(|call| call.args[4].clone()).collect();
+    assert_eq!(instance_counts, ["1", "2"]);
+}
+
+#[test]
+fn sprite_instance_attributes_follow_uniform_blocks() {
+    let (_, recorder) = draw_sprites(&[0]);
+    let pointers: Vec<_> = recorder
+        .calls()
+        .into_iter()
+        .filter(|call| call.name.starts_with("vertex_attrib_pointer"))
+        .map(|call| (call.args[0].clone(), call.args.last().unwrap().clone()))
+        .collect();
+    // Transform matrix columns, then `size` and `flip` of `Sprite` block
+    let instance_pointers = &pointers[pointers.len() - 6..];
+    let offsets: Vec<_> = instance_pointers
+        .iter()
+        .map(|(location, offset)| (location.as_str(), offset.as_str()))
+        .collect();
+    assert_eq!(
+        offsets,
+        [
+            ("3", "0"),
+            ("4", "16"),
+            ("5", "32"),
+            ("6", "48"),
+            ("7", "64"),
+            ("8", "72")
+        ]
+    );
+
+    // `TextureAtlasSprite`: color, index and flip
+    let attributes: Vec<_> = SpriteBatchKind::SpriteSheet
+        .instance_attributes()
+        .iter()
+        .map(|attribute| (attribute.location, attribute.offset))
+        .collect();
+    assert_eq!(attributes[4..], [(7, 64), (8, 80), (9, 84)]);
+    assert_eq!(SpriteBatchKind::SpriteSheet.instance_stride(), 96);
+    let mut instance = Vec::new();
+    SpriteBatchKind::SpriteSheet.write_instance(&mut instance, &[1; 64], &[2; 24]);
+    assert_eq!(instance.len(), 96);
+    assert_eq!(instance[63..66], [1, 2, 2]);
+    assert_eq!(instance[87..], [2, 0, 0, 0, 0, 0, 0, 0, 0]);
+}
+
+#[test]
+fn only_uniform_buffers_of_sprite_bind_groups_are_shadowed() {
+    let (context, recorder) = render_context();
+    let scene = sprite_scene(&context, &recorder);
+    let buffers = context.render_resource_context.resources.buffers.read();
+    let shadowed = |buffer: &BufferId| buffers.get(buffer).unwrap().shadow.is_some();
+    assert!(!shadowed(&scene.vertex_buffer));
+    assert!(!shadowed(&scene.index_buffer));
+    let uniforms = scene.sprite_bind_groups[0]
+        .indexed_bindings
+        .iter()
+        .filter_map(|binding| match &binding.entry {
+            RenderResourceBinding::Buffer { buffer, .. } => Some(*buffer),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert!(uniforms.iter().all(shadowed));
+    // shared buffer is read back once
+    assert_eq!(recorder.calls_named("get_buffer_sub_data").len(), 1);
+}
@@ -1,4 +1,4 @@
-use super::{Gl, WebGl2RenderingContext};
+use super::{Gl, GlBackend};
 use crate::{gl_call, GlBindGroups, GlProgram, GlShader, GlVertexFormat};
 use bevy::log::prelude::*;
 use bevy::render::{
@@ -10,10 +10,9 @@ use bevy::render::{
 };
 use bevy::utils::HashSet;
 use std::iter::Extend;
-use web_sys::WebGlActiveInfo;
 
 pub fn compile_shader(
-    context: &WebGl2RenderingContext,
+    context: &dyn GlBackend,
     shader_type: u32,
     source: &str,
 ) -> Result<GlShader, String> {
@@ -38,46 +37,47 @@ pub fn compile_shader(
         );
     }
 
+    // storage buffers emulated with data textures, see `GlStorageTexture`
+    let storage_buffer_re =
+        regex::Regex::new(r"usampler2D\s+(\w+)\s*;\s*//[^\n]*binding\s*=\s*\d+[, ]+buffer\b")
+            .unwrap();
+    let storage_buffers = storage_buffer_re
+        .captures_iter(source)
+        .map(|cap| cap[1].to_string())
+        .collect();
+
     let shader = gl_call!(context.create_shader(shader_type))
         .ok_or_else(|| String::from("Unable to create shader object"))?;
-    gl_call!(context.shader_source(&shader, source));
-    gl_call!(context.compile_shader(&shader));
-
-    if gl_call!(context.get_shader_parameter(&shader, WebGl2RenderingContext::COMPILE_STATUS))
-        .as_bool()
-        .unwrap_or(false)
-    {
-        Ok(GlShader::new(shader, bind_groups))
+    gl_call!(context.shader_source(shader, source));
+    gl_call!(context.compile_shader(shader));
+
+    if gl_call!(context.get_shader_compile_status(shader)) {
+        Ok(GlShader::new(shader, bind_groups, storage_buffers))
     } else {
         Err(context
-            .get_shader_info_log(&shader)
+            .get_shader_info_log(shader)
             .unwrap_or_else(|| String::from("Unknown error creating shader")))
     }
 }
 
-pub fn link_program(
-    context: &WebGl2RenderingContext,
-    shaders: &[GlShader],
-) -> Result<GlProgram, String> {
+pub fn link_program(context: &dyn GlBackend, shaders: &[GlShader]) -> Result<GlProgram, String> {
     let program = gl_call!(context.create_program())
         .ok_or_else(|| String::from("Unable to create shader object"))?;
 
     let mut bind_groups = GlBindGroups::default();
+    let mut storage_buffers = HashSet::default();
     for shader in shaders {
-        gl_call!(context.attach_shader(&program, &shader.shader));
+        gl_call!(context.attach_shader(program, shader.shader));
         bind_groups.extend(shader.bind_groups.clone());
+        storage_buffers.extend(shader.storage_buffers.iter().cloned());
     }
-    gl_call!(context.link_program(&program));
-
-    if context
-        .get_program_parameter(&program, WebGl2RenderingContext::LINK_STATUS)
-        .as_bool()
-        .unwrap_or(false)
-    {
-        Ok(GlProgram::new(program, bind_groups))
+    gl_call!(context.link_program(program));
+
+    if context.get_program_link_status(program) {
+        Ok(GlProgram::new(program, bind_groups, storage_buffers))
     } else {
         Err(context
-            .get_program_info_log(&program)
+            .get_program_info_log(program)
             .unwrap_or_else(|| String::from("Unknown error creating program object")))
     }
 }
@@ -100,32 +100,30 @@ fn get_vertex_format(gl_type: u32) -> VertexFormat {
     }
 }
 
-pub fn reflect_layout(context: &WebGl2RenderingContext, program: &GlProgram) -> PipelineLayout {
+pub fn reflect_layout(context: &dyn GlBackend, program: &GlProgram) -> PipelineLayout {
     let gl = context;
     let mut shader_location = 0;
     info!("program bind groups: {:?}", program.bind_groups);
-    let active_attributes = gl
-        .get_program_parameter(&program.program, Gl::ACTIVE_ATTRIBUTES)
-        .as_f64()
-        .unwrap() as u32;
+    let active_attributes =
+        gl.get_program_parameter_i32(program.program, Gl::ACTIVE_ATTRIBUTES) as u32;
 
     let mut vertex_buffer_descriptors = vec![];
 
     for index in 0..active_attributes {
-        let info: WebGlActiveInfo = gl.get_active_attrib(&program.program, index).unwrap();
-        let name = info.name();
+        let info = gl.get_active_attrib(program.program, index).unwrap();
+        let name = info.name.as_str();
         if name == "gl_VertexID" || name == "gl_InstanceID" {
             continue;
         }
 
-        let format = get_vertex_format(info.type_());
+        let format = get_vertex_format(info.type_);
 
         vertex_buffer_descriptors.push(VertexBufferLayout {
-            name: info.name().into(),
+            name: info.name.clone().into(),
             stride: 0,
             step_mode: InputStepMode::Vertex,
             attributes: vec![VertexAttribute {
-                name: info.name().into(),
+                name: info.name.clone().into(),
                 offset: 0,
                 format,
                 shader_location,
@@ -135,10 +133,8 @@ pub fn reflect_layout(context: &WebGl2RenderingContext, program: &GlProgram) ->
     }
     let mut bind_groups: Vec<BindGroupDescriptor> = Vec::new();
 
-    let active_uniform_blocks = gl
-        .get_program_parameter(&program.program, Gl::ACTIVE_UNIFORM_BLOCKS)
-        .as_f64()
-        .unwrap() as u32;
+    let active_uniform_blocks =
+        gl.get_program_parameter_i32(program.program, Gl::ACTIVE_UNIFORM_BLOCKS) as u32;
 
     let mut used_indices: HashSet<u32> = HashSet::default();
     used_indices.extend(bind_groups.iter().map(|g| g.index));
@@ -157,7 +153,7 @@ pub fn reflect_layout(context: &WebGl2RenderingContext, program: &GlProgram) ->
 
     for uniform_index in 0..active_uniform_blocks {
         let name = gl
-            .get_active_uniform_block_name(&program.program, uniform_index)
+            .get_active_uniform_block_name(program.program, uniform_index)
             .unwrap();
 
         if name == "CameraPosition" {
@@ -208,15 +204,11 @@ pub fn reflect_layout(context: &WebGl2RenderingContext, program: &GlProgram) ->
             continue;
         }
 
-        let size = gl
-            .get_active_uniform_block_parameter(
-                &program.program,
-                uniform_index,
-                Gl::UNIFORM_BLOCK_DATA_SIZE,
-            )
-            .unwrap()
-            .as_f64()
-            .unwrap() as u32;
+        let size = gl.get_active_uniform_block_parameter_i32(
+            program.program,
+            uniform_index,
+            Gl::UNIFORM_BLOCK_DATA_SIZE,
+        ) as u32;
         // let active_uniforms = gl
         //     .get_active_uniform_block_parameter(
         //         &program,
@@ -267,23 +259,29 @@ pub fn reflect_layout(context: &WebGl2RenderingContext, program: &GlProgram) ->
         }
     }
 
-    let active_uniforms = gl
-        .get_program_parameter(&program.program, Gl::ACTIVE_UNIFORMS)
-        .as_f64()
-        .unwrap() as u32;
+    let active_uniforms = gl.get_program_parameter_i32(program.program, Gl::ACTIVE_UNIFORMS) as u32;
     for uniform_index in 0..active_uniforms {
         let info = gl
-            .get_active_uniform(&program.program, uniform_index)
+            .get_active_uniform(program.program, uniform_index)
             .unwrap();
-        let name = info.name();
-
-        if [
-            Gl::SAMPLER_2D,
-            Gl::UNSIGNED_INT_SAMPLER_2D,
-            Gl::INT_SAMPLER_2D,
-        ]
-        .contains(&info.type_())
-        {
+        let name = info.name;
+
+        let view_dimension = match info.type_ {
+            Gl::SAMPLER_2D | Gl::UNSIGNED_INT_SAMPLER_2D | Gl::INT_SAMPLER_2D => {
+                Some(TextureViewDimension::D2)
+            }
+            Gl::SAMPLER_CUBE | Gl::UNSIGNED_INT_SAMPLER_CUBE | Gl::INT_SAMPLER_CUBE => {
+                Some(TextureViewDimension::Cube)
+            }
+            Gl::SAMPLER_2D_ARRAY | Gl::UNSIGNED_INT_SAMPLER_2D_ARRAY | Gl::INT_SAMPLER_2D_ARRAY => {
+                Some(TextureViewDimension::D2Array)
+            }
+            Gl::SAMPLER_3D | Gl::UNSIGNED_INT_SAMPLER_3D | Gl::INT_SAMPLER_3D => {
+                Some(TextureViewDimension::D3)
+            }
+            _ => None,
+        };
+        if let Some(view_dimension) = view_dimension {
             let (group_index, index) =
                 if let Some((group_index, index)) = program.bind_groups.get(&name) {
                     (*group_index, *index)
@@ -291,21 +289,67 @@ pub fn reflect_layout(context: &WebGl2RenderingContext, program: &GlProgram) ->
                     (next_group_index(&mut used_indices), 0)
                 };
 
+            if program.storage_buffers.contains(&name) {
+                let binding = BindingDescriptor {
+                    name,
+                    index,
+                    bind_type: BindType::StorageBuffer {
+                        has_dynamic_offset: false,
+                        readonly: true,
+                    },
+                    shader_stage: BindingShaderStage::VERTEX | BindingShaderStage::FRAGMENT,
+                };
+                let bind_group = bind_groups.iter_mut().find(|bg| bg.index == group_index);
+                if let Some(bind_group) = bind_group {
+                    bind_group.bindings.push(binding);
+                } else {
+                    bind_groups.push(BindGroupDescriptor::new(group_index, vec![binding]));
+                }
+                continue;
+            }
+
+            // integer textures can't be filtered, their samplers are bound with NEAREST filters
+            let sample_type = match info.type_ {
+                Gl::UNSIGNED_INT_SAMPLER_2D
+                | Gl::UNSIGNED_INT_SAMPLER_CUBE
+                | Gl::UNSIGNED_INT_SAMPLER_2D_ARRAY
+                | Gl::UNSIGNED_INT_SAMPLER_3D => TextureSampleType::Uint,
+                Gl::INT_SAMPLER_2D
+                | Gl::INT_SAMPLER_CUBE
+                | Gl::INT_SAMPLER_2D_ARRAY
+                | Gl::INT_SAMPLER_3D => TextureSampleType::Sint,
+                _ => TextureSampleType::Float { filterable: true },
+            };
+            // bevy provides sampler for each texture as `<texture>_sampler` binding,
+            // it is expected right after the texture
+            let sampler_binding = BindingDescriptor {
+                name: format!("{}_sampler", name),
+                index: index + 1,
+                bind_type: BindType::Sampler {
+                    comparison: false,
+                    filtering: matches!(sample_type, TextureSampleType::Float { .. }),
+                },
+                shader_stage: BindingShaderStage::FRAGMENT,
+            };
             let binding = BindingDescriptor {
-                name: info.name(),
+                name,
                 index: index,
                 bind_type: BindType::Texture {
                     multisampled: false,
-                    view_dimension: TextureViewDimension::D2,
-                    sample_type: TextureSampleType::Float { filterable: true },
+                    view_dimension,
+                    sample_type,
                 },
                 shader_stage: BindingShaderStage::FRAGMENT,
             };
             let bind_group = bind_groups.iter_mut().find(|bg| bg.index == group_index);
             if let Some(bind_group) = bind_group {
                 bind_group.bindings.push(binding);
+                bind_group.bindings.push(sampler_binding);
             } else {
-                bind_groups.push(BindGroupDescriptor::new(group_index, vec![binding]));
+                bind_groups.push(BindGroupDescriptor::new(
+                    group_index,
+                    vec![binding, sampler_binding],
+                ));
             }
         }
     }
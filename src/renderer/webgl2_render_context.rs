@@ -1,14 +1,19 @@
-use super::{Gl, WebGL2RenderResourceContext};
+use super::{
+    update_storage_texture, write_shadow, Gl, GlBackend, NativeFramebuffer, NativeTexture,
+    WebGL2RenderResourceContext,
+};
 use crate::converters::*;
-use crate::{gl_call, Buffer, WebGL2RenderPass};
+use crate::{gl_call, Buffer, FramebufferAttachments, WebGL2RenderPass};
+use bevy::log::prelude::*;
 use bevy::render::{
-    pass::{LoadOp, PassDescriptor, RenderPass, TextureAttachment},
+    color::Color,
+    pass::{
+        LoadOp, PassDescriptor, RenderPass, RenderPassDepthStencilAttachment, TextureAttachment,
+    },
     renderer::{BufferId, RenderContext, RenderResourceBindings, RenderResourceContext, TextureId},
-    texture::Extent3d,
+    texture::{Extent3d, TextureFormat},
 };
-use js_sys::Object;
 use std::sync::Arc;
-use wasm_bindgen::JsValue;
 
 pub struct WebGL2RenderContext {
     pub device: Arc<crate::Device>,
@@ -26,6 +31,266 @@ impl WebGL2RenderContext {
     /// Consume this context, finalize the current CommandEncoder (if it exists), and take the current WebGL2Resources.
     /// This is intended to be called from a worker thread right before synchronizing with the main thread.
     pub fn finish(&mut self) {}
+
+    /// Resolves (multisampled) color attachments of offscreen pass rendered to `framebuffer`
+    /// into their resolve targets
+    fn resolve_pass(&self, pass_descriptor: &PassDescriptor, framebuffer: NativeFramebuffer) {
+        let gl = &self.device.get_context();
+        let resources = &self.render_resource_context.resources;
+        let textures = resources.textures.read();
+        let texture_descriptors = resources.texture_descriptors.read();
+        let mut resolve_framebuffers = resources.resolve_framebuffers.write();
+        // blits are affected by scissor test
+        gl_call!(gl.disable(Gl::SCISSOR_TEST));
+        gl_call!(gl.bind_framebuffer(Gl::READ_FRAMEBUFFER, Some(framebuffer)));
+        for (i, descr) in pass_descriptor.color_attachments.iter().enumerate() {
+            let resolve_target = match descr.resolve_target {
+                Some(TextureAttachment::Id(id)) => id,
+                _ => continue,
+            };
+            let descriptor = texture_descriptors.get(&resolve_target).unwrap();
+            if let Some(resolve_framebuffer) = resolve_framebuffers.get(&resolve_target) {
+                gl_call!(gl.bind_framebuffer(Gl::DRAW_FRAMEBUFFER, Some(*resolve_framebuffer)));
+            } else {
+                let resolve_framebuffer = gl_call!(gl.create_framebuffer()).unwrap();
+                gl_call!(gl.bind_framebuffer(Gl::DRAW_FRAMEBUFFER, Some(resolve_framebuffer)));
+                gl_call!(gl.framebuffer_texture_2d(
+                    Gl::DRAW_FRAMEBUFFER,
+                    Gl::COLOR_ATTACHMENT0,
+                    Gl::TEXTURE_2D,
+                    textures.get(&resolve_target).cloned(),
+                    0,
+                ));
+                let complete = check_framebuffer_complete(&**gl, || {
+                    format!(
+                        "resolve target {:?} {}x{}",
+                        descriptor.format, descriptor.size.width, descriptor.size.height
+                    )
+                });
+                if !complete {
+                    // binding FRAMEBUFFER restores READ_FRAMEBUFFER too
+                    gl_call!(gl.bind_framebuffer(Gl::FRAMEBUFFER, Some(framebuffer)));
+                    gl_call!(gl.delete_framebuffer(resolve_framebuffer));
+                    continue;
+                }
+                resolve_framebuffers.insert(resolve_target, resolve_framebuffer);
+            }
+            gl_call!(gl.read_buffer(Gl::COLOR_ATTACHMENT0 + i as u32));
+            let (width, height) = (descriptor.size.width as i32, descriptor.size.height as i32);
+            gl_call!(gl.blit_framebuffer(
+                0,
+                0,
+                width,
+                height,
+                0,
+                0,
+                width,
+                height,
+                Gl::COLOR_BUFFER_BIT,
+                Gl::NEAREST,
+            ));
+        }
+        gl_call!(gl.read_buffer(Gl::COLOR_ATTACHMENT0));
+        gl_call!(gl.bind_framebuffer(Gl::FRAMEBUFFER, Some(framebuffer)));
+    }
+}
+
+/// Textures attached by the pass, used as key of cached framebuffers
+fn framebuffer_attachments(pass_descriptor: &PassDescriptor) -> FramebufferAttachments {
+    let texture_id = |attachment: &TextureAttachment| match attachment {
+        TextureAttachment::Id(id) => Some(*id),
+        _ => None,
+    };
+    let depth_stencil = pass_descriptor
+        .depth_stencil_attachment
+        .as_ref()
+        .and_then(|descr| texture_id(&descr.attachment));
+    pass_descriptor
+        .color_attachments
+        .iter()
+        .map(|descr| texture_id(&descr.attachment))
+        .chain(std::iter::once(depth_stencil))
+        .collect()
+}
+
+fn framebuffer_incomplete_reason(status: u32) -> &'static str {
+    match status {
+        Gl::FRAMEBUFFER_INCOMPLETE_ATTACHMENT => {
+            "attachment is not renderable (float formats require EXT_color_buffer_float)"
+        }
+        Gl::FRAMEBUFFER_INCOMPLETE_MISSING_ATTACHMENT => "no attachments",
+        Gl::FRAMEBUFFER_INCOMPLETE_DIMENSIONS => "attachments have different sizes",
+        Gl::FRAMEBUFFER_INCOMPLETE_MULTISAMPLE => "attachments have different sample counts",
+        Gl::FRAMEBUFFER_UNSUPPORTED => "combination of attachment formats is not supported",
+        _ => "unknown status",
+    }
+}
+
+/// Checks completeness of framebuffer bound to `FRAMEBUFFER`, logs the reason and description
+/// of its attachments if it can't be used (the caller skips the pass / copy)
+fn check_framebuffer_complete(gl: &dyn GlBackend, attachments: impl FnOnce() -> String) -> bool {
+    let status = gl_call!(gl.check_framebuffer_status(Gl::FRAMEBUFFER));
+    if status != Gl::FRAMEBUFFER_COMPLETE {
+        error!(
+            "skipping use of incomplete framebuffer: {} (0x{:x}), attachments: {}",
+            framebuffer_incomplete_reason(status),
+            status,
+            attachments()
+        );
+        return false;
+    }
+    true
+}
+
+/// Clears color attachment `draw_buffer` with `clearBuffer*` function matching its `format`.
+///
+/// Float and normalized formats use linear color components (sRGB ones are encoded on write),
+/// normalized ones clamped to range of the format. Integer formats are cleared with components
+/// of `color` as given, converted to integers (e.g. `Color::rgba(-1.0, 2.0, 0.0, 1.0)` is
+/// `[-1, 2, 0, 1]` in `Rgba32Sint` attachment), as they don't store colors.
+fn clear_color_attachment(
+    gl: &dyn GlBackend,
+    draw_buffer: i32,
+    format: TextureFormat,
+    color: Color,
+) {
+    let [r, g, b, a] = color.as_linear_rgba_f32();
+    let [raw_r, raw_g, raw_b, raw_a] = match color {
+        Color::Rgba {
+            red,
+            green,
+            blue,
+            alpha,
+        }
+        | Color::RgbaLinear {
+            red,
+            green,
+            blue,
+            alpha,
+        } => [red, green, blue, alpha],
+        _ => color.as_rgba_f32(),
+    };
+    let clamped = |min: f32, max: f32| {
+        [
+            r.clamp(min, max),
+            g.clamp(min, max),
+            b.clamp(min, max),
+            a.clamp(min, max),
+        ]
+    };
+    match format {
+        TextureFormat::R8Sint
+        | TextureFormat::R16Sint
+        | TextureFormat::R32Sint
+        | TextureFormat::Rg8Sint
+        | TextureFormat::Rg16Sint
+        | TextureFormat::Rg32Sint
+        | TextureFormat::Rgba8Sint
+        | TextureFormat::Rgba16Sint
+        | TextureFormat::Rgba32Sint => {
+            gl_call!(gl.clear_bufferiv(
+                Gl::COLOR,
+                draw_buffer,
+                &[raw_r as i32, raw_g as i32, raw_b as i32, raw_a as i32]
+            ))
+        }
+        TextureFormat::R8Uint
+        | TextureFormat::R16Uint
+        | TextureFormat::R32Uint
+        | TextureFormat::Rg8Uint
+        | TextureFormat::Rg16Uint
+        | TextureFormat::Rg32Uint
+        | TextureFormat::Rgba8Uint
+        | TextureFormat::Rgba16Uint
+        | TextureFormat::Rgba32Uint => {
+            gl_call!(gl.clear_bufferuiv(
+                Gl::COLOR,
+                draw_buffer,
+                &[raw_r as u32, raw_g as u32, raw_b as u32, raw_a as u32]
+            ))
+        }
+        TextureFormat::R8Unorm
+        | TextureFormat::Rg8Unorm
+        | TextureFormat::Rgba8Unorm
+        | TextureFormat::Rgba8UnormSrgb
+        | TextureFormat::Bgra8Unorm
+        | TextureFormat::Bgra8UnormSrgb
+        | TextureFormat::Rgb10a2Unorm => {
+            gl_call!(gl.clear_bufferfv(Gl::COLOR, draw_buffer, &clamped(0.0, 1.0)))
+        }
+        TextureFormat::R8Snorm | TextureFormat::Rg8Snorm | TextureFormat::Rgba8Snorm => {
+            gl_call!(gl.clear_bufferfv(Gl::COLOR, draw_buffer, &clamped(-1.0, 1.0)))
+        }
+        _ => gl_call!(gl.clear_bufferfv(Gl::COLOR, draw_buffer, &[r, g, b, a])),
+    }
+}
+
+/// Clears depth and stencil of the pass with `clearBuffer*`, both at once if both are cleared.
+/// Stencil is cleared only if the attachment `has_stencil`
+fn clear_depth_stencil_attachment(
+    gl: &dyn GlBackend,
+    descr: &RenderPassDepthStencilAttachment,
+    has_stencil: bool,
+) {
+    let depth = match descr.depth_ops.as_ref().map(|ops| ops.load) {
+        Some(LoadOp::Clear(value)) => Some(value),
+        _ => None,
+    };
+    let stencil = match descr.stencil_ops.as_ref().map(|ops| ops.load) {
+        Some(LoadOp::Clear(value)) if has_stencil => Some(value as i32),
+        _ => None,
+    };
+    match (depth, stencil) {
+        (Some(depth), Some(stencil)) => {
+            gl_call!(gl.clear_bufferfi(Gl::DEPTH_STENCIL, 0, depth, stencil))
+        }
+        (Some(depth), None) => gl_call!(gl.clear_bufferfv(Gl::DEPTH, 0, &[depth])),
+        (None, Some(stencil)) => gl_call!(gl.clear_bufferiv(Gl::STENCIL, 0, &[stencil])),
+        (None, None) => (),
+    }
+}
+
+/// Detaches color attachment of (reused) framebuffer bound to `FRAMEBUFFER` and unbinds it,
+/// so it doesn't keep the texture alive after removal
+fn detach_color_attachment(gl: &dyn GlBackend) {
+    gl_call!(gl.framebuffer_texture_2d(
+        Gl::FRAMEBUFFER,
+        Gl::COLOR_ATTACHMENT0,
+        Gl::TEXTURE_2D,
+        None,
+        0
+    ));
+    gl_call!(gl.bind_framebuffer(Gl::FRAMEBUFFER, None));
+}
+
+/// Attaches single 2D image (mip level of cube map face, array layer or 3D slice)
+/// of texture to currently bound framebuffer
+fn attach_texture_layer(
+    gl: &dyn GlBackend,
+    framebuffer_target: u32,
+    attachment: u32,
+    target: u32,
+    texture: NativeTexture,
+    level: u32,
+    layer: u32,
+) {
+    if is_3d_target(target) {
+        gl_call!(gl.framebuffer_texture_layer(
+            framebuffer_target,
+            attachment,
+            Some(texture),
+            level as i32,
+            layer as i32,
+        ));
+    } else {
+        gl_call!(gl.framebuffer_texture_2d(
+            framebuffer_target,
+            attachment,
+            image_target(target, layer),
+            Some(texture),
+            level as i32,
+        ));
+    }
 }
 
 impl RenderContext for WebGL2RenderContext {
@@ -44,9 +309,9 @@ impl RenderContext for WebGL2RenderContext {
         let dst = buffers.get(&destination_buffer).unwrap();
         match (&src.buffer, &dst.buffer) {
             (Buffer::WebGlBuffer(src_id), Buffer::WebGlBuffer(dst_id)) => {
-                gl_call!(gl.bind_buffer(Gl::COPY_READ_BUFFER, Some(&src_id)));
-                gl_call!(gl.bind_buffer(Gl::COPY_WRITE_BUFFER, Some(&dst_id)));
-                gl_call!(gl.copy_buffer_sub_data_with_i32_and_i32_and_i32(
+                gl_call!(gl.bind_buffer(Gl::COPY_READ_BUFFER, Some(*src_id)));
+                gl_call!(gl.bind_buffer(Gl::COPY_WRITE_BUFFER, Some(*dst_id)));
+                gl_call!(gl.copy_buffer_sub_data(
                     Gl::COPY_READ_BUFFER,
                     Gl::COPY_WRITE_BUFFER,
                     source_offset as i32,
@@ -55,31 +320,57 @@ impl RenderContext for WebGL2RenderContext {
                 ));
             }
             (Buffer::Data(data), Buffer::WebGlBuffer(dst_id)) => {
-                gl_call!(gl.bind_buffer(Gl::COPY_WRITE_BUFFER, Some(dst_id)));
-                (gl.buffer_sub_data_with_i32_and_u8_array_and_src_offset_and_length(
+                gl_call!(gl.bind_buffer(Gl::COPY_WRITE_BUFFER, Some(*dst_id)));
+                gl_call!(gl.buffer_sub_data_u8_slice(
                     Gl::COPY_WRITE_BUFFER,
                     destination_offset as i32,
-                    data,
-                    source_offset as u32,
-                    size as u32,
+                    &data[source_offset as usize..(source_offset + size) as usize],
                 ));
             }
             _ => panic!("copy_buffer_to_buffer: writing to in-memory buffer is not supported"),
         }
+        update_storage_texture(&**gl, dst);
+        if dst.shadow.is_some() {
+            let range = source_offset as usize..(source_offset + size) as usize;
+            let data = match (&src.buffer, &src.shadow) {
+                (Buffer::Data(data), _) => Some(data[range].to_vec()),
+                (_, Some(shadow)) => shadow.get(range).map(|data| data.to_vec()),
+                _ => None,
+            };
+            drop(buffers);
+            let mut buffers = resources.buffers.write();
+            let dst = buffers.get_mut(&destination_buffer).unwrap();
+            match data {
+                Some(data) => write_shadow(
+                    dst.shadow.as_mut().unwrap(),
+                    destination_offset as usize,
+                    &data,
+                ),
+                // source contents are unknown
+                None => dst.shadow = None,
+            }
+        }
     }
 
     fn copy_buffer_to_texture(
         &mut self,
         source_buffer: BufferId,
         source_offset: u64,
-        _source_bytes_per_row: u32,
+        source_bytes_per_row: u32,
         destination_texture: TextureId,
-        _destination_origin: [u32; 3],
-        _destination_mip_level: u32,
+        destination_origin: [u32; 3],
+        destination_mip_level: u32,
         size: Extent3d,
     ) {
-        let gl = &self.device.get_context();
         let resources = &self.render_resource_context.resources;
+        if resources
+            .skipped_uploads
+            .write()
+            .remove(&destination_texture)
+        {
+            return;
+        }
+        let gl = &self.device.get_context();
         let textures = resources.textures.read();
         let texture = textures.get(&destination_texture).unwrap();
         let buffers = resources.buffers.read();
@@ -93,59 +384,177 @@ impl RenderContext for WebGL2RenderContext {
         //     _ => Gl::RGBA,
         // };
 
-        gl_call!(gl.bind_texture(Gl::TEXTURE_2D, Some(&texture)));
-        let (internal_format, format, _type) = texture_descriptor.format.webgl2_into();
-
-        match &buffer.buffer {
-            Buffer::WebGlBuffer(buffer_id) => {
-                gl_call!(gl.bind_buffer(Gl::PIXEL_UNPACK_BUFFER, Some(buffer_id)));
-                gl_call!(
-                    gl.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_f64(
-                        Gl::TEXTURE_2D,
-                        0,                      //destination_mip_level as i32,
-                        internal_format as i32, // TODO
+        let target = resources.texture_target(destination_texture);
+        gl_call!(gl.bind_texture(target, Some(*texture)));
+
+        let compressed_format = resources
+            .compressed_textures
+            .read()
+            .get(&destination_texture)
+            .cloned();
+        if let Some(compressed_format) = compressed_format {
+            // images are specified with compressedTexImage2D, which can't update a region
+            if is_3d_target(target) || destination_origin[0] != 0 || destination_origin[1] != 0 {
+                warn!(
+                    "compressed textures support only whole 2D / cube map images, skipping upload to {:?} at {:?}",
+                    destination_texture, destination_origin
+                );
+                gl_call!(gl.bind_texture(target, None));
+                return;
+            }
+            let internal_format = compressed_format.webgl2_into();
+            let image_size = compressed_format.image_size(size.width, size.height);
+            for layer in 0..size.depth {
+                let image_target = image_target(target, destination_origin[2] + layer);
+                let offset = source_offset as u32 + layer * image_size;
+                match &buffer.buffer {
+                    Buffer::WebGlBuffer(buffer_id) => {
+                        gl_call!(gl.bind_buffer(Gl::PIXEL_UNPACK_BUFFER, Some(*buffer_id)));
+                        gl_call!(gl.compressed_tex_image_2d_with_pbo_offset(
+                            image_target,
+                            destination_mip_level as i32,
+                            internal_format,
+                            size.width as i32,
+                            size.height as i32,
+                            0,
+                            image_size as i32,
+                            offset as i32,
+                        ));
+                        gl_call!(gl.bind_buffer(Gl::PIXEL_UNPACK_BUFFER, None));
+                    }
+                    Buffer::Data(data) => {
+                        gl_call!(gl.compressed_tex_image_2d(
+                            image_target,
+                            destination_mip_level as i32,
+                            internal_format,
+                            size.width as i32,
+                            size.height as i32,
+                            0,
+                            &data[offset as usize..(offset + image_size) as usize],
+                        ));
+                    }
+                }
+            }
+            gl_call!(gl.bind_texture(target, None));
+            return;
+        }
+
+        let (_, format, _type) = texture_descriptor.format.webgl2_into();
+        let level = destination_mip_level as i32;
+        // storage for all levels is allocated in create_texture,
+        // so only given region is updated here
+        let pixel_size = texture_descriptor.format.pixel_size() as u32;
+        let bytes_per_row = if source_bytes_per_row > 0 {
+            source_bytes_per_row
+        } else {
+            size.width * pixel_size
+        };
+        let source_offset = source_offset as u32;
+        gl_call!(gl.pixel_storei(Gl::UNPACK_ALIGNMENT, 1));
+        gl_call!(gl.pixel_storei(Gl::UNPACK_ROW_LENGTH, (bytes_per_row / pixel_size) as i32));
+        gl_call!(gl.pixel_storei(
+            Gl::UNPACK_SKIP_PIXELS,
+            (source_offset % bytes_per_row / pixel_size) as i32,
+        ));
+
+        if is_3d_target(target) {
+            gl_call!(gl.pixel_storei(Gl::UNPACK_IMAGE_HEIGHT, size.height as i32));
+            gl_call!(gl.pixel_storei(Gl::UNPACK_SKIP_ROWS, (source_offset / bytes_per_row) as i32,));
+            match &buffer.buffer {
+                Buffer::WebGlBuffer(buffer_id) => {
+                    gl_call!(gl.bind_buffer(Gl::PIXEL_UNPACK_BUFFER, Some(*buffer_id)));
+                    gl_call!(gl.tex_sub_image_3d_with_pbo_offset(
+                        target,
+                        level,
+                        destination_origin[0] as i32,
+                        destination_origin[1] as i32,
+                        destination_origin[2] as i32,
                         size.width as i32,
                         size.height as i32,
+                        size.depth as i32,
+                        format,
+                        _type,
                         0,
+                    ));
+                    gl_call!(gl.bind_buffer(Gl::PIXEL_UNPACK_BUFFER, None));
+                }
+                Buffer::Data(data) => {
+                    gl_call!(gl.tex_sub_image_3d(
+                        target,
+                        level,
+                        destination_origin[0] as i32,
+                        destination_origin[1] as i32,
+                        destination_origin[2] as i32,
+                        size.width as i32,
+                        size.height as i32,
+                        size.depth as i32,
                         format,
                         _type,
-                        source_offset as f64,
-                    )
-                )
-                .expect("tex image");
-            }
-            Buffer::Data(data) => {
-                let buffer: Object = unsafe {
-                    let len = data.len() / 4;
-                    let buffer = &std::mem::transmute::<&[u8], &[u32]>(&data)[..len];
-                    js_sys::Uint32Array::view(buffer).into()
+                        data,
+                    ));
+                }
+            };
+            gl_call!(gl.pixel_storei(Gl::UNPACK_IMAGE_HEIGHT, 0));
+        } else {
+            // cube map faces are stored one after another in source buffer
+            for layer in 0..size.depth {
+                let image_target = image_target(target, destination_origin[2] + layer);
+                let skip_rows = source_offset / bytes_per_row + layer * size.height;
+                gl_call!(gl.pixel_storei(Gl::UNPACK_SKIP_ROWS, skip_rows as i32));
+
+                match &buffer.buffer {
+                    Buffer::WebGlBuffer(buffer_id) => {
+                        gl_call!(gl.bind_buffer(Gl::PIXEL_UNPACK_BUFFER, Some(*buffer_id)));
+                        gl_call!(gl.tex_sub_image_2d_with_pbo_offset(
+                            image_target,
+                            level,
+                            destination_origin[0] as i32,
+                            destination_origin[1] as i32,
+                            size.width as i32,
+                            size.height as i32,
+                            format,
+                            _type,
+                            0,
+                        ));
+                        gl_call!(gl.bind_buffer(Gl::PIXEL_UNPACK_BUFFER, None));
+                    }
+                    Buffer::Data(data) => {
+                        gl_call!(gl.tex_sub_image_2d(
+                            image_target,
+                            level,
+                            destination_origin[0] as i32,
+                            destination_origin[1] as i32,
+                            size.width as i32,
+                            size.height as i32,
+                            format,
+                            _type,
+                            data,
+                        ));
+                    }
                 };
-                gl.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_array_buffer_view_and_src_offset(
-                    Gl::TEXTURE_2D,
-                    0,                      //destination_mip_level as i32,
-                    internal_format as i32, // TODO
-                    size.width as i32,
-                    size.height as i32,
-                    0,
-                    format,
-                    _type,
-                    &buffer,
-                    0,
-                ).expect("tex image");
             }
-        };
-        // gl_call!(gl.generate_mipmap(Gl::TEXTURE_2D));
-        gl_call!(gl.tex_parameteri(Gl::TEXTURE_2D, Gl::TEXTURE_MIN_FILTER, Gl::NEAREST as i32));
-        gl_call!(gl.tex_parameteri(Gl::TEXTURE_2D, Gl::TEXTURE_MAG_FILTER, Gl::NEAREST as i32));
-        gl_call!(gl.tex_parameteri(Gl::TEXTURE_2D, Gl::TEXTURE_WRAP_S, Gl::CLAMP_TO_EDGE as i32));
-        gl_call!(gl.tex_parameteri(Gl::TEXTURE_2D, Gl::TEXTURE_WRAP_T, Gl::CLAMP_TO_EDGE as i32));
+        }
+
+        gl_call!(gl.pixel_storei(Gl::UNPACK_ALIGNMENT, 4));
+        gl_call!(gl.pixel_storei(Gl::UNPACK_ROW_LENGTH, 0));
+        gl_call!(gl.pixel_storei(Gl::UNPACK_SKIP_ROWS, 0));
+        gl_call!(gl.pixel_storei(Gl::UNPACK_SKIP_PIXELS, 0));
+
+        if texture_descriptor.mip_level_count > 1
+            && level == 0
+            && supports_mipmap_generation(texture_descriptor.format)
+        {
+            // levels uploaded explicitly later will overwrite generated ones
+            gl_call!(gl.generate_mipmap(target));
+        }
+        gl_call!(gl.bind_texture(target, None));
     }
 
     fn copy_texture_to_buffer(
         &mut self,
         source_texture: TextureId,
         source_origin: [u32; 3],
-        _source_mip_level: u32,
+        source_mip_level: u32,
         destination_buffer: BufferId,
         _destination_offset: u64,
         _destination_bytes_per_row: u32,
@@ -167,15 +576,41 @@ impl RenderContext for WebGL2RenderContext {
             _ => panic!("not supported read_pixels fmt"),
         };
 
+        let target = resources.texture_target(source_texture);
+        // cube map face, array layer or mip level other than the one attached by passes
+        let single_image = target != Gl::TEXTURE_2D || source_mip_level != 0;
         let mut framebuffers = resources.framebuffers.write();
-        if let Some(fb) = framebuffers.get(&source_texture) {
-            gl_call!(gl.bind_framebuffer(Gl::FRAMEBUFFER, Some(&fb)));
+        // same attachment set as color-only pass rendering to the texture
+        let attachments = vec![Some(source_texture), None];
+        if single_image {
+            let texture = *resources.textures.read().get(&source_texture).unwrap();
+            let (read_fb, _) = *resources.copy_framebuffers.write().get_or_insert_with(|| {
+                (
+                    gl_call!(gl.create_framebuffer()).unwrap(),
+                    gl_call!(gl.create_framebuffer()).unwrap(),
+                )
+            });
+            gl_call!(gl.bind_framebuffer(Gl::FRAMEBUFFER, Some(read_fb)));
+            attach_texture_layer(
+                &**gl,
+                Gl::FRAMEBUFFER,
+                Gl::COLOR_ATTACHMENT0,
+                target,
+                texture,
+                source_mip_level,
+                source_origin[2],
+            );
+            if !check_framebuffer_complete(&**gl, || format!("{:?}", source_texture)) {
+                detach_color_attachment(&**gl);
+                return;
+            }
+        } else if let Some(fb) = framebuffers.get(&attachments) {
+            gl_call!(gl.bind_framebuffer(Gl::FRAMEBUFFER, Some(*fb)));
         } else {
             let textures = resources.textures.read();
-            let gl_texture = textures.get(&source_texture);
+            let gl_texture = textures.get(&source_texture).cloned();
             let fb = gl_call!(gl.create_framebuffer()).unwrap();
-            gl_call!(gl.bind_framebuffer(Gl::FRAMEBUFFER, Some(&fb)));
-            framebuffers.insert(source_texture, fb);
+            gl_call!(gl.bind_framebuffer(Gl::FRAMEBUFFER, Some(fb)));
             gl_call!(gl.framebuffer_texture_2d(
                 Gl::FRAMEBUFFER,
                 Gl::COLOR_ATTACHMENT0 as u32,
@@ -183,13 +618,18 @@ impl RenderContext for WebGL2RenderContext {
                 gl_texture,
                 0,
             ));
-            assert!(gl.check_framebuffer_status(Gl::FRAMEBUFFER) == Gl::FRAMEBUFFER_COMPLETE);
+            if !check_framebuffer_complete(&**gl, || format!("{:?}", source_texture)) {
+                gl_call!(gl.bind_framebuffer(Gl::FRAMEBUFFER, None));
+                gl_call!(gl.delete_framebuffer(fb));
+                return;
+            }
+            framebuffers.insert(attachments, fb);
         }
         if let Buffer::WebGlBuffer(dst_id) = &dst.buffer {
-            gl_call!(gl.bind_buffer(Gl::PIXEL_PACK_BUFFER, Some(&dst_id)));
+            gl_call!(gl.bind_buffer(Gl::PIXEL_PACK_BUFFER, Some(*dst_id)));
             gl_call!(gl.read_buffer(Gl::COLOR_ATTACHMENT0));
             // bevy::utils::tracing::info!("read_pixels, width: {}, height: {}, fmt: {}, type: {}", size.width, size.height, format, _type);
-            gl_call!(gl.read_pixels_with_i32(
+            gl_call!(gl.read_pixels_with_pbo_offset(
                 source_origin[0] as i32,
                 source_origin[1] as i32,
                 size.width as i32,
@@ -197,8 +637,7 @@ impl RenderContext for WebGL2RenderContext {
                 read_fmt,
                 _type,
                 0,
-            ))
-            .unwrap();
+            ));
             // let sync = gl.fence_sync(Gl::SYNC_GPU_COMMANDS_COMPLETE, 0).unwrap();
             // gl.client_wait_sync_with_u32(&sync, Gl::SYNC_FLUSH_COMMANDS_BIT, 0);
             // let mut fence_sync = resources.fence_sync.write();
@@ -208,19 +647,158 @@ impl RenderContext for WebGL2RenderContext {
             // fence_sync.replace(sync);
             // bevy::utils::tracing::info!("created fence sync");
         }
+        if single_image {
+            detach_color_attachment(&**gl);
+        }
     }
 
     fn copy_texture_to_texture(
         &mut self,
-        _source_texture: TextureId,
-        _source_origin: [u32; 3],
-        _source_mip_level: u32,
-        _destination_texture: TextureId,
-        _destination_origin: [u32; 3],
-        _destination_mip_level: u32,
-        _size: Extent3d,
+        source_texture: TextureId,
+        source_origin: [u32; 3],
+        source_mip_level: u32,
+        destination_texture: TextureId,
+        destination_origin: [u32; 3],
+        destination_mip_level: u32,
+        size: Extent3d,
     ) {
-        unimplemented!()
+        let gl = &self.device.get_context();
+        let resources = &self.render_resource_context.resources;
+        let textures = resources.textures.read();
+        let texture_descriptors = resources.texture_descriptors.read();
+        let src = *textures.get(&source_texture).unwrap();
+        let dst = *textures.get(&destination_texture).unwrap();
+        let src_descriptor = texture_descriptors.get(&source_texture).unwrap();
+        let dst_descriptor = texture_descriptors.get(&destination_texture).unwrap();
+        let (src_format, dst_format) = (src_descriptor.format, dst_descriptor.format);
+        let src_target = resources.texture_target(source_texture);
+        let dst_target = resources.texture_target(destination_texture);
+        let (src_attachment, mask) = attachment_for_format(src_format);
+        let (dst_attachment, _) = attachment_for_format(dst_format);
+        let blit = can_blit(src_format, dst_format);
+        if !blit && mask != Gl::COLOR_BUFFER_BIT {
+            warn!(
+                "copy_texture_to_texture: unsupported copy from {:?} to {:?}",
+                src_format, dst_format
+            );
+            return;
+        }
+
+        let (src_x, src_y) = (source_origin[0] as i32, source_origin[1] as i32);
+        let (dst_x, dst_y) = (destination_origin[0] as i32, destination_origin[1] as i32);
+        let (width, height) = (size.width as i32, size.height as i32);
+
+        let (read_fb, draw_fb) = *resources.copy_framebuffers.write().get_or_insert_with(|| {
+            (
+                gl_call!(gl.create_framebuffer()).unwrap(),
+                gl_call!(gl.create_framebuffer()).unwrap(),
+            )
+        });
+        gl_call!(gl.bind_framebuffer(Gl::READ_FRAMEBUFFER, Some(read_fb)));
+        gl_call!(gl.bind_framebuffer(Gl::DRAW_FRAMEBUFFER, Some(draw_fb)));
+        if !blit {
+            gl_call!(gl.bind_texture(dst_target, Some(dst)));
+        }
+
+        for layer in 0..size.depth {
+            let src_layer = source_origin[2] + layer;
+            let dst_layer = destination_origin[2] + layer;
+            attach_texture_layer(
+                &**gl,
+                Gl::READ_FRAMEBUFFER,
+                src_attachment,
+                src_target,
+                src,
+                source_mip_level,
+                src_layer,
+            );
+            if blit {
+                attach_texture_layer(
+                    &**gl,
+                    Gl::DRAW_FRAMEBUFFER,
+                    dst_attachment,
+                    dst_target,
+                    dst,
+                    destination_mip_level,
+                    dst_layer,
+                );
+            }
+            let mut status = gl_call!(gl.check_framebuffer_status(Gl::READ_FRAMEBUFFER));
+            if blit && status == Gl::FRAMEBUFFER_COMPLETE {
+                status = gl_call!(gl.check_framebuffer_status(Gl::DRAW_FRAMEBUFFER));
+            }
+            if status != Gl::FRAMEBUFFER_COMPLETE {
+                warn!(
+                    "copy_texture_to_texture: skipping copy from {:?} to {:?}, framebuffer is incomplete: {} (0x{:x})",
+                    src_format,
+                    dst_format,
+                    framebuffer_incomplete_reason(status),
+                    status
+                );
+                break;
+            }
+            if blit {
+                gl_call!(gl.blit_framebuffer(
+                    src_x,
+                    src_y,
+                    src_x + width,
+                    src_y + height,
+                    dst_x,
+                    dst_y,
+                    dst_x + width,
+                    dst_y + height,
+                    mask,
+                    Gl::NEAREST,
+                ));
+            } else if is_3d_target(dst_target) {
+                // destination is not renderable or of different kind than source,
+                // copyTexSubImage converts between compatible color formats
+                gl_call!(gl.copy_tex_sub_image_3d(
+                    dst_target,
+                    destination_mip_level as i32,
+                    dst_x,
+                    dst_y,
+                    dst_layer as i32,
+                    src_x,
+                    src_y,
+                    width,
+                    height,
+                ));
+            } else {
+                gl_call!(gl.copy_tex_sub_image_2d(
+                    image_target(dst_target, dst_layer),
+                    destination_mip_level as i32,
+                    dst_x,
+                    dst_y,
+                    src_x,
+                    src_y,
+                    width,
+                    height,
+                ));
+            }
+        }
+
+        // detach textures, so the cached framebuffers don't keep them alive after removal
+        gl_call!(gl.framebuffer_texture_2d(
+            Gl::READ_FRAMEBUFFER,
+            src_attachment,
+            Gl::TEXTURE_2D,
+            None,
+            0
+        ));
+        if blit {
+            gl_call!(gl.framebuffer_texture_2d(
+                Gl::DRAW_FRAMEBUFFER,
+                dst_attachment,
+                Gl::TEXTURE_2D,
+                None,
+                0
+            ));
+        } else {
+            gl_call!(gl.bind_texture(dst_target, None));
+        }
+        gl_call!(gl.bind_framebuffer(Gl::READ_FRAMEBUFFER, None));
+        gl_call!(gl.bind_framebuffer(Gl::DRAW_FRAMEBUFFER, None));
     }
 
     fn resources(&self) -> &dyn RenderResourceContext {
@@ -239,6 +817,10 @@ impl RenderContext for WebGL2RenderContext {
     ) {
         let gl = &self.device.get_context();
         gl_call!(gl.disable(Gl::SCISSOR_TEST));
+        // clears are affected by write masks left by the last pipeline
+        gl_call!(gl.color_mask(true, true, true, true));
+        gl_call!(gl.depth_mask(true));
+        gl_call!(gl.stencil_mask_separate(Gl::FRONT_AND_BACK, !0));
         let texture_id = if let TextureAttachment::Id(texture_id) =
             &pass_descriptor.color_attachments[0].attachment
         {
@@ -246,16 +828,18 @@ impl RenderContext for WebGL2RenderContext {
         } else {
             panic!("first attachment must be a texture");
         };
-        let is_swapchain = texture_id == &self.render_resource_context.swapchain_texture
-            || pass_descriptor.color_attachments[0]
-                .resolve_target
-                .is_some();
+        let swapchain_texture = self.render_resource_context.swapchain_texture;
+        // multisampled main pass is rendered directly to the canvas,
+        // which is antialiased by the browser
+        let is_swapchain = *texture_id == swapchain_texture
+            || matches!(
+                pass_descriptor.color_attachments[0].resolve_target,
+                Some(TextureAttachment::Id(id)) if id == swapchain_texture
+            );
         // info!("pass_descriptor: {:#?}", pass_descriptor);
+        let mut framebuffer = None;
         if is_swapchain {
-            gl_call!(gl.bind_framebuffer(
-                Gl::FRAMEBUFFER,
-                None as Option<&crate::renderer::WebGlFramebuffer>
-            ));
+            gl_call!(gl.bind_framebuffer(Gl::FRAMEBUFFER, None));
             let window_size = self.render_resource_context.resources.window_size.read();
             gl_call!(gl.viewport(0, 0, window_size.0 as i32, window_size.1 as i32));
             let mut mask = 0;
@@ -263,91 +847,136 @@ impl RenderContext for WebGL2RenderContext {
                 gl_call!(gl.clear_color(c.r(), c.g(), c.b(), c.a()));
                 mask |= Gl::COLOR_BUFFER_BIT;
             }
-            if let Some(d) = &pass_descriptor.depth_stencil_attachment {
-                if let Some(x) = &d.depth_ops {
-                    if let LoadOp::Clear(value) = x.load {
-                        // it seems it has no effect
-                        gl_call!(gl.clear_depth(value));
-                        mask |= Gl::DEPTH_BUFFER_BIT;
-                    }
-                }
-            }
             if mask > 0 {
                 gl_call!(gl.clear(mask));
             }
+            if let Some(descr) = &pass_descriptor.depth_stencil_attachment {
+                let settings = self
+                    .render_resource_context
+                    .resources
+                    .context_settings
+                    .read();
+                clear_depth_stencil_attachment(&**gl, descr, settings.stencil);
+            }
         } else {
             let textures = self.render_resource_context.resources.textures.read();
+            let renderbuffers = self.render_resource_context.resources.renderbuffers.read();
             let texture_info = self
                 .render_resource_context
                 .resources
                 .texture_descriptors
                 .read();
             let mut framebuffers = self.render_resource_context.resources.framebuffers.write();
-            if let Some(fb) = framebuffers.get(texture_id) {
-                gl_call!(gl.bind_framebuffer(Gl::FRAMEBUFFER, Some(&fb)));
+            let attachments = framebuffer_attachments(pass_descriptor);
+            if let Some(fb) = framebuffers.get(&attachments) {
+                gl_call!(gl.bind_framebuffer(Gl::FRAMEBUFFER, Some(*fb)));
+                framebuffer = Some(*fb);
             } else {
-                let fb = gl_call!(gl.create_framebuffer()).unwrap();
-                gl_call!(gl.bind_framebuffer(Gl::FRAMEBUFFER, Some(&fb)));
-                framebuffers.insert(*texture_id, fb);
-                let draw_buffers = JsValue::from(
-                    pass_descriptor
-                        .color_attachments
-                        .iter()
-                        .enumerate()
-                        .map(|(n, descr)| match descr.attachment {
-                            TextureAttachment::Id(_) => Gl::COLOR_ATTACHMENT0 + n as u32,
-                            _ => Gl::NONE,
-                        })
-                        .map(|x| JsValue::from_f64(x as f64))
-                        .collect::<js_sys::Array>(),
-                );
-                gl_call!(gl.draw_buffers(&draw_buffers));
+                let mut attached = Vec::new();
                 for (i, descr) in pass_descriptor.color_attachments.iter().enumerate() {
                     if let TextureAttachment::Id(id) = descr.attachment {
-                        let gl_texture = textures.get(&id);
+                        attached.push((Gl::COLOR_ATTACHMENT0 + i as u32, id));
+                    }
+                }
+                if let Some(TextureAttachment::Id(id)) = pass_descriptor
+                    .depth_stencil_attachment
+                    .as_ref()
+                    .map(|descr| &descr.attachment)
+                {
+                    let format = texture_info.get(id).unwrap().format;
+                    let (attachment, _) = attachment_for_format(format);
+                    if attachment == Gl::COLOR_ATTACHMENT0 {
+                        error!(
+                            "depth_stencil_attachment has non-depth format {:?}, skipping pass",
+                            format
+                        );
+                        return;
+                    }
+                    attached.push((attachment, *id));
+                }
+                let fb = gl_call!(gl.create_framebuffer()).unwrap();
+                gl_call!(gl.bind_framebuffer(Gl::FRAMEBUFFER, Some(fb)));
+                let draw_buffers = pass_descriptor
+                    .color_attachments
+                    .iter()
+                    .enumerate()
+                    .map(|(n, descr)| match descr.attachment {
+                        TextureAttachment::Id(_) => Gl::COLOR_ATTACHMENT0 + n as u32,
+                        _ => Gl::NONE,
+                    })
+                    .collect::<Vec<_>>();
+                gl_call!(gl.draw_buffers(&draw_buffers));
+                for (attachment, id) in attached.iter() {
+                    if let Some(renderbuffer) = renderbuffers.get(id) {
+                        gl_call!(gl.framebuffer_renderbuffer(
+                            Gl::FRAMEBUFFER,
+                            *attachment,
+                            Gl::RENDERBUFFER,
+                            Some(*renderbuffer),
+                        ));
+                    } else {
                         gl_call!(gl.framebuffer_texture_2d(
                             Gl::FRAMEBUFFER,
-                            Gl::COLOR_ATTACHMENT0 + i as u32,
+                            *attachment,
                             Gl::TEXTURE_2D,
-                            gl_texture,
+                            textures.get(id).cloned(),
                             0,
                         ));
-                        assert!(
-                            gl.check_framebuffer_status(Gl::FRAMEBUFFER)
-                                == Gl::FRAMEBUFFER_COMPLETE
-                        );
                     }
                 }
+                let complete = check_framebuffer_complete(&**gl, || {
+                    attached
+                        .iter()
+                        .map(|(attachment, id)| {
+                            let descr = texture_info.get(id).unwrap();
+                            format!(
+                                "0x{:x}: {:?} {}x{} ({} samples)",
+                                attachment,
+                                descr.format,
+                                descr.size.width,
+                                descr.size.height,
+                                descr.sample_count
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                });
+                if !complete {
+                    gl_call!(gl.bind_framebuffer(Gl::FRAMEBUFFER, None));
+                    gl_call!(gl.delete_framebuffer(fb));
+                    return;
+                }
+                framebuffers.insert(attachments, fb);
+                framebuffer = Some(fb);
             }
             for (i, descr) in pass_descriptor.color_attachments.iter().enumerate() {
+                let id = match descr.attachment {
+                    TextureAttachment::Id(id) => id,
+                    _ => continue,
+                };
+                let texture_descr = texture_info.get(&id).unwrap();
                 if i == 0 {
-                    if let TextureAttachment::Id(id) = descr.attachment {
-                        let texture_descr = texture_info.get(&id).unwrap();
-                        gl_call!(gl.viewport(
-                            0,
-                            0,
-                            texture_descr.size.width as i32,
-                            texture_descr.size.height as i32,
-                        ));
-                    }
+                    gl_call!(gl.viewport(
+                        0,
+                        0,
+                        texture_descr.size.width as i32,
+                        texture_descr.size.height as i32,
+                    ));
                 }
-                if let LoadOp::Clear(c) = descr.ops.load {
-                    // TODO - use proper function / color scalling depending
-                    // on texture format
-                    if i == 0 {
-                        gl_call!(gl.clear_bufferfv_with_f32_array(
-                            Gl::COLOR,
-                            i as i32,
-                            &[c.r(), c.g(), c.b(), c.a()]
-                        ));
-                    } else {
-                        gl_call!(gl.clear_bufferuiv_with_u32_array_and_src_offset(
-                            Gl::COLOR,
-                            i as i32,
-                            &[c.r() as u32, c.g() as u32, c.b() as u32, c.a() as u32],
-                            0
-                        ));
-                    }
+                if let LoadOp::Clear(color) = descr.ops.load {
+                    clear_color_attachment(&**gl, i as i32, texture_descr.format, color);
+                }
+            }
+            if let Some(descr) = &pass_descriptor.depth_stencil_attachment {
+                if let TextureAttachment::Id(id) = descr.attachment {
+                    let (attachment, _) =
+                        attachment_for_format(texture_info.get(&id).unwrap().format);
+                    // stencil is available only in DEPTH_STENCIL_ATTACHMENT
+                    clear_depth_stencil_attachment(
+                        &**gl,
+                        descr,
+                        attachment == Gl::DEPTH_STENCIL_ATTACHMENT,
+                    );
                 }
             }
         }
@@ -355,7 +984,18 @@ impl RenderContext for WebGL2RenderContext {
         let mut render_pass = WebGL2RenderPass {
             render_context: self,
             pipeline: None,
+            sprite_batch: Default::default(),
+            stencil_reference: 0,
         };
         run_pass(&mut render_pass);
+        render_pass.flush_sprite_batch();
+
+        let has_resolve_targets = pass_descriptor
+            .color_attachments
+            .iter()
+            .any(|descr| descr.resolve_target.is_some());
+        if let Some(framebuffer) = framebuffer.filter(|_| has_resolve_targets) {
+            self.resolve_pass(pass_descriptor, framebuffer);
+        }
     }
 }
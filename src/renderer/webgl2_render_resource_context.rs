@@ -1,21 +1,27 @@
-use super::{compile_shader, link_program, reflect_layout, Gl};
+use super::{
+    compile_shader, link_program, reflect_layout, Gl, GlBackend, NativeProgram, NativeRenderbuffer,
+    NativeSampler,
+};
 use crate::{
-    converters::*, gl_call, Buffer, Device, GlBufferInfo, GlShader, GlVertexBufferDescripror,
-    WebGL2Pipeline, WebGL2RenderResourceBinding, WebGL2Resources,
+    converters::*, gl_call, Buffer, Device, GlBufferInfo, GlShader, GlSpriteBatchPipeline,
+    GlStorageTexture, GlVertexBufferDescripror, SpriteBatchKind, WebGL2Capabilities,
+    WebGL2ContextSettings, WebGL2Limits, WebGL2Pipeline, WebGL2RenderResourceBinding,
+    WebGL2Resources, SPRITE_BIND_GROUP_INDEX,
 };
 use bevy::asset::{Assets, Handle, HandleUntyped};
 use bevy::log::prelude::*;
 use bevy::render::{
+    color::Color,
     pipeline::{
-        BindGroupDescriptor, BindGroupDescriptorId, BindType, IndexFormat, PipelineDescriptor,
-        PipelineLayout,
+        BindGroupDescriptor, BindGroupDescriptorId, BindType, IndexFormat, PipelineCompiler,
+        PipelineDescriptor, PipelineLayout,
     },
     renderer::{
         BindGroup, BufferId, BufferInfo, BufferMapMode, BufferUsage, RenderResourceBinding,
         RenderResourceContext, RenderResourceId, SamplerId, TextureId,
     },
     shader::{Shader, ShaderError, ShaderSource, ShaderStage, ShaderStages},
-    texture::{SamplerDescriptor, TextureDescriptor},
+    texture::{FilterMode, SamplerDescriptor, TextureDescriptor, TextureViewDimension},
 };
 use bevy::utils::HashMap;
 use bevy::window::Window;
@@ -33,8 +39,46 @@ pub struct WebGL2RenderResourceContext {
 unsafe impl Send for WebGL2RenderResourceContext {}
 unsafe impl Sync for WebGL2RenderResourceContext {}
 
-pub const BIND_BUFFER_ALIGNMENT: usize = 256;
-pub const STORAGE_BUFFER_SIZE: usize = 65536;
+/// Copies contents of storage buffer to its data texture
+pub(crate) fn update_storage_texture(gl: &dyn GlBackend, buffer: &GlBufferInfo) {
+    if let (Buffer::WebGlBuffer(buffer_id), Some(storage_texture)) =
+        (&buffer.buffer, &buffer.storage_texture)
+    {
+        gl_call!(gl.bind_buffer(Gl::PIXEL_UNPACK_BUFFER, Some(*buffer_id)));
+        gl_call!(gl.bind_texture(Gl::TEXTURE_2D, Some(storage_texture.texture)));
+        gl_call!(gl.tex_sub_image_2d_with_pbo_offset(
+            Gl::TEXTURE_2D,
+            0,
+            0,
+            0,
+            storage_texture.width,
+            storage_texture.height,
+            Gl::RGBA_INTEGER,
+            Gl::UNSIGNED_INT,
+            0,
+        ));
+        gl_call!(gl.bind_texture(Gl::TEXTURE_2D, None));
+        gl_call!(gl.bind_buffer(Gl::PIXEL_UNPACK_BUFFER, None));
+    }
+}
+
+/// Writes `data` to uniform buffer shadow, ignoring bytes past its end
+pub(crate) fn write_shadow(shadow: &mut [u8], offset: usize, data: &[u8]) {
+    if offset < shadow.len() {
+        let len = data.len().min(shadow.len() - offset);
+        shadow[offset..offset + len].copy_from_slice(&data[..len]);
+    }
+}
+
+fn set_default_texture_parameters(gl: &dyn GlBackend, target: u32, mip_level_count: u32) {
+    // sampling state used when no sampler object is bound to texture unit
+    gl_call!(gl.tex_parameteri(target, Gl::TEXTURE_MIN_FILTER, Gl::NEAREST as i32));
+    gl_call!(gl.tex_parameteri(target, Gl::TEXTURE_MAG_FILTER, Gl::NEAREST as i32));
+    gl_call!(gl.tex_parameteri(target, Gl::TEXTURE_WRAP_S, Gl::CLAMP_TO_EDGE as i32));
+    gl_call!(gl.tex_parameteri(target, Gl::TEXTURE_WRAP_T, Gl::CLAMP_TO_EDGE as i32));
+    // keeps texture complete for samplers with mipmap filtering
+    gl_call!(gl.tex_parameteri(target, Gl::TEXTURE_MAX_LEVEL, mip_level_count as i32 - 1,));
+}
 
 impl WebGL2RenderResourceContext {
     pub fn new(device: Arc<crate::Device>) -> Self {
@@ -54,6 +98,296 @@ impl WebGL2RenderResourceContext {
             .insert(texture, descriptor);
     }
 
+    pub fn limits(&self) -> WebGL2Limits {
+        self.resources.capabilities.read().limits
+    }
+
+    pub fn capabilities(&self) -> WebGL2Capabilities {
+        self.resources.capabilities.read().clone()
+    }
+
+    /// Sets blend constant of pipelines using `BlendFactor::Constant` / `OneMinusConstant`
+    /// (transparent black by default), applied when next pipeline is set
+    pub fn set_blend_constant(&self, color: Color) {
+        *self.resources.blend_constant.write() = color.as_linear_rgba_f32();
+    }
+
+    /// Creates renderbuffer for multisampled texture, `sample_count` is clamped to `MAX_SAMPLES`
+    fn create_multisampled_renderbuffer(
+        &self,
+        gl: &dyn GlBackend,
+        descriptor: &TextureDescriptor,
+    ) -> NativeRenderbuffer {
+        let max_samples = self.limits().max_samples;
+        if descriptor.sample_count > max_samples {
+            warn!(
+                "sample_count {} exceeds MAX_SAMPLES, using {}",
+                descriptor.sample_count, max_samples
+            );
+        }
+        let (internal_format, _, _) = descriptor.format.webgl2_into();
+        let renderbuffer = gl_call!(gl.create_renderbuffer()).unwrap();
+        gl_call!(gl.bind_renderbuffer(Gl::RENDERBUFFER, Some(renderbuffer)));
+        gl_call!(gl.renderbuffer_storage_multisample(
+            Gl::RENDERBUFFER,
+            descriptor.sample_count.min(max_samples) as i32,
+            internal_format,
+            descriptor.size.width as i32,
+            descriptor.size.height as i32,
+        ));
+        gl_call!(gl.bind_renderbuffer(Gl::RENDERBUFFER, None));
+        renderbuffer
+    }
+
+    /// Creates data texture for storage buffer of `size` bytes, one row of texels
+    /// if it fits in `MAX_TEXTURE_SIZE`, full rows of `MAX_TEXTURE_SIZE` texels otherwise.
+    /// Returns `None` (and logs an error) if the buffer doesn't fit in the largest texture.
+    fn create_storage_texture(&self, gl: &dyn GlBackend, size: usize) -> Option<GlStorageTexture> {
+        let max_texture_size = self.limits().max_texture_size;
+        let texels =
+            ((size + GlStorageTexture::TEXEL_SIZE - 1) / GlStorageTexture::TEXEL_SIZE).max(1);
+        let width = texels.min(max_texture_size);
+        let height = (texels + width - 1) / width;
+        if height > max_texture_size {
+            error!(
+                "storage buffer of {} bytes doesn't fit in {}x{} data texture (MAX_TEXTURE_SIZE), \
+                 it won't be readable in shaders",
+                size, max_texture_size, max_texture_size
+            );
+            return None;
+        }
+        let texture = gl_call!(gl.create_texture()).unwrap();
+        gl_call!(gl.bind_texture(Gl::TEXTURE_2D, Some(texture)));
+        gl_call!(gl.tex_storage_2d(Gl::TEXTURE_2D, 1, Gl::RGBA32UI, width as i32, height as i32));
+        set_default_texture_parameters(gl, Gl::TEXTURE_2D, 1);
+        gl_call!(gl.bind_texture(Gl::TEXTURE_2D, None));
+        Some(GlStorageTexture {
+            texture,
+            width: width as i32,
+            height: height as i32,
+        })
+    }
+
+    pub fn supports_compressed_format(&self, format: CompressedTextureFormat) -> bool {
+        self.resources
+            .compressed_texture_formats
+            .read()
+            .contains(&format)
+    }
+
+    /// Creates texture in block-compressed `format`, `texture_descriptor.format` is ignored.
+    /// Each mip level (cube face) has to be specified with `copy_buffer_to_texture`
+    /// from tightly packed blocks.
+    ///
+    /// Fails if `format` isn't supported by the context (see `supports_compressed_format`).
+    pub fn create_compressed_texture(
+        &self,
+        texture_descriptor: TextureDescriptor,
+        view_dimension: TextureViewDimension,
+        format: CompressedTextureFormat,
+    ) -> Result<TextureId, String> {
+        if !self.supports_compressed_format(format) {
+            return Err(format!("{:?} is not supported by WebGL2 context", format));
+        }
+        let texture_id = TextureId::new();
+        self.add_texture_descriptor(texture_id, texture_descriptor);
+        self.resources
+            .compressed_textures
+            .write()
+            .insert(texture_id, format);
+        let gl = &self.device.get_context();
+        let texture = gl_call!(gl.create_texture()).unwrap();
+        let target = self.register_texture_target(texture_id, &texture_descriptor, view_dimension);
+        gl_call!(gl.bind_texture(target, Some(texture)));
+        set_default_texture_parameters(&**gl, target, texture_descriptor.mip_level_count);
+        gl_call!(gl.bind_texture(target, None));
+        self.resources.textures.write().insert(texture_id, texture);
+        Ok(texture_id)
+    }
+
+    /// Stores GL target of texture created with given view dimension
+    fn register_texture_target(
+        &self,
+        texture_id: TextureId,
+        texture_descriptor: &TextureDescriptor,
+        view_dimension: TextureViewDimension,
+    ) -> u32 {
+        let size = texture_descriptor.size;
+        let view_dimension = if view_dimension == TextureViewDimension::Cube
+            && (size.depth != 6 || size.width != size.height)
+        {
+            let fallback = default_view_dimension(texture_descriptor);
+            error!(
+                "cube map texture requires 6 square layers, got {}x{}x{}, creating it as {:?}",
+                size.width, size.height, size.depth, fallback
+            );
+            fallback
+        } else {
+            view_dimension
+        };
+        let target = view_dimension.webgl2_into();
+        self.resources
+            .texture_targets
+            .write()
+            .insert(texture_id, target);
+        target
+    }
+
+    /// Re-creates 2D array texture of 6 square layers as cube map, copying its layers into
+    /// faces. `create_texture` can't tell cube maps from 2D arrays, so they're converted
+    /// once bound to `samplerCube` uniform. Returns the new target, `None` if the texture
+    /// can't be converted.
+    fn convert_to_cube_map(&self, gl: &dyn GlBackend, texture_id: TextureId) -> Option<u32> {
+        let descriptor = *self.resources.texture_descriptors.read().get(&texture_id)?;
+        let size = descriptor.size;
+        if size.depth != 6 || size.width != size.height {
+            error!(
+                "texture bound as cube map requires 6 square layers, got {}x{}x{}",
+                size.width, size.height, size.depth
+            );
+            return None;
+        }
+        // layers are copied from framebuffer with copyTexSubImage2D
+        if !is_color_renderable(descriptor.format) {
+            error!(
+                "texture of {:?} format bound as cube map can't be converted from 2D array",
+                descriptor.format
+            );
+            return None;
+        }
+        let mut textures = self.resources.textures.write();
+        let array_texture = *textures.get(&texture_id)?;
+        let (internal_format, _, _) = descriptor.format.webgl2_into();
+        let levels = descriptor.mip_level_count;
+        let cube_texture = gl_call!(gl.create_texture()).unwrap();
+        gl_call!(gl.bind_texture(Gl::TEXTURE_CUBE_MAP, Some(cube_texture)));
+        gl_call!(gl.tex_storage_2d(
+            Gl::TEXTURE_CUBE_MAP,
+            levels as i32,
+            internal_format,
+            size.width as i32,
+            size.height as i32,
+        ));
+        set_default_texture_parameters(gl, Gl::TEXTURE_CUBE_MAP, levels);
+
+        let framebuffer = gl_call!(gl.create_framebuffer()).unwrap();
+        gl_call!(gl.bind_framebuffer(Gl::READ_FRAMEBUFFER, Some(framebuffer)));
+        for level in 0..levels {
+            let level_size = (size.width >> level).max(1) as i32;
+            for (layer, face) in CUBE_MAP_FACES.iter().enumerate() {
+                gl_call!(gl.framebuffer_texture_layer(
+                    Gl::READ_FRAMEBUFFER,
+                    Gl::COLOR_ATTACHMENT0,
+                    Some(array_texture),
+                    level as i32,
+                    layer as i32,
+                ));
+                gl_call!(gl.copy_tex_sub_image_2d(
+                    *face,
+                    level as i32,
+                    0,
+                    0,
+                    0,
+                    0,
+                    level_size,
+                    level_size,
+                ));
+            }
+        }
+        gl_call!(gl.bind_framebuffer(Gl::READ_FRAMEBUFFER, None));
+        gl_call!(gl.delete_framebuffer(framebuffer));
+        gl_call!(gl.bind_texture(Gl::TEXTURE_CUBE_MAP, None));
+
+        gl_call!(gl.delete_texture(array_texture));
+        textures.insert(texture_id, cube_texture);
+        self.resources
+            .texture_targets
+            .write()
+            .insert(texture_id, Gl::TEXTURE_CUBE_MAP);
+        // pass framebuffers have the 2D array attached
+        self.resources
+            .framebuffers
+            .write()
+            .retain(|attachments, framebuffer| {
+                let attached = attachments.contains(&Some(texture_id));
+                if attached {
+                    gl_call!(gl.delete_framebuffer(*framebuffer));
+                }
+                !attached
+            });
+        Some(Gl::TEXTURE_CUBE_MAP)
+    }
+
+    /// Creates texture with given view dimension, e.g. `Cube` for 6-layer cube maps
+    /// (`create_texture` creates them as 2D arrays, see [`default_view_dimension`], converted
+    /// to cube maps once bound to `samplerCube`)
+    pub fn create_texture_with_view_dimension(
+        &self,
+        texture_descriptor: TextureDescriptor,
+        view_dimension: TextureViewDimension,
+    ) -> TextureId {
+        let texture_id = TextureId::new();
+        self.add_texture_descriptor(texture_id, texture_descriptor);
+        let gl = &self.device.get_context();
+        if texture_descriptor.sample_count > 1 {
+            let renderbuffer = self.create_multisampled_renderbuffer(&**gl, &texture_descriptor);
+            self.resources
+                .renderbuffers
+                .write()
+                .insert(texture_id, renderbuffer);
+            return texture_id;
+        }
+        let texture = gl_call!(gl.create_texture()).unwrap();
+
+        let size = texture_descriptor.size;
+        let target = self.register_texture_target(texture_id, &texture_descriptor, view_dimension);
+        gl_call!(gl.bind_texture(target, Some(texture)));
+
+        let (internal_format, format, _type) = texture_descriptor.format.webgl2_into();
+
+        if is_3d_target(target) {
+            // immutable storage for all layers and levels, filled with texSubImage3D
+            gl_call!(gl.tex_storage_3d(
+                target,
+                texture_descriptor.mip_level_count as i32,
+                internal_format,
+                size.width as i32,
+                size.height as i32,
+                size.depth as i32,
+            ));
+        } else if texture_descriptor.mip_level_count > 1 {
+            // immutable storage for all levels, filled with texSubImage2D
+            gl_call!(gl.tex_storage_2d(
+                target,
+                texture_descriptor.mip_level_count as i32,
+                internal_format,
+                size.width as i32,
+                size.height as i32,
+            ));
+        } else {
+            gl_call!(gl.bind_buffer(Gl::PIXEL_UNPACK_BUFFER, None));
+            let layers = if target == Gl::TEXTURE_CUBE_MAP { 6 } else { 1 };
+            for layer in 0..layers {
+                gl_call!(gl.tex_image_2d(
+                    image_target(target, layer),
+                    0,
+                    internal_format as i32,
+                    size.width as i32,
+                    size.height as i32,
+                    0,
+                    format,
+                    _type,
+                    None,
+                ));
+            }
+        }
+        set_default_texture_parameters(&**gl, target, texture_descriptor.mip_level_count);
+        gl_call!(gl.bind_texture(target, None));
+
+        self.resources.textures.write().insert(texture_id, texture);
+        texture_id
+    }
+
     pub fn create_bind_group_layout(&self, descriptor: &BindGroupDescriptor) {
         if self.bind_group_descriptor_exists(descriptor.id) {
             return;
@@ -74,7 +408,7 @@ impl WebGL2RenderResourceContext {
         match &shader.source {
             ShaderSource::Glsl(source) => {
                 info!("compiling shader: {:?}", source);
-                compile_shader(&self.device.get_context(), shader_type, source).unwrap()
+                compile_shader(&*self.device.get_context(), shader_type, source).unwrap()
             }
             _ => {
                 panic!("unsupported shader format");
@@ -82,35 +416,218 @@ impl WebGL2RenderResourceContext {
         }
     }
 
-    #[allow(unused_variables)]
-    pub fn initialize(&mut self, winit_window: &winit::window::Window) {
-        use wasm_bindgen::JsCast;
-        use winit::platform::web::WindowExtWebSys;
+    /// Assigns uniform block binding points and texture units to uniforms of `program`
+    fn bind_program_uniforms(
+        &self,
+        gl: &dyn GlBackend,
+        program: NativeProgram,
+        layout: &PipelineLayout,
+    ) {
+        gl_call!(gl.use_program(Some(program)));
+        info!("start binding");
+        for bind_group in layout.bind_groups.iter() {
+            for binding in bind_group.bindings.iter() {
+                let block_index = gl_call!(gl.get_uniform_block_index(program, &binding.name));
+                info!("trying to bind {:?}", binding.name);
+                if (block_index as i32) < 0 {
+                    info!("invalid block index for {:?}, skipping", &binding.name);
+                    if let Some(uniform_location) =
+                        gl_call!(gl.get_uniform_location(program, &binding.name))
+                    {
+                        info!("found uniform location: {:?}", uniform_location);
+                        if let BindType::Texture { .. } | BindType::StorageBuffer { .. } =
+                            binding.bind_type
+                        {
+                            let texture_unit = match self
+                                .resources
+                                .get_or_create_texture_unit(bind_group.index, binding.index)
+                            {
+                                Some(texture_unit) => texture_unit,
+                                None => continue,
+                            };
+                            gl_call!(gl.uniform1i(Some(uniform_location), texture_unit as i32));
+                            info!(
+                                "found texture uniform {:?}, binding to unit {:?}",
+                                binding.name, texture_unit
+                            );
+                        } else {
+                            panic!(
+                                "non-block uniforms expected only for textures and storage buffers"
+                            );
+                        }
+                    } else {
+                        info!("can't bind {:?}", binding.name);
+                    }
+                    continue;
+                }
+                let binding_point = match self
+                    .resources
+                    .get_or_create_binding_point(bind_group.index, binding.index)
+                {
+                    Some(binding_point) => binding_point,
+                    None => continue,
+                };
+                gl_call!(gl.uniform_block_binding(program, block_index, binding_point));
+                let _min_data_size = gl_call!(gl.get_active_uniform_block_parameter_i32(
+                    program,
+                    block_index,
+                    Gl::UNIFORM_BLOCK_DATA_SIZE,
+                ));
+                info!(
+                    "uniform_block_binding: name: {:?}, block_index: {:?}, binding_point: {:?}, min_data_size: {:?}",
+                    binding.name,
+                    block_index,
+                    binding_point,
+                    _min_data_size,
+                );
+            }
+        }
+        info!("done binding");
+    }
+
+    /// Creates batched variants of sprite pipelines compiled by `pipeline_compiler`
+    /// (recognized by handles of their source pipelines), see [`crate::SpriteBatch`]
+    pub fn create_sprite_batch_pipelines(
+        &self,
+        pipeline_compiler: &PipelineCompiler,
+        shaders: &Assets<Shader>,
+    ) {
+        for kind in SpriteBatchKind::ALL.iter() {
+            let compiled_pipelines =
+                match pipeline_compiler.iter_compiled_pipelines(kind.pipeline_handle()) {
+                    Some(compiled_pipelines) => compiled_pipelines,
+                    None => continue,
+                };
+            for pipeline_handle in compiled_pipelines {
+                let is_batched = match self.resources.pipelines.read().get(pipeline_handle) {
+                    Some(pipeline) => pipeline.sprite_batch.is_some(),
+                    None => continue,
+                };
+                if is_batched {
+                    continue;
+                }
+                let pipeline_descriptor =
+                    match self.pipeline_descriptors.read().get(pipeline_handle) {
+                        Some(pipeline_descriptor) => pipeline_descriptor.clone(),
+                        None => continue,
+                    };
+                let gl = &self.device.get_context();
+                let sprite_batch =
+                    self.create_sprite_batch_pipeline(&**gl, *kind, &pipeline_descriptor, shaders);
+                if let Some(pipeline) = self.resources.pipelines.write().get_mut(pipeline_handle) {
+                    pipeline.sprite_batch = sprite_batch;
+                }
+            }
+        }
+    }
+
+    /// Creates batched variant of sprite pipeline
+    fn create_sprite_batch_pipeline(
+        &self,
+        gl: &dyn GlBackend,
+        kind: SpriteBatchKind,
+        pipeline_descriptor: &PipelineDescriptor,
+        shaders: &Assets<Shader>,
+    ) -> Option<GlSpriteBatchPipeline> {
+        let layout = pipeline_descriptor.get_layout()?;
+        let stages = &pipeline_descriptor.shader_stages;
+        let vertex_source = match &shaders.get(&stages.vertex)?.source {
+            ShaderSource::Glsl(source) => source,
+            _ => return None,
+        };
+        let batch_vertex_source = kind.batch_vertex_shader(vertex_source);
+        let fragment = self.compile_shader(shaders.get(stages.fragment.as_ref()?)?);
+        let vertex = compile_shader(gl, Gl::VERTEX_SHADER, &batch_vertex_source)
+            .expect("sprite batch vertex shader");
+        let program = link_program(gl, &[vertex, fragment]).expect("sprite batch program");
+        self.bind_program_uniforms(gl, program.program, layout);
+        // uniform buffers of sprite bind group are shadowed, see `shadow_uniform_buffer`
+        if let Some(bind_group) = layout.get_bind_group(SPRITE_BIND_GROUP_INDEX) {
+            self.resources
+                .sprite_bind_group_layouts
+                .write()
+                .insert(bind_group.id);
+        }
+        info!("created sprite batch pipeline: {:?}", kind);
+        Some(GlSpriteBatchPipeline {
+            kind,
+            program: program.program,
+            vao: gl_call!(gl.create_vertex_array()).unwrap(),
+            instance_buffer: gl_call!(gl.create_buffer()).unwrap(),
+        })
+    }
+
+    /// Starts keeping CPU copy of uniform buffer contents (read back from GL buffer),
+    /// used to pack per-sprite uniforms into instance data of sprite batches
+    fn shadow_uniform_buffer(&self, gl: &dyn GlBackend, buffer: BufferId) {
+        let mut buffers = self.resources.buffers.write();
+        let gl_buffer = match buffers.get_mut(&buffer) {
+            Some(gl_buffer) => gl_buffer,
+            None => return,
+        };
+        if let (Buffer::WebGlBuffer(id), None) = (&gl_buffer.buffer, &gl_buffer.shadow) {
+            let mut shadow = vec![0; gl_buffer.info.size];
+            gl_call!(gl.bind_buffer(Gl::COPY_READ_BUFFER, Some(*id)));
+            gl_call!(gl.get_buffer_sub_data(Gl::COPY_READ_BUFFER, 0, &mut shadow));
+            gl_call!(gl.bind_buffer(Gl::COPY_READ_BUFFER, None));
+            gl_buffer.shadow = Some(shadow);
+        }
+    }
 
+    pub fn initialize(
+        &mut self,
+        winit_window: &winit::window::Window,
+        settings: &WebGL2ContextSettings,
+    ) -> Result<(), String> {
         let size = winit_window.inner_size();
-        let ctx_options = js_sys::Object::new();
-        // TODO - test performance for alpha disabled / enabled
-        // #[allow(unused_unsafe)]
-        // unsafe {
-        //     js_sys::Reflect::set(&ctx_options, &"alpha".into(), &false.into()).unwrap();
-        // }
-        let gl = winit_window
-            .canvas()
-            .get_context_with_context_options("webgl2", &ctx_options)
-            .unwrap()
-            .unwrap()
-            .dyn_into::<web_sys::WebGl2RenderingContext>()
-            .unwrap();
+        let gl = Self::create_backend(winit_window, settings)?;
+        *self.resources.context_settings.write() = settings.clone();
+        self.initialize_with_backend(size.width, size.height, gl);
+        Ok(())
+    }
 
-        let uniform_buffer_offset_alignment = gl
-            .get_parameter(Gl::UNIFORM_BUFFER_OFFSET_ALIGNMENT)
-            .unwrap()
-            .as_f64()
-            .unwrap() as usize;
-        info!(
-            "uniform_buffer_offset_alignment: {:?}",
-            uniform_buffer_offset_alignment
-        );
+    /// Initializes context without a window, on offscreen OSMesa context.
+    /// Rendering has to target textures, which can be read back with
+    /// `copy_texture_to_buffer` / `read_mapped_buffer`
+    #[cfg(all(feature = "native", not(target_arch = "wasm32")))]
+    pub fn initialize_headless(&mut self, width: u32, height: u32) -> Result<(), String> {
+        let gl = super::GlowBackend::headless(width, height)?;
+        self.initialize_with_backend(width, height, Box::new(gl));
+        *self.resources.window_size.write() = (width, height);
+        Ok(())
+    }
+
+    #[allow(unused_variables)]
+    fn create_backend(
+        winit_window: &winit::window::Window,
+        settings: &WebGL2ContextSettings,
+    ) -> Result<Box<dyn GlBackend>, String> {
+        cfg_if::cfg_if! {
+            if #[cfg(target_arch = "wasm32")] {
+                use wasm_bindgen::JsCast;
+                use winit::platform::web::WindowExtWebSys;
+
+                let ctx_options = settings.context_attributes();
+                let gl = winit_window
+                    .canvas()
+                    .get_context_with_context_options("webgl2", &ctx_options)
+                    .map_err(|err| format!("can't get webgl2 context: {:?}", err))?
+                    .ok_or("webgl2 is not supported")?
+                    .dyn_into::<web_sys::WebGl2RenderingContext>()
+                    .map_err(|_| "invalid webgl2 context")?;
+                Ok(Box::new(super::WebGlBackend::new(gl)))
+            } else if #[cfg(feature = "native")] {
+                let size = winit_window.inner_size();
+                Ok(Box::new(super::GlowBackend::headless(size.width, size.height)?))
+            } else {
+                Err("native targets require `native` feature of bevy_webgl2".to_string())
+            }
+        }
+    }
+
+    pub fn initialize_with_backend(&mut self, width: u32, height: u32, gl: Box<dyn GlBackend>) {
+        let capabilities = WebGL2Capabilities::query(&*gl);
+        info!("capabilities: {:?}", capabilities);
 
         // let ret = gl`
         //     .get_framebuffer_attachment_parameter(
@@ -128,7 +645,19 @@ impl WebGL2RenderResourceContext {
         //     ret == Gl::SRGB
         // );
 
-        gl_call!(gl.viewport(0, 0, size.width as i32, size.height as i32));
+        {
+            let mut formats = self.resources.compressed_texture_formats.write();
+            for format in CompressedTextureFormat::ALL.iter() {
+                let extension = format.extension();
+                if capabilities.supports_extension(extension) && gl.enable_extension(extension) {
+                    formats.insert(*format);
+                }
+            }
+            info!("compressed texture formats: {:?}", formats);
+        }
+        *self.resources.capabilities.write() = capabilities;
+
+        gl_call!(gl.viewport(0, 0, width as i32, height as i32));
         gl_call!(gl.enable(Gl::BLEND));
         gl_call!(gl.enable(Gl::DEPTH_TEST));
         self.device.set_context(gl);
@@ -136,6 +665,42 @@ impl WebGL2RenderResourceContext {
     }
 }
 
+/// Creates GL sampler object with parameters of `d`
+fn create_gl_sampler(gl: &dyn GlBackend, d: &SamplerDescriptor) -> NativeSampler {
+    let sampler = gl_call!(gl.create_sampler()).unwrap();
+    let params: [(u32, u32); 6] = [
+        (Gl::TEXTURE_WRAP_S, d.address_mode_u.webgl2_into()),
+        (Gl::TEXTURE_WRAP_T, d.address_mode_v.webgl2_into()),
+        (Gl::TEXTURE_WRAP_R, d.address_mode_w.webgl2_into()),
+        (Gl::TEXTURE_MAG_FILTER, d.mag_filter.webgl2_into()),
+        (
+            Gl::TEXTURE_MIN_FILTER,
+            (d.min_filter, d.mipmap_filter).webgl2_into(),
+        ),
+        (
+            Gl::TEXTURE_COMPARE_MODE,
+            if d.compare_function.is_some() {
+                Gl::COMPARE_REF_TO_TEXTURE
+            } else {
+                Gl::NONE
+            },
+        ),
+    ];
+    for (pname, param) in params.iter() {
+        gl_call!(gl.sampler_parameteri(sampler, *pname, *param as i32));
+    }
+    if let Some(compare_function) = d.compare_function {
+        gl_call!(gl.sampler_parameteri(
+            sampler,
+            Gl::TEXTURE_COMPARE_FUNC,
+            WebGl2Into::<u32>::webgl2_into(compare_function) as i32,
+        ));
+    }
+    gl_call!(gl.sampler_parameterf(sampler, Gl::TEXTURE_MIN_LOD, d.lod_min_clamp));
+    gl_call!(gl.sampler_parameterf(sampler, Gl::TEXTURE_MAX_LOD, d.lod_max_clamp));
+    sampler
+}
+
 impl RenderResourceContext for WebGL2RenderResourceContext {
     fn reflect_pipeline_layout(
         &self,
@@ -153,7 +718,7 @@ impl RenderResourceContext for WebGL2RenderResourceContext {
 
         let gl = &self.device.get_context();
 
-        let layout = reflect_layout(&*gl, &program);
+        let layout = reflect_layout(&**gl, &program);
         debug!("reflected layout: {:#?}", layout);
         self.resources
             .programs
@@ -167,7 +732,7 @@ impl RenderResourceContext for WebGL2RenderResourceContext {
     }
 
     fn get_aligned_uniform_size(&self, size: usize, _dynamic: bool) -> usize {
-        return (size + BIND_BUFFER_ALIGNMENT - 1) & !(BIND_BUFFER_ALIGNMENT - 1);
+        self.limits().align_uniform_size(size)
     }
 
     fn create_swap_chain(&self, window: &Window) {
@@ -187,46 +752,36 @@ impl RenderResourceContext for WebGL2RenderResourceContext {
 
     fn drop_all_swap_chain_textures(&self) {}
 
-    fn create_sampler(&self, _sampler_descriptor: &SamplerDescriptor) -> SamplerId {
-        SamplerId::new()
+    fn create_sampler(&self, sampler_descriptor: &SamplerDescriptor) -> SamplerId {
+        let sampler_id = SamplerId::new();
+        let gl = &self.device.get_context();
+        let d = sampler_descriptor;
+        let sampler = create_gl_sampler(&**gl, d);
+        let filters = [d.mag_filter, d.min_filter, d.mipmap_filter];
+        if filters.contains(&FilterMode::Linear) {
+            let non_filtering = create_gl_sampler(
+                &**gl,
+                &SamplerDescriptor {
+                    mag_filter: FilterMode::Nearest,
+                    min_filter: FilterMode::Nearest,
+                    mipmap_filter: FilterMode::Nearest,
+                    ..*d
+                },
+            );
+            self.resources
+                .non_filtering_samplers
+                .write()
+                .insert(sampler_id, non_filtering);
+        }
+        self.resources.samplers.write().insert(sampler_id, sampler);
+        sampler_id
     }
 
     fn create_texture(&self, texture_descriptor: TextureDescriptor) -> TextureId {
-        let texture_id = TextureId::new();
-        self.add_texture_descriptor(texture_id, texture_descriptor);
-        let gl = &self.device.get_context();
-        let texture = gl_call!(gl.create_texture()).unwrap();
-
-        let size = texture_descriptor.size;
-        gl_call!(gl.bind_texture(Gl::TEXTURE_2D, Some(&texture)));
-
-        let (internal_format, format, _type) = texture_descriptor.format.webgl2_into();
-
-        gl_call!(
-            gl.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
-                Gl::TEXTURE_2D,
-                0, //destination_mip_level as i32,
-                internal_format as i32,
-                size.width as i32,
-                size.height as i32,
-                0,
-                format,
-                _type,
-                None as Option<&[u8]>,
-            )
+        self.create_texture_with_view_dimension(
+            texture_descriptor,
+            default_view_dimension(&texture_descriptor),
         )
-        .unwrap();
-        gl_call!(gl.tex_parameteri(Gl::TEXTURE_2D, Gl::TEXTURE_MIN_FILTER, Gl::NEAREST as i32));
-        gl_call!(gl.tex_parameteri(Gl::TEXTURE_2D, Gl::TEXTURE_MAG_FILTER, Gl::NEAREST as i32));
-        gl_call!(gl.tex_parameteri(Gl::TEXTURE_2D, Gl::TEXTURE_WRAP_S, Gl::CLAMP_TO_EDGE as i32));
-        gl_call!(gl.tex_parameteri(Gl::TEXTURE_2D, Gl::TEXTURE_WRAP_T, Gl::CLAMP_TO_EDGE as i32));
-        gl_call!(gl.bind_texture(
-            Gl::TEXTURE_2D,
-            None as Option<&crate::renderer::WebGlTexture>,
-        ));
-
-        self.resources.textures.write().insert(texture_id, texture);
-        texture_id
     }
 
     fn create_buffer(&self, info: BufferInfo) -> BufferId {
@@ -238,6 +793,7 @@ impl RenderResourceContext for WebGL2RenderResourceContext {
             buffer_id,
         );
 
+        let mut storage_texture = None;
         let buffer = if info
             .buffer_usage
             .contains(BufferUsage::MAP_WRITE | BufferUsage::COPY_SRC)
@@ -247,16 +803,17 @@ impl RenderResourceContext for WebGL2RenderResourceContext {
             unsafe { data.set_len(info.size) };
             Buffer::Data(data)
         } else {
-            let size = if info.buffer_usage.contains(BufferUsage::STORAGE) {
-                STORAGE_BUFFER_SIZE
-            } else {
-                info.size
-            };
             let gl = &self.device.get_context();
+            if info.buffer_usage.contains(BufferUsage::STORAGE) {
+                storage_texture = self.create_storage_texture(&**gl, info.size);
+            }
+            let size = storage_texture
+                .as_ref()
+                .map_or(info.size, GlStorageTexture::buffer_size);
             let id = gl_call!(gl.create_buffer())
                 .ok_or("failed to create_buffer")
                 .unwrap();
-            gl_call!(gl.bind_buffer(Gl::UNIFORM_BUFFER, Some(&id)));
+            gl_call!(gl.bind_buffer(Gl::UNIFORM_BUFFER, Some(id)));
             let type_ = if info
                 .buffer_usage
                 .contains(BufferUsage::COPY_DST | BufferUsage::INDIRECT)
@@ -265,10 +822,16 @@ impl RenderResourceContext for WebGL2RenderResourceContext {
             } else {
                 Gl::DYNAMIC_DRAW
             };
-            gl_call!(gl.buffer_data_with_i32(Gl::UNIFORM_BUFFER, size as i32, type_));
+            gl_call!(gl.buffer_data_size(Gl::UNIFORM_BUFFER, size as i32, type_));
             Buffer::WebGlBuffer(id)
         };
-        let gl_buffer_info = GlBufferInfo { buffer, info };
+        let gl_buffer_info = GlBufferInfo {
+            buffer,
+            info,
+            // kept for buffers of sprite bind groups, see `shadow_uniform_buffer`
+            shadow: None,
+            storage_texture,
+        };
         self.resources
             .buffers
             .write()
@@ -296,16 +859,16 @@ impl RenderResourceContext for WebGL2RenderResourceContext {
         match &mut buffer.buffer {
             Buffer::WebGlBuffer(buffer_id) => {
                 let gl = &self.device.get_context();
-                gl_call!(gl.bind_buffer(Gl::COPY_WRITE_BUFFER, Some(&buffer_id)));
-                gl_call!(
-                    gl.buffer_sub_data_with_i32_and_u8_array_and_src_offset_and_length(
-                        Gl::COPY_WRITE_BUFFER,
-                        range.start as i32,
-                        &data,
-                        0,
-                        data.len() as u32,
-                    )
-                );
+                gl_call!(gl.bind_buffer(Gl::COPY_WRITE_BUFFER, Some(*buffer_id)));
+                gl_call!(gl.buffer_sub_data_u8_slice(
+                    Gl::COPY_WRITE_BUFFER,
+                    range.start as i32,
+                    &data,
+                ));
+                if let Some(shadow) = &mut buffer.shadow {
+                    write_shadow(shadow, range.start as usize, &data);
+                }
+                update_storage_texture(&**gl, buffer);
             }
             Buffer::Data(buffer_data) => {
                 let sub_data =
@@ -349,8 +912,8 @@ impl RenderResourceContext for WebGL2RenderResourceContext {
                 data.set_len((range.end - range.start) as usize);
             }
             let gl = &self.device.get_context();
-            gl.bind_buffer(Gl::PIXEL_PACK_BUFFER, Some(buffer_id));
-            gl.get_buffer_sub_data_with_i32_and_u8_array(Gl::PIXEL_PACK_BUFFER, 0, data.as_mut());
+            gl.bind_buffer(Gl::PIXEL_PACK_BUFFER, Some(*buffer_id));
+            gl.get_buffer_sub_data(Gl::PIXEL_PACK_BUFFER, 0, data.as_mut());
             read(data.as_mut(), self);
         }
     }
@@ -375,6 +938,7 @@ impl RenderResourceContext for WebGL2RenderResourceContext {
         if info.size == 0 {
             info.size = data.len();
         }
+        let mut storage_texture = None;
         let buffer = if info
             .buffer_usage
             .contains(BufferUsage::MAP_WRITE | BufferUsage::COPY_SRC)
@@ -387,27 +951,47 @@ impl RenderResourceContext for WebGL2RenderResourceContext {
                 .ok_or("failed to create_buffer")
                 .unwrap();
             if info.buffer_usage & BufferUsage::VERTEX == BufferUsage::VERTEX {
-                gl_call!(gl.bind_buffer(Gl::ARRAY_BUFFER, Some(&id)));
-                gl_call!(gl.buffer_data_with_u8_array(Gl::ARRAY_BUFFER, &data, Gl::DYNAMIC_DRAW));
+                gl_call!(gl.bind_buffer(Gl::ARRAY_BUFFER, Some(id)));
+                gl_call!(gl.buffer_data_u8_slice(Gl::ARRAY_BUFFER, &data, Gl::DYNAMIC_DRAW));
             } else if info.buffer_usage & BufferUsage::INDEX == BufferUsage::INDEX {
-                gl_call!(gl.bind_buffer(Gl::ELEMENT_ARRAY_BUFFER, Some(&id)));
-                gl_call!(gl.buffer_data_with_u8_array(
+                // ELEMENT_ARRAY_BUFFER binding is a part of VAO state, so make sure
+                // we don't modify VAO left bound after the last draw call
+                gl_call!(gl.bind_vertex_array(None));
+                gl_call!(gl.bind_buffer(Gl::ELEMENT_ARRAY_BUFFER, Some(id)));
+                gl_call!(gl.buffer_data_u8_slice(
                     Gl::ELEMENT_ARRAY_BUFFER,
                     &data,
                     Gl::DYNAMIC_DRAW
                 ));
-            } else {
-                gl_call!(gl.bind_buffer(Gl::PIXEL_UNPACK_BUFFER, Some(&id)));
-                gl_call!(gl.buffer_data_with_u8_array(
+            } else if info.buffer_usage.contains(BufferUsage::STORAGE) {
+                storage_texture = self.create_storage_texture(&**gl, info.size);
+                let size = storage_texture
+                    .as_ref()
+                    .map_or(data.len(), GlStorageTexture::buffer_size);
+                gl_call!(gl.bind_buffer(Gl::PIXEL_UNPACK_BUFFER, Some(id)));
+                gl_call!(gl.buffer_data_size(
                     Gl::PIXEL_UNPACK_BUFFER,
-                    &data,
+                    size as i32,
                     Gl::DYNAMIC_DRAW
                 ));
+                gl_call!(gl.buffer_sub_data_u8_slice(Gl::PIXEL_UNPACK_BUFFER, 0, &data));
+            } else {
+                gl_call!(gl.bind_buffer(Gl::PIXEL_UNPACK_BUFFER, Some(id)));
+                gl_call!(gl.buffer_data_u8_slice(Gl::PIXEL_UNPACK_BUFFER, &data, Gl::DYNAMIC_DRAW));
             };
             Buffer::WebGlBuffer(id)
         };
 
-        let gl_buffer_info = GlBufferInfo { buffer, info };
+        let gl_buffer_info = GlBufferInfo {
+            buffer,
+            info,
+            // kept for buffers of sprite bind groups, see `shadow_uniform_buffer`
+            shadow: None,
+            storage_texture,
+        };
+        if gl_buffer_info.storage_texture.is_some() {
+            update_storage_texture(&*self.device.get_context(), &gl_buffer_info);
+        }
         self.resources
             .buffers
             .write()
@@ -422,7 +1006,10 @@ impl RenderResourceContext for WebGL2RenderResourceContext {
         let mut buffers = self.resources.buffers.write();
         let gl_buffer = buffers.remove(&buffer).unwrap();
         if let Buffer::WebGlBuffer(buffer_id) = &gl_buffer.buffer {
-            gl_call!(gl.delete_buffer(Some(buffer_id)));
+            gl_call!(gl.delete_buffer(*buffer_id));
+        }
+        if let Some(storage_texture) = &gl_buffer.storage_texture {
+            gl_call!(gl.delete_texture(storage_texture.texture));
         }
     }
 
@@ -430,12 +1017,45 @@ impl RenderResourceContext for WebGL2RenderResourceContext {
         let gl = &self.device.get_context();
         let mut texture_descriptors = self.resources.texture_descriptors.write();
         let mut textures = self.resources.textures.write();
-        let gl_texture = textures.remove(&texture).unwrap();
-        gl_call!(gl.delete_texture(Some(&gl_texture)));
+        if let Some(gl_texture) = textures.remove(&texture) {
+            gl_call!(gl.delete_texture(gl_texture));
+        } else {
+            let renderbuffer = self.resources.renderbuffers.write().remove(&texture);
+            gl_call!(gl.delete_renderbuffer(renderbuffer.unwrap()));
+        }
+        if let Some(framebuffer) = self.resources.resolve_framebuffers.write().remove(&texture) {
+            gl_call!(gl.delete_framebuffer(framebuffer));
+        }
+        self.resources
+            .framebuffers
+            .write()
+            .retain(|attachments, framebuffer| {
+                let attached = attachments.contains(&Some(texture));
+                if attached {
+                    gl_call!(gl.delete_framebuffer(*framebuffer));
+                }
+                !attached
+            });
         texture_descriptors.remove(&texture);
+        self.resources.texture_targets.write().remove(&texture);
+        self.resources.compressed_textures.write().remove(&texture);
+        self.resources.skipped_uploads.write().remove(&texture);
     }
 
-    fn remove_sampler(&self, _sampler: SamplerId) {}
+    fn remove_sampler(&self, sampler: SamplerId) {
+        let gl = &self.device.get_context();
+        if let Some(gl_sampler) = self.resources.samplers.write().remove(&sampler) {
+            gl_call!(gl.delete_sampler(gl_sampler));
+        }
+        if let Some(gl_sampler) = self
+            .resources
+            .non_filtering_samplers
+            .write()
+            .remove(&sampler)
+        {
+            gl_call!(gl.delete_sampler(gl_sampler));
+        }
+    }
 
     fn set_asset_resource_untyped(
         &self,
@@ -481,61 +1101,12 @@ impl RenderResourceContext for WebGL2RenderResourceContext {
 
         let programs = self.resources.programs.read();
         let program = programs.get(&pipeline_descriptor.shader_stages).unwrap();
-        gl_call!(gl.use_program(Some(&program.program)));
-        info!("start binding");
-        for bind_group in layout.bind_groups.iter() {
-            for binding in bind_group.bindings.iter() {
-                let block_index =
-                    gl_call!(gl.get_uniform_block_index(&program.program, &binding.name));
-                info!("trying to bind {:?}", binding.name);
-                if (block_index as i32) < 0 {
-                    info!("invalid block index for {:?}, skipping", &binding.name);
-                    if let Some(uniform_location) =
-                        gl_call!(gl.get_uniform_location(&program.program, &binding.name))
-                    {
-                        info!("found uniform location: {:?}", uniform_location);
-                        if let BindType::Texture { .. } = binding.bind_type {
-                            let texture_unit = self
-                                .resources
-                                .get_or_create_texture_unit(bind_group.index, binding.index);
-                            gl_call!(gl.uniform1i(Some(&uniform_location), texture_unit as i32));
-                            info!(
-                                "found texture uniform {:?}, binding to unit {:?}",
-                                binding.name, texture_unit
-                            );
-                        } else {
-                            panic!("use non-block uniforms expected only for textures");
-                        }
-                    } else {
-                        info!("can't bind {:?}", binding.name);
-                    }
-                    continue;
-                }
-                let binding_point = self
-                    .resources
-                    .get_or_create_binding_point(bind_group.index, binding.index);
-                gl_call!(gl.uniform_block_binding(&program.program, block_index, binding_point));
-                let _min_data_size = gl_call!(gl.get_active_uniform_block_parameter(
-                    &program.program,
-                    block_index,
-                    Gl::UNIFORM_BLOCK_DATA_SIZE,
-                ))
-                .unwrap();
-                info!(
-                    "uniform_block_binding: name: {:?}, block_index: {:?}, binding_point: {:?}, min_data_size: {:?}",
-                    binding.name,
-                    block_index,
-                    binding_point,
-                    _min_data_size,
-                );
-            }
-        }
-        info!("done binding");
+        self.bind_program_uniforms(&**gl, program.program, layout);
         info!("vertex_buffer_descriptors: {:?}", vertex_buffer_descriptors);
         let vertex_buffer_descriptors = vertex_buffer_descriptors
             .iter()
             .map(|vertex_buffer_descriptor| {
-                GlVertexBufferDescripror::from(gl, &program.program, vertex_buffer_descriptor)
+                GlVertexBufferDescripror::from(&**gl, program.program, vertex_buffer_descriptor)
             })
             .collect();
         let vao = gl_call!(gl.create_vertex_array()).unwrap();
@@ -545,13 +1116,27 @@ impl RenderResourceContext for WebGL2RenderResourceContext {
             vao,
             update_vao: false,
             index_buffer: None,
+            index_buffer_offset: 0,
             index_format: IndexFormat::Uint32,
-            vertex_buffer: None,
+            base_vertex: 0,
+            first_instance: 0,
+            vertex_buffers: Vec::new(),
             color_target_states: pipeline_descriptor.color_target_states.clone(),
             depth_stencil: pipeline_descriptor.depth_stencil.clone(),
             primitive: pipeline_descriptor.primitive.clone(),
             scissors_state: None,
+            // created in `create_sprite_batch_pipelines`
+            sprite_batch: None,
         };
+        if !pipeline.has_uniform_color_targets()
+            && !self.resources.capabilities.read().draw_buffers_indexed
+        {
+            warn!(
+                "pipeline {:?} has different blend states or write masks per color target, \
+                 without OES_draw_buffers_indexed all targets use state of the first one",
+                pipeline_handle
+            );
+        }
         self.pipeline_descriptors
             .write()
             .insert(pipeline_handle.clone(), pipeline_descriptor.clone());
@@ -569,40 +1154,140 @@ impl RenderResourceContext for WebGL2RenderResourceContext {
         assert!(self.bind_group_descriptor_exists(bind_group_descriptor_id));
         let layouts = self.resources.bind_group_layouts.read();
         let bind_group_layout = layouts.get(&bind_group_descriptor_id).unwrap();
-        let _gl = &self.device.get_context();
+        let gl = &self.device.get_context();
         let mut bind_groups = self.resources.bind_groups.write();
         if bind_groups.get(&bind_group.id).is_some() {
             return;
         }
+        if self
+            .resources
+            .sprite_bind_group_layouts
+            .read()
+            .contains(&bind_group_descriptor_id)
+        {
+            for entry in bind_group.indexed_bindings.iter() {
+                if let RenderResourceBinding::Buffer { buffer, .. } = &entry.entry {
+                    self.shadow_uniform_buffer(&**gl, *buffer);
+                }
+            }
+        }
+        let max_uniform_block_size = self.limits().max_uniform_block_size;
+        let layout_binding = |index: u32| {
+            bind_group_layout
+                .bindings
+                .iter()
+                .find(|binding| binding.index == index)
+        };
         let bind_group_vec: Vec<_> = bind_group
             .indexed_bindings
             .iter()
-            .filter(|entry| {
-                entry.entry.get_buffer().is_some() || entry.entry.get_texture().is_some()
-            }) // TODO
-            .map(|entry| match &entry.entry {
-                RenderResourceBinding::Buffer { buffer, range, .. } => {
-                    let binding_point = self
-                        .resources
-                        .get_or_create_binding_point(bind_group_layout.index, entry.index);
-                    WebGL2RenderResourceBinding::Buffer {
-                        binding_point,
-                        buffer: *buffer,
-                        range: range.clone(),
+            .filter_map(|entry| {
+                let binding = layout_binding(entry.index);
+                let bind_type = binding.map(|binding| &binding.bind_type);
+                Some(match &entry.entry {
+                    RenderResourceBinding::Buffer { buffer, .. }
+                        if matches!(bind_type, Some(BindType::StorageBuffer { .. })) =>
+                    {
+                        let texture_unit = self
+                            .resources
+                            .get_or_create_texture_unit(bind_group_layout.index, entry.index)?;
+                        WebGL2RenderResourceBinding::StorageBuffer {
+                            texture_unit,
+                            buffer: *buffer,
+                        }
                     }
-                }
-                RenderResourceBinding::Texture(texture) => {
-                    let texture_unit = self
-                        .resources
-                        .get_or_create_texture_unit(bind_group_layout.index, entry.index);
-                    WebGL2RenderResourceBinding::Texture {
-                        texture: *texture,
-                        texture_unit,
+                    RenderResourceBinding::Buffer { buffer, range, .. } => {
+                        let size = (range.end - range.start) as usize;
+                        if size > max_uniform_block_size {
+                            error!(
+                                "uniform buffer binding (set = {}, binding = {}) of {} bytes \
+                                 exceeds MAX_UNIFORM_BLOCK_SIZE ({}), \
+                                 shaders can't read past the limit",
+                                bind_group_layout.index, entry.index, size, max_uniform_block_size
+                            );
+                        }
+                        let binding_point = self
+                            .resources
+                            .get_or_create_binding_point(bind_group_layout.index, entry.index)?;
+                        WebGL2RenderResourceBinding::Buffer {
+                            binding_point,
+                            buffer: *buffer,
+                            range: range.clone(),
+                        }
                     }
-                }
-                RenderResourceBinding::Sampler(sampler) => {
-                    WebGL2RenderResourceBinding::Sampler(*sampler)
-                }
+                    RenderResourceBinding::Texture(texture) => {
+                        let texture_unit = self
+                            .resources
+                            .get_or_create_texture_unit(bind_group_layout.index, entry.index)?;
+                        let mut target = self
+                            .resources
+                            .texture_targets
+                            .read()
+                            .get(texture)
+                            .cloned()
+                            .unwrap_or(Gl::TEXTURE_2D);
+                        if let Some(BindType::Texture { view_dimension, .. }) = bind_type {
+                            if *view_dimension == TextureViewDimension::Cube
+                                && target == Gl::TEXTURE_2D_ARRAY
+                            {
+                                target =
+                                    self.convert_to_cube_map(&**gl, *texture).unwrap_or(target);
+                            }
+                            let expected: u32 = (*view_dimension).webgl2_into();
+                            if target != expected {
+                                warn!(
+                                    "texture bound as {:?} has target 0x{:x} (expected 0x{:x})",
+                                    binding.unwrap().name,
+                                    target,
+                                    expected
+                                );
+                            }
+                        }
+                        WebGL2RenderResourceBinding::Texture {
+                            texture: *texture,
+                            texture_unit,
+                            target,
+                        }
+                    }
+                    RenderResourceBinding::Sampler(sampler) => {
+                        // sampler of `<texture>` is bound as `<texture>_sampler`
+                        // and uses texture unit of its texture
+                        let texture_binding = binding
+                            .and_then(|binding| binding.name.strip_suffix("_sampler"))
+                            .and_then(|name| {
+                                bind_group_layout
+                                    .bindings
+                                    .iter()
+                                    .find(|binding| binding.name == name)
+                            });
+                        let texture_binding = match texture_binding {
+                            Some(texture_binding) => texture_binding,
+                            None => {
+                                warn!(
+                                    "no texture for sampler (set = {}, binding = {})",
+                                    bind_group_layout.index, entry.index
+                                );
+                                return None;
+                            }
+                        };
+                        let texture_unit = self.resources.get_or_create_texture_unit(
+                            bind_group_layout.index,
+                            texture_binding.index,
+                        )?;
+                        let filtering = !matches!(
+                            bind_type,
+                            Some(BindType::Sampler {
+                                filtering: false,
+                                ..
+                            })
+                        );
+                        WebGL2RenderResourceBinding::Sampler {
+                            sampler: *sampler,
+                            texture_unit,
+                            filtering,
+                        }
+                    }
+                })
             })
             .collect();
         bind_groups.insert(bind_group.id, bind_group_vec);
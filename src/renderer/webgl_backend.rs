@@ -0,0 +1,1021 @@
+use super::{
+    GlActiveInfo, GlBackend, NativeBuffer, NativeFramebuffer, NativeProgram, NativeRenderbuffer,
+    NativeSampler, NativeShader, NativeTexture, NativeUniformLocation, NativeVertexArray,
+};
+use bevy::utils::HashMap;
+use js_sys::Object;
+use std::cell::{Cell, RefCell};
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{
+    OesDrawBuffersIndexed, WebGl2RenderingContext, WebGlBuffer, WebGlFramebuffer, WebGlProgram,
+    WebGlRenderbuffer, WebGlSampler, WebGlShader, WebGlTexture, WebGlUniformLocation,
+    WebGlVertexArrayObject,
+};
+
+type Gl = WebGl2RenderingContext;
+
+const DRAW_BUFFERS_INDEXED: &str = "OES_draw_buffers_indexed";
+
+/// `GlBackend` implementation calling browser's WebGL2 context
+pub struct WebGlBackend {
+    gl: WebGl2RenderingContext,
+    objects: RefCell<HashMap<u32, JsValue>>,
+    object_id_seq: Cell<u32>,
+    draw_buffers_indexed: RefCell<Option<OesDrawBuffersIndexed>>,
+}
+
+impl WebGlBackend {
+    pub fn new(gl: WebGl2RenderingContext) -> Self {
+        WebGlBackend {
+            gl,
+            objects: Default::default(),
+            object_id_seq: Cell::new(1),
+            draw_buffers_indexed: Default::default(),
+        }
+    }
+
+    pub fn context(&self) -> &WebGl2RenderingContext {
+        &self.gl
+    }
+
+    fn insert<T: Into<JsValue>>(&self, object: Option<T>) -> Option<u32> {
+        let object = object?;
+        let id = self.object_id_seq.get();
+        self.object_id_seq.set(id + 1);
+        self.objects.borrow_mut().insert(id, object.into());
+        Some(id)
+    }
+
+    fn get<T: JsCast>(&self, id: u32) -> T {
+        self.objects
+            .borrow()
+            .get(&id)
+            .expect("unknown GL object")
+            .clone()
+            .unchecked_into()
+    }
+
+    fn draw_buffers_indexed(&self) -> OesDrawBuffersIndexed {
+        self.draw_buffers_indexed
+            .borrow()
+            .clone()
+            .expect("OES_draw_buffers_indexed is not enabled")
+    }
+
+    fn remove<T: JsCast>(&self, id: u32) -> T {
+        self.objects
+            .borrow_mut()
+            .remove(&id)
+            .expect("unknown GL object")
+            .unchecked_into()
+    }
+}
+
+impl std::fmt::Debug for WebGlBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&format!("WebGlBackend: {:?}", self.gl))
+    }
+}
+
+fn active_info(info: Option<web_sys::WebGlActiveInfo>) -> Option<GlActiveInfo> {
+    info.map(|info| GlActiveInfo {
+        name: info.name(),
+        size: info.size(),
+        type_: info.type_(),
+    })
+}
+
+// Views pixel data as `$elem` array, copying it first when `data` isn't aligned for `$elem`
+macro_rules! pixels_array {
+    ($array:ty, $elem:ty, $data:expr) => {{
+        let (prefix, values, _) = $data.align_to::<$elem>();
+        if prefix.is_empty() {
+            <$array>::view(values).into()
+        } else {
+            let mut aligned = vec![<$elem>::default(); $data.len() / std::mem::size_of::<$elem>()];
+            std::ptr::copy_nonoverlapping(
+                $data.as_ptr(),
+                aligned.as_mut_ptr() as *mut u8,
+                aligned.len() * std::mem::size_of::<$elem>(),
+            );
+            <$array>::from(&aligned[..]).into()
+        }
+    }};
+}
+
+// WebGL requires array buffer view type to match pixel data type
+fn pixels_view(type_: u32, data: &[u8]) -> Object {
+    unsafe {
+        match type_ {
+            Gl::BYTE => pixels_array!(js_sys::Int8Array, i8, data),
+            Gl::UNSIGNED_SHORT | Gl::HALF_FLOAT => pixels_array!(js_sys::Uint16Array, u16, data),
+            Gl::SHORT => pixels_array!(js_sys::Int16Array, i16, data),
+            Gl::UNSIGNED_INT
+            | Gl::UNSIGNED_INT_24_8
+            | Gl::UNSIGNED_INT_2_10_10_10_REV
+            | Gl::UNSIGNED_INT_10F_11F_11F_REV => pixels_array!(js_sys::Uint32Array, u32, data),
+            Gl::INT => pixels_array!(js_sys::Int32Array, i32, data),
+            Gl::FLOAT => pixels_array!(js_sys::Float32Array, f32, data),
+            _ => js_sys::Uint8Array::view(data).into(),
+        }
+    }
+}
+
+impl GlBackend for WebGlBackend {
+    fn get_parameter_i32(&self, pname: u32) -> i32 {
+        self.gl
+            .get_parameter(pname)
+            .unwrap()
+            .as_f64()
+            .unwrap_or(0.0) as i32
+    }
+
+    fn get_parameter_i32_array(&self, pname: u32) -> Vec<i32> {
+        let value: js_sys::Int32Array = self.gl.get_parameter(pname).unwrap().dyn_into().unwrap();
+        value.to_vec()
+    }
+
+    fn get_parameter_string(&self, pname: u32) -> Option<String> {
+        self.gl.get_parameter(pname).ok()?.as_string()
+    }
+
+    fn get_supported_extensions(&self) -> Vec<String> {
+        self.gl
+            .get_supported_extensions()
+            .map(|extensions| {
+                extensions
+                    .iter()
+                    .filter_map(|extension| extension.as_string())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn enable_extension(&self, name: &str) -> bool {
+        match self.gl.get_extension(name) {
+            Ok(Some(extension)) => {
+                if name == DRAW_BUFFERS_INDEXED {
+                    *self.draw_buffers_indexed.borrow_mut() = Some(extension.unchecked_into());
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn enable(&self, cap: u32) {
+        self.gl.enable(cap)
+    }
+
+    fn disable(&self, cap: u32) {
+        self.gl.disable(cap)
+    }
+
+    fn viewport(&self, x: i32, y: i32, width: i32, height: i32) {
+        self.gl.viewport(x, y, width, height)
+    }
+
+    fn scissor(&self, x: i32, y: i32, width: i32, height: i32) {
+        self.gl.scissor(x, y, width, height)
+    }
+
+    fn cull_face(&self, mode: u32) {
+        self.gl.cull_face(mode)
+    }
+
+    fn front_face(&self, mode: u32) {
+        self.gl.front_face(mode)
+    }
+
+    fn depth_func(&self, func: u32) {
+        self.gl.depth_func(func)
+    }
+
+    fn depth_mask(&self, flag: bool) {
+        self.gl.depth_mask(flag)
+    }
+
+    fn stencil_func_separate(&self, face: u32, func: u32, reference: i32, mask: u32) {
+        self.gl.stencil_func_separate(face, func, reference, mask)
+    }
+
+    fn stencil_op_separate(&self, face: u32, fail: u32, depth_fail: u32, pass: u32) {
+        self.gl.stencil_op_separate(face, fail, depth_fail, pass)
+    }
+
+    fn stencil_mask_separate(&self, face: u32, mask: u32) {
+        self.gl.stencil_mask_separate(face, mask)
+    }
+
+    fn polygon_offset(&self, factor: f32, units: f32) {
+        self.gl.polygon_offset(factor, units)
+    }
+
+    fn blend_func_separate(&self, src_rgb: u32, dst_rgb: u32, src_alpha: u32, dst_alpha: u32) {
+        self.gl
+            .blend_func_separate(src_rgb, dst_rgb, src_alpha, dst_alpha)
+    }
+
+    fn blend_equation_separate(&self, mode_rgb: u32, mode_alpha: u32) {
+        self.gl.blend_equation_separate(mode_rgb, mode_alpha)
+    }
+
+    fn blend_color(&self, r: f32, g: f32, b: f32, a: f32) {
+        self.gl.blend_color(r, g, b, a)
+    }
+
+    fn color_mask(&self, r: bool, g: bool, b: bool, a: bool) {
+        self.gl.color_mask(r, g, b, a)
+    }
+
+    fn enable_i(&self, cap: u32, index: u32) {
+        self.draw_buffers_indexed().enablei_oes(cap, index)
+    }
+
+    fn disable_i(&self, cap: u32, index: u32) {
+        self.draw_buffers_indexed().disablei_oes(cap, index)
+    }
+
+    fn blend_equation_separate_i(&self, buffer: u32, mode_rgb: u32, mode_alpha: u32) {
+        self.draw_buffers_indexed()
+            .blend_equation_separatei_oes(buffer, mode_rgb, mode_alpha)
+    }
+
+    fn blend_func_separate_i(
+        &self,
+        buffer: u32,
+        src_rgb: u32,
+        dst_rgb: u32,
+        src_alpha: u32,
+        dst_alpha: u32,
+    ) {
+        self.draw_buffers_indexed()
+            .blend_func_separatei_oes(buffer, src_rgb, dst_rgb, src_alpha, dst_alpha)
+    }
+
+    fn color_mask_i(&self, buffer: u32, r: bool, g: bool, b: bool, a: bool) {
+        self.draw_buffers_indexed()
+            .color_maski_oes(buffer, r, g, b, a)
+    }
+
+    fn clear_color(&self, r: f32, g: f32, b: f32, a: f32) {
+        self.gl.clear_color(r, g, b, a)
+    }
+
+    fn clear_depth(&self, depth: f32) {
+        self.gl.clear_depth(depth)
+    }
+
+    fn clear(&self, mask: u32) {
+        self.gl.clear(mask)
+    }
+
+    fn clear_bufferfv(&self, buffer: u32, draw_buffer: i32, values: &[f32]) {
+        self.gl
+            .clear_bufferfv_with_f32_array(buffer, draw_buffer, values)
+    }
+
+    fn clear_bufferiv(&self, buffer: u32, draw_buffer: i32, values: &[i32]) {
+        self.gl
+            .clear_bufferiv_with_i32_array_and_src_offset(buffer, draw_buffer, values, 0)
+    }
+
+    fn clear_bufferuiv(&self, buffer: u32, draw_buffer: i32, values: &[u32]) {
+        self.gl
+            .clear_bufferuiv_with_u32_array_and_src_offset(buffer, draw_buffer, values, 0)
+    }
+
+    fn clear_bufferfi(&self, buffer: u32, draw_buffer: i32, depth: f32, stencil: i32) {
+        self.gl.clear_bufferfi(buffer, draw_buffer, depth, stencil)
+    }
+
+    fn create_shader(&self, shader_type: u32) -> Option<NativeShader> {
+        self.insert(self.gl.create_shader(shader_type))
+            .map(NativeShader)
+    }
+
+    fn shader_source(&self, shader: NativeShader, source: &str) {
+        self.gl
+            .shader_source(&self.get::<WebGlShader>(shader.0), source)
+    }
+
+    fn compile_shader(&self, shader: NativeShader) {
+        self.gl.compile_shader(&self.get::<WebGlShader>(shader.0))
+    }
+
+    fn get_shader_compile_status(&self, shader: NativeShader) -> bool {
+        self.gl
+            .get_shader_parameter(&self.get::<WebGlShader>(shader.0), Gl::COMPILE_STATUS)
+            .as_bool()
+            .unwrap_or(false)
+    }
+
+    fn get_shader_info_log(&self, shader: NativeShader) -> Option<String> {
+        self.gl
+            .get_shader_info_log(&self.get::<WebGlShader>(shader.0))
+    }
+
+    fn create_program(&self) -> Option<NativeProgram> {
+        self.insert(self.gl.create_program()).map(NativeProgram)
+    }
+
+    fn attach_shader(&self, program: NativeProgram, shader: NativeShader) {
+        self.gl.attach_shader(
+            &self.get::<WebGlProgram>(program.0),
+            &self.get::<WebGlShader>(shader.0),
+        )
+    }
+
+    fn link_program(&self, program: NativeProgram) {
+        self.gl.link_program(&self.get::<WebGlProgram>(program.0))
+    }
+
+    fn get_program_link_status(&self, program: NativeProgram) -> bool {
+        self.gl
+            .get_program_parameter(&self.get::<WebGlProgram>(program.0), Gl::LINK_STATUS)
+            .as_bool()
+            .unwrap_or(false)
+    }
+
+    fn get_program_info_log(&self, program: NativeProgram) -> Option<String> {
+        self.gl
+            .get_program_info_log(&self.get::<WebGlProgram>(program.0))
+    }
+
+    fn get_program_parameter_i32(&self, program: NativeProgram, pname: u32) -> i32 {
+        self.gl
+            .get_program_parameter(&self.get::<WebGlProgram>(program.0), pname)
+            .as_f64()
+            .unwrap_or(0.0) as i32
+    }
+
+    fn use_program(&self, program: Option<NativeProgram>) {
+        let program = program.map(|program| self.get::<WebGlProgram>(program.0));
+        self.gl.use_program(program.as_ref())
+    }
+
+    fn get_active_attrib(&self, program: NativeProgram, index: u32) -> Option<GlActiveInfo> {
+        active_info(
+            self.gl
+                .get_active_attrib(&self.get::<WebGlProgram>(program.0), index),
+        )
+    }
+
+    fn get_active_uniform(&self, program: NativeProgram, index: u32) -> Option<GlActiveInfo> {
+        active_info(
+            self.gl
+                .get_active_uniform(&self.get::<WebGlProgram>(program.0), index),
+        )
+    }
+
+    fn get_active_uniform_block_name(&self, program: NativeProgram, index: u32) -> Option<String> {
+        self.gl
+            .get_active_uniform_block_name(&self.get::<WebGlProgram>(program.0), index)
+    }
+
+    fn get_active_uniform_block_parameter_i32(
+        &self,
+        program: NativeProgram,
+        index: u32,
+        pname: u32,
+    ) -> i32 {
+        self.gl
+            .get_active_uniform_block_parameter(&self.get::<WebGlProgram>(program.0), index, pname)
+            .unwrap()
+            .as_f64()
+            .unwrap_or(0.0) as i32
+    }
+
+    fn get_attrib_location(&self, program: NativeProgram, name: &str) -> i32 {
+        self.gl
+            .get_attrib_location(&self.get::<WebGlProgram>(program.0), name)
+    }
+
+    fn get_uniform_block_index(&self, program: NativeProgram, name: &str) -> u32 {
+        self.gl
+            .get_uniform_block_index(&self.get::<WebGlProgram>(program.0), name)
+    }
+
+    fn get_uniform_location(
+        &self,
+        program: NativeProgram,
+        name: &str,
+    ) -> Option<NativeUniformLocation> {
+        self.insert(
+            self.gl
+                .get_uniform_location(&self.get::<WebGlProgram>(program.0), name),
+        )
+        .map(NativeUniformLocation)
+    }
+
+    fn uniform_block_binding(&self, program: NativeProgram, index: u32, binding: u32) {
+        self.gl
+            .uniform_block_binding(&self.get::<WebGlProgram>(program.0), index, binding)
+    }
+
+    fn uniform1i(&self, location: Option<NativeUniformLocation>, value: i32) {
+        let location = location.map(|location| self.get::<WebGlUniformLocation>(location.0));
+        self.gl.uniform1i(location.as_ref(), value)
+    }
+
+    fn create_buffer(&self) -> Option<NativeBuffer> {
+        self.insert(self.gl.create_buffer()).map(NativeBuffer)
+    }
+
+    fn delete_buffer(&self, buffer: NativeBuffer) {
+        self.gl
+            .delete_buffer(Some(&self.remove::<WebGlBuffer>(buffer.0)))
+    }
+
+    fn bind_buffer(&self, target: u32, buffer: Option<NativeBuffer>) {
+        let buffer = buffer.map(|buffer| self.get::<WebGlBuffer>(buffer.0));
+        self.gl.bind_buffer(target, buffer.as_ref())
+    }
+
+    fn bind_buffer_range(
+        &self,
+        target: u32,
+        index: u32,
+        buffer: Option<NativeBuffer>,
+        offset: i32,
+        size: i32,
+    ) {
+        let buffer = buffer.map(|buffer| self.get::<WebGlBuffer>(buffer.0));
+        self.gl
+            .bind_buffer_range_with_i32_and_i32(target, index, buffer.as_ref(), offset, size)
+    }
+
+    fn buffer_data_size(&self, target: u32, size: i32, usage: u32) {
+        self.gl.buffer_data_with_i32(target, size, usage)
+    }
+
+    fn buffer_data_u8_slice(&self, target: u32, data: &[u8], usage: u32) {
+        self.gl.buffer_data_with_u8_array(target, data, usage)
+    }
+
+    fn buffer_sub_data_u8_slice(&self, target: u32, offset: i32, data: &[u8]) {
+        self.gl
+            .buffer_sub_data_with_i32_and_u8_array_and_src_offset_and_length(
+                target,
+                offset,
+                data,
+                0,
+                data.len() as u32,
+            )
+    }
+
+    fn get_buffer_sub_data(&self, target: u32, offset: i32, data: &mut [u8]) {
+        self.gl
+            .get_buffer_sub_data_with_i32_and_u8_array(target, offset, data)
+    }
+
+    fn copy_buffer_sub_data(
+        &self,
+        read_target: u32,
+        write_target: u32,
+        read_offset: i32,
+        write_offset: i32,
+        size: i32,
+    ) {
+        self.gl.copy_buffer_sub_data_with_i32_and_i32_and_i32(
+            read_target,
+            write_target,
+            read_offset,
+            write_offset,
+            size,
+        )
+    }
+
+    fn create_texture(&self) -> Option<NativeTexture> {
+        self.insert(self.gl.create_texture()).map(NativeTexture)
+    }
+
+    fn delete_texture(&self, texture: NativeTexture) {
+        self.gl
+            .delete_texture(Some(&self.remove::<WebGlTexture>(texture.0)))
+    }
+
+    fn active_texture(&self, unit: u32) {
+        self.gl.active_texture(unit)
+    }
+
+    fn bind_texture(&self, target: u32, texture: Option<NativeTexture>) {
+        let texture = texture.map(|texture| self.get::<WebGlTexture>(texture.0));
+        self.gl.bind_texture(target, texture.as_ref())
+    }
+
+    fn tex_parameteri(&self, target: u32, pname: u32, param: i32) {
+        self.gl.tex_parameteri(target, pname, param)
+    }
+
+    fn pixel_storei(&self, pname: u32, param: i32) {
+        self.gl.pixel_storei(pname, param)
+    }
+
+    fn tex_image_2d(
+        &self,
+        target: u32,
+        level: i32,
+        internal_format: i32,
+        width: i32,
+        height: i32,
+        border: i32,
+        format: u32,
+        type_: u32,
+        pixels: Option<&[u8]>,
+    ) {
+        match pixels {
+            Some(pixels) => self.gl
+                .tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_array_buffer_view_and_src_offset(
+                    target,
+                    level,
+                    internal_format,
+                    width,
+                    height,
+                    border,
+                    format,
+                    type_,
+                    &pixels_view(type_, pixels),
+                    0,
+                ),
+            None => self.gl
+                .tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
+                    target,
+                    level,
+                    internal_format,
+                    width,
+                    height,
+                    border,
+                    format,
+                    type_,
+                    None,
+                ),
+        }
+        .expect("tex image");
+    }
+
+    fn tex_image_2d_with_pbo_offset(
+        &self,
+        target: u32,
+        level: i32,
+        internal_format: i32,
+        width: i32,
+        height: i32,
+        border: i32,
+        format: u32,
+        type_: u32,
+        offset: i32,
+    ) {
+        self.gl
+            .tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_f64(
+                target,
+                level,
+                internal_format,
+                width,
+                height,
+                border,
+                format,
+                type_,
+                offset as f64,
+            )
+            .expect("tex image");
+    }
+
+    fn compressed_tex_image_2d(
+        &self,
+        target: u32,
+        level: i32,
+        internal_format: u32,
+        width: i32,
+        height: i32,
+        border: i32,
+        data: &[u8],
+    ) {
+        self.gl.compressed_tex_image_2d_with_u8_array(
+            target,
+            level,
+            internal_format,
+            width,
+            height,
+            border,
+            data,
+        )
+    }
+
+    fn compressed_tex_image_2d_with_pbo_offset(
+        &self,
+        target: u32,
+        level: i32,
+        internal_format: u32,
+        width: i32,
+        height: i32,
+        border: i32,
+        image_size: i32,
+        offset: i32,
+    ) {
+        self.gl.compressed_tex_image_2d_with_i32_and_i32(
+            target,
+            level,
+            internal_format,
+            width,
+            height,
+            border,
+            image_size,
+            offset,
+        )
+    }
+
+    fn tex_storage_2d(
+        &self,
+        target: u32,
+        levels: i32,
+        internal_format: u32,
+        width: i32,
+        height: i32,
+    ) {
+        self.gl
+            .tex_storage_2d(target, levels, internal_format, width, height)
+    }
+
+    fn tex_sub_image_2d(
+        &self,
+        target: u32,
+        level: i32,
+        x_offset: i32,
+        y_offset: i32,
+        width: i32,
+        height: i32,
+        format: u32,
+        type_: u32,
+        pixels: &[u8],
+    ) {
+        self.gl
+            .tex_sub_image_2d_with_i32_and_i32_and_u32_and_type_and_array_buffer_view_and_src_offset(
+                target,
+                level,
+                x_offset,
+                y_offset,
+                width,
+                height,
+                format,
+                type_,
+                &pixels_view(type_, pixels),
+                0,
+            )
+            .expect("tex sub image");
+    }
+
+    fn tex_sub_image_2d_with_pbo_offset(
+        &self,
+        target: u32,
+        level: i32,
+        x_offset: i32,
+        y_offset: i32,
+        width: i32,
+        height: i32,
+        format: u32,
+        type_: u32,
+        offset: i32,
+    ) {
+        self.gl
+            .tex_sub_image_2d_with_i32_and_i32_and_u32_and_type_and_f64(
+                target,
+                level,
+                x_offset,
+                y_offset,
+                width,
+                height,
+                format,
+                type_,
+                offset as f64,
+            )
+            .expect("tex sub image");
+    }
+
+    fn copy_tex_sub_image_2d(
+        &self,
+        target: u32,
+        level: i32,
+        x_offset: i32,
+        y_offset: i32,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+    ) {
+        self.gl
+            .copy_tex_sub_image_2d(target, level, x_offset, y_offset, x, y, width, height)
+    }
+
+    fn tex_storage_3d(
+        &self,
+        target: u32,
+        levels: i32,
+        internal_format: u32,
+        width: i32,
+        height: i32,
+        depth: i32,
+    ) {
+        self.gl
+            .tex_storage_3d(target, levels, internal_format, width, height, depth)
+    }
+
+    fn tex_sub_image_3d(
+        &self,
+        target: u32,
+        level: i32,
+        x_offset: i32,
+        y_offset: i32,
+        z_offset: i32,
+        width: i32,
+        height: i32,
+        depth: i32,
+        format: u32,
+        type_: u32,
+        pixels: &[u8],
+    ) {
+        self.gl
+            .tex_sub_image_3d_with_opt_array_buffer_view(
+                target,
+                level,
+                x_offset,
+                y_offset,
+                z_offset,
+                width,
+                height,
+                depth,
+                format,
+                type_,
+                Some(&pixels_view(type_, pixels)),
+            )
+            .expect("tex sub image");
+    }
+
+    fn tex_sub_image_3d_with_pbo_offset(
+        &self,
+        target: u32,
+        level: i32,
+        x_offset: i32,
+        y_offset: i32,
+        z_offset: i32,
+        width: i32,
+        height: i32,
+        depth: i32,
+        format: u32,
+        type_: u32,
+        offset: i32,
+    ) {
+        self.gl
+            .tex_sub_image_3d_with_i32(
+                target, level, x_offset, y_offset, z_offset, width, height, depth, format, type_,
+                offset,
+            )
+            .expect("tex sub image");
+    }
+
+    fn copy_tex_sub_image_3d(
+        &self,
+        target: u32,
+        level: i32,
+        x_offset: i32,
+        y_offset: i32,
+        z_offset: i32,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+    ) {
+        self.gl.copy_tex_sub_image_3d(
+            target, level, x_offset, y_offset, z_offset, x, y, width, height,
+        )
+    }
+
+    fn generate_mipmap(&self, target: u32) {
+        self.gl.generate_mipmap(target)
+    }
+
+    fn create_sampler(&self) -> Option<NativeSampler> {
+        self.insert(self.gl.create_sampler()).map(NativeSampler)
+    }
+
+    fn delete_sampler(&self, sampler: NativeSampler) {
+        self.gl
+            .delete_sampler(Some(&self.remove::<WebGlSampler>(sampler.0)))
+    }
+
+    fn bind_sampler(&self, unit: u32, sampler: Option<NativeSampler>) {
+        let sampler = sampler.map(|sampler| self.get::<WebGlSampler>(sampler.0));
+        self.gl.bind_sampler(unit, sampler.as_ref())
+    }
+
+    fn sampler_parameteri(&self, sampler: NativeSampler, pname: u32, param: i32) {
+        self.gl
+            .sampler_parameteri(&self.get::<WebGlSampler>(sampler.0), pname, param)
+    }
+
+    fn sampler_parameterf(&self, sampler: NativeSampler, pname: u32, param: f32) {
+        self.gl
+            .sampler_parameterf(&self.get::<WebGlSampler>(sampler.0), pname, param)
+    }
+
+    fn create_framebuffer(&self) -> Option<NativeFramebuffer> {
+        self.insert(self.gl.create_framebuffer())
+            .map(NativeFramebuffer)
+    }
+
+    fn delete_framebuffer(&self, framebuffer: NativeFramebuffer) {
+        self.gl
+            .delete_framebuffer(Some(&self.remove::<WebGlFramebuffer>(framebuffer.0)))
+    }
+
+    fn bind_framebuffer(&self, target: u32, framebuffer: Option<NativeFramebuffer>) {
+        let framebuffer =
+            framebuffer.map(|framebuffer| self.get::<WebGlFramebuffer>(framebuffer.0));
+        self.gl.bind_framebuffer(target, framebuffer.as_ref())
+    }
+
+    fn framebuffer_texture_2d(
+        &self,
+        target: u32,
+        attachment: u32,
+        texture_target: u32,
+        texture: Option<NativeTexture>,
+        level: i32,
+    ) {
+        let texture = texture.map(|texture| self.get::<WebGlTexture>(texture.0));
+        self.gl
+            .framebuffer_texture_2d(target, attachment, texture_target, texture.as_ref(), level)
+    }
+
+    fn framebuffer_texture_layer(
+        &self,
+        target: u32,
+        attachment: u32,
+        texture: Option<NativeTexture>,
+        level: i32,
+        layer: i32,
+    ) {
+        let texture = texture.map(|texture| self.get::<WebGlTexture>(texture.0));
+        self.gl
+            .framebuffer_texture_layer(target, attachment, texture.as_ref(), level, layer)
+    }
+
+    fn check_framebuffer_status(&self, target: u32) -> u32 {
+        self.gl.check_framebuffer_status(target)
+    }
+
+    fn create_renderbuffer(&self) -> Option<NativeRenderbuffer> {
+        self.insert(self.gl.create_renderbuffer())
+            .map(NativeRenderbuffer)
+    }
+
+    fn delete_renderbuffer(&self, renderbuffer: NativeRenderbuffer) {
+        self.gl
+            .delete_renderbuffer(Some(&self.remove::<WebGlRenderbuffer>(renderbuffer.0)))
+    }
+
+    fn bind_renderbuffer(&self, target: u32, renderbuffer: Option<NativeRenderbuffer>) {
+        let renderbuffer =
+            renderbuffer.map(|renderbuffer| self.get::<WebGlRenderbuffer>(renderbuffer.0));
+        self.gl.bind_renderbuffer(target, renderbuffer.as_ref())
+    }
+
+    fn renderbuffer_storage_multisample(
+        &self,
+        target: u32,
+        samples: i32,
+        internal_format: u32,
+        width: i32,
+        height: i32,
+    ) {
+        self.gl
+            .renderbuffer_storage_multisample(target, samples, internal_format, width, height)
+    }
+
+    fn framebuffer_renderbuffer(
+        &self,
+        target: u32,
+        attachment: u32,
+        renderbuffer_target: u32,
+        renderbuffer: Option<NativeRenderbuffer>,
+    ) {
+        let renderbuffer =
+            renderbuffer.map(|renderbuffer| self.get::<WebGlRenderbuffer>(renderbuffer.0));
+        self.gl.framebuffer_renderbuffer(
+            target,
+            attachment,
+            renderbuffer_target,
+            renderbuffer.as_ref(),
+        )
+    }
+
+    fn draw_buffers(&self, buffers: &[u32]) {
+        let buffers = buffers
+            .iter()
+            .map(|x| JsValue::from_f64(*x as f64))
+            .collect::<js_sys::Array>();
+        self.gl.draw_buffers(&buffers)
+    }
+
+    fn read_buffer(&self, src: u32) {
+        self.gl.read_buffer(src)
+    }
+
+    fn blit_framebuffer(
+        &self,
+        src_x0: i32,
+        src_y0: i32,
+        src_x1: i32,
+        src_y1: i32,
+        dst_x0: i32,
+        dst_y0: i32,
+        dst_x1: i32,
+        dst_y1: i32,
+        mask: u32,
+        filter: u32,
+    ) {
+        self.gl.blit_framebuffer(
+            src_x0, src_y0, src_x1, src_y1, dst_x0, dst_y0, dst_x1, dst_y1, mask, filter,
+        )
+    }
+
+    fn read_pixels_with_pbo_offset(
+        &self,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        format: u32,
+        type_: u32,
+        offset: i32,
+    ) {
+        self.gl
+            .read_pixels_with_i32(x, y, width, height, format, type_, offset)
+            .expect("read pixels");
+    }
+
+    fn create_vertex_array(&self) -> Option<NativeVertexArray> {
+        self.insert(self.gl.create_vertex_array())
+            .map(NativeVertexArray)
+    }
+
+    fn bind_vertex_array(&self, vertex_array: Option<NativeVertexArray>) {
+        let vertex_array =
+            vertex_array.map(|vertex_array| self.get::<WebGlVertexArrayObject>(vertex_array.0));
+        self.gl.bind_vertex_array(vertex_array.as_ref())
+    }
+
+    fn enable_vertex_attrib_array(&self, index: u32) {
+        self.gl.enable_vertex_attrib_array(index)
+    }
+
+    fn vertex_attrib_pointer_f32(
+        &self,
+        index: u32,
+        size: i32,
+        type_: u32,
+        normalized: bool,
+        stride: i32,
+        offset: i32,
+    ) {
+        self.gl
+            .vertex_attrib_pointer_with_i32(index, size, type_, normalized, stride, offset)
+    }
+
+    fn vertex_attrib_pointer_i32(
+        &self,
+        index: u32,
+        size: i32,
+        type_: u32,
+        stride: i32,
+        offset: i32,
+    ) {
+        self.gl
+            .vertex_attrib_i_pointer_with_i32(index, size, type_, stride, offset)
+    }
+
+    fn vertex_attrib_divisor(&self, index: u32, divisor: u32) {
+        self.gl.vertex_attrib_divisor(index, divisor)
+    }
+
+    fn draw_arrays(&self, mode: u32, first: i32, count: i32) {
+        self.gl.draw_arrays(mode, first, count)
+    }
+
+    fn draw_arrays_instanced(&self, mode: u32, first: i32, count: i32, instance_count: i32) {
+        self.gl
+            .draw_arrays_instanced(mode, first, count, instance_count)
+    }
+
+    fn draw_elements_instanced(
+        &self,
+        mode: u32,
+        count: i32,
+        type_: u32,
+        offset: i32,
+        instance_count: i32,
+    ) {
+        self.gl
+            .draw_elements_instanced_with_i32(mode, count, type_, offset, instance_count)
+    }
+}
@@ -21,9 +21,15 @@ struct Rect {
     vec2 end;
 };
 
-layout(std140) uniform TextureAtlas_textures {  // set = 1, binding = 1
-    Rect[256] Textures;
-};
+// storage buffer emulated with data texture, one Rect per texel
+uniform highp usampler2D TextureAtlas_textures;  // set = 1, binding = 1, buffer
+
+Rect atlas_rect(uint rect_index) {
+    int width = textureSize(TextureAtlas_textures, 0).x;
+    ivec2 coord = ivec2(int(rect_index) % width, int(rect_index) / width);
+    vec4 rect = uintBitsToFloat(texelFetch(TextureAtlas_textures, coord, 0));
+    return Rect(rect.xy, rect.zw);
+}
 
 
 layout(std140) uniform Transform {  // set = 2, binding = 0
@@ -37,7 +43,7 @@ layout(std140) uniform TextureAtlasSprite { // set = 2, binding = 1
 };
 
 void main() {
-    Rect sprite_rect = Textures[index];
+    Rect sprite_rect = atlas_rect(index);
     vec2 sprite_dimensions = sprite_rect.end - sprite_rect.begin;
     vec3 vertex_position = vec3(Vertex_Position.xy * sprite_dimensions, 0.0);
 
@@ -0,0 +1,85 @@
+#version 300 es
+
+layout(location = 0) in vec3 Vertex_Position;
+layout(location = 1) in vec3 Vertex_Normal;
+layout(location = 2) in vec2 Vertex_Uv;
+
+// per-instance data, packed from Transform and TextureAtlasSprite uniform blocks
+layout(location = 3) in mat4 I_SpriteTransform;  // locations 3..6
+layout(location = 7) in vec4 I_Color;
+layout(location = 8) in uint I_Index;
+layout(location = 9) in uint I_Flip;
+
+out vec2 v_Uv;
+out vec4 v_Color;
+
+layout(std140) uniform CameraViewProj {
+    mat4 ViewProj;
+};
+
+// TODO: merge dimensions into "sprites" buffer when that is supported in the Uniforms derive abstraction
+layout(std140) uniform TextureAtlas_size {  // set = 1, binding = 0
+    vec2 AtlasSize;
+};
+
+struct Rect {
+    vec2 begin;
+    vec2 end;
+};
+
+// storage buffer emulated with data texture, one Rect per texel
+uniform highp usampler2D TextureAtlas_textures;  // set = 1, binding = 1, buffer
+
+Rect atlas_rect(uint rect_index) {
+    int width = textureSize(TextureAtlas_textures, 0).x;
+    ivec2 coord = ivec2(int(rect_index) % width, int(rect_index) / width);
+    vec4 rect = uintBitsToFloat(texelFetch(TextureAtlas_textures, coord, 0));
+    return Rect(rect.xy, rect.zw);
+}
+
+
+void main() {
+    Rect sprite_rect = atlas_rect(I_Index);
+    vec2 sprite_dimensions = sprite_rect.end - sprite_rect.begin;
+    vec3 vertex_position = vec3(Vertex_Position.xy * sprite_dimensions, 0.0);
+
+    // Specify the corners of the sprite
+    vec2 bottom_left = vec2(sprite_rect.begin.x, sprite_rect.end.y);
+    vec2 top_left = sprite_rect.begin;
+    vec2 top_right = vec2(sprite_rect.end.x, sprite_rect.begin.y);
+    vec2 bottom_right = sprite_rect.end;
+
+    // Flip the sprite if necessary
+    uint x_flip_bit = 1u;
+    uint y_flip_bit = 2u;
+
+    vec2 tmp;
+    if ((I_Flip & x_flip_bit) == x_flip_bit) {
+        // Shuffle the corners to flip around x
+        tmp = bottom_left;
+        bottom_left = bottom_right;
+        bottom_right = tmp;
+        tmp = top_left;
+        top_left = top_right;
+        top_right = tmp;
+    }
+    if ((I_Flip & y_flip_bit) == y_flip_bit) {
+        // Shuffle the corners to flip around y
+        tmp = bottom_left;
+        bottom_left = top_left;
+        top_left = tmp;
+        tmp = bottom_right;
+        bottom_right = top_right;
+        top_right = tmp;
+    }
+
+    vec2 atlas_positions[4] = vec2[](
+        bottom_left,
+        top_left,
+        top_right,
+        bottom_right
+    );
+    v_Uv = atlas_positions[gl_VertexID] / AtlasSize;
+    v_Color = I_Color;
+    gl_Position = ViewProj * I_SpriteTransform * vec4(ceil(vertex_position), 1.0);
+}
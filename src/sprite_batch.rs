@@ -0,0 +1,166 @@
+use crate::renderer::{Gl, NativeBuffer, NativeProgram, NativeVertexArray};
+use crate::{SPRITE_PIPELINE_HANDLE, SPRITE_SHEET_PIPELINE_HANDLE};
+use bevy::asset::Handle;
+use bevy::render::{
+    pipeline::{IndexFormat, PipelineDescriptor},
+    renderer::{BindGroupId, BufferId},
+};
+use bevy::utils::HashMap;
+use std::ops::Range;
+
+const SPRITE_BATCH_VERT: &str = include_str!("shaders/sprite_batch.vert");
+const SPRITE_SHEET_BATCH_VERT: &str = include_str!("shaders/sprite_sheet_batch.vert");
+
+/// std140 size of `Transform` uniform block
+const TRANSFORM_BLOCK_SIZE: usize = 64;
+
+/// Index of bind group with per-sprite `Transform` and `Sprite` / `TextureAtlasSprite` uniforms
+pub const SPRITE_BIND_GROUP_INDEX: u32 = 2;
+
+/// Per-instance vertex attribute of batched sprites
+pub struct InstanceAttribute {
+    pub location: u32,
+    pub nr_of_components: i32,
+    pub format: u32,
+    pub offset: i32,
+}
+
+macro_rules! instance_attributes {
+    ($( ($location:expr, $nr_of_components:expr, $format:expr, $offset:expr) ),* $(,)?) => {
+        &[$(
+            InstanceAttribute {
+                location: $location,
+                nr_of_components: $nr_of_components,
+                format: $format,
+                offset: $offset,
+            }
+        ),*]
+    };
+}
+
+// mat4 is passed as four vec4 attributes
+const SPRITE_INSTANCE_ATTRIBUTES: &[InstanceAttribute] = instance_attributes!(
+    (3, 4, Gl::FLOAT, 0),
+    (4, 4, Gl::FLOAT, 16),
+    (5, 4, Gl::FLOAT, 32),
+    (6, 4, Gl::FLOAT, 48),
+    (7, 2, Gl::FLOAT, 64),
+    (8, 1, Gl::UNSIGNED_INT, 72),
+);
+
+const SPRITE_SHEET_INSTANCE_ATTRIBUTES: &[InstanceAttribute] = instance_attributes!(
+    (3, 4, Gl::FLOAT, 0),
+    (4, 4, Gl::FLOAT, 16),
+    (5, 4, Gl::FLOAT, 32),
+    (6, 4, Gl::FLOAT, 48),
+    (7, 4, Gl::FLOAT, 64),
+    (8, 1, Gl::UNSIGNED_INT, 80),
+    (9, 1, Gl::UNSIGNED_INT, 84),
+);
+
+/// Sprite pipelines which can be batched.
+///
+/// Instance data of a batched sprite is `Transform` uniform block followed by
+/// `Sprite` (or `TextureAtlasSprite`) uniform block, both from bind group 2.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpriteBatchKind {
+    Sprite,
+    SpriteSheet,
+}
+
+/// Shader source without its `#version` line
+fn shader_body(source: &str) -> &str {
+    let source = source.trim_start();
+    &source[source.find('\n').unwrap()..]
+}
+
+impl SpriteBatchKind {
+    pub const ALL: [SpriteBatchKind; 2] = [SpriteBatchKind::Sprite, SpriteBatchKind::SpriteSheet];
+
+    /// Pipeline whose specialized (compiled) variants are batched
+    pub fn pipeline_handle(&self) -> Handle<PipelineDescriptor> {
+        match self {
+            SpriteBatchKind::Sprite => SPRITE_PIPELINE_HANDLE.typed(),
+            SpriteBatchKind::SpriteSheet => SPRITE_SHEET_PIPELINE_HANDLE.typed(),
+        }
+    }
+
+    /// Vertex shader of batched variant of the pipeline, with the same `#version`
+    /// and `#define`s as specialized vertex shader `source`
+    pub fn batch_vertex_shader(&self, source: &str) -> String {
+        let header: String = source
+            .trim_start()
+            .lines()
+            .take_while(|line| line.starts_with("#version") || line.starts_with("#define"))
+            .map(|line| format!("{}\n", line))
+            .collect();
+        let batch_vert = match self {
+            SpriteBatchKind::Sprite => SPRITE_BATCH_VERT,
+            SpriteBatchKind::SpriteSheet => SPRITE_SHEET_BATCH_VERT,
+        };
+        format!("{}{}", header, shader_body(batch_vert))
+    }
+
+    /// std140 size of `Sprite` / `TextureAtlasSprite` uniform block
+    pub fn sprite_block_size(&self) -> usize {
+        match self {
+            SpriteBatchKind::Sprite => 16,
+            SpriteBatchKind::SpriteSheet => 32,
+        }
+    }
+
+    pub fn instance_stride(&self) -> usize {
+        TRANSFORM_BLOCK_SIZE + self.sprite_block_size()
+    }
+
+    pub fn instance_attributes(&self) -> &'static [InstanceAttribute] {
+        match self {
+            SpriteBatchKind::Sprite => SPRITE_INSTANCE_ATTRIBUTES,
+            SpriteBatchKind::SpriteSheet => SPRITE_SHEET_INSTANCE_ATTRIBUTES,
+        }
+    }
+
+    /// Packs instance data of a sprite from `Transform` and sprite uniform blocks
+    pub fn write_instance(&self, instance: &mut Vec<u8>, transform: &[u8], sprite: &[u8]) {
+        instance.clear();
+        instance.extend_from_slice(transform);
+        instance.resize(TRANSFORM_BLOCK_SIZE, 0);
+        instance.extend_from_slice(sprite);
+        instance.resize(self.instance_stride(), 0);
+    }
+}
+
+/// GL objects used to draw batches of sprite pipeline
+pub struct GlSpriteBatchPipeline {
+    pub kind: SpriteBatchKind,
+    pub program: NativeProgram,
+    pub vao: NativeVertexArray,
+    pub instance_buffer: NativeBuffer,
+}
+
+/// State shared by all sprites of a batch
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpriteBatchKey {
+    pub pipeline: Handle<PipelineDescriptor>,
+    pub vertex_buffers: Vec<Option<(BufferId, u64)>>,
+    pub index_buffer: Option<BufferId>,
+    pub index_buffer_offset: u64,
+    pub index_format: IndexFormat,
+    pub indices: Range<u32>,
+    pub base_vertex: i32,
+}
+
+/// Sprites collected by render pass, drawn with a single instanced draw call
+/// when any state they depend on changes (or at the end of the pass)
+#[derive(Default)]
+pub struct SpriteBatch {
+    pub key: Option<SpriteBatchKey>,
+    pub instances: Vec<u8>,
+    pub instance_count: u32,
+    /// instance data of the sprite from the last bind group 2
+    pub instance: Option<Vec<u8>>,
+    /// bind group 2 of sprite pipeline, bound only if the sprite can't be batched
+    pub deferred_bind_group: Option<(BindGroupId, Option<Vec<u32>>)>,
+    /// bind groups (with dynamic offsets) bound to GL
+    pub bind_groups: HashMap<u32, (BindGroupId, Option<Vec<u32>>)>,
+}
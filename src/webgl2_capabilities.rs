@@ -0,0 +1,78 @@
+use crate::renderer::{Gl, GlBackend};
+use crate::WebGL2Limits;
+
+/// `WEBGL_debug_renderer_info` extension
+const DEBUG_RENDERER_INFO: &str = "WEBGL_debug_renderer_info";
+/// `UNMASKED_VENDOR_WEBGL` of `WEBGL_debug_renderer_info`
+const UNMASKED_VENDOR_WEBGL: u32 = 0x9245;
+/// `UNMASKED_RENDERER_WEBGL` of `WEBGL_debug_renderer_info`
+const UNMASKED_RENDERER_WEBGL: u32 = 0x9246;
+/// Extension required to render to floating point textures
+const COLOR_BUFFER_FLOAT: &str = "EXT_color_buffer_float";
+/// Extension required to render to half float textures
+const COLOR_BUFFER_HALF_FLOAT: &str = "EXT_color_buffer_half_float";
+/// Extension required to set blend state and color mask per draw buffer
+const DRAW_BUFFERS_INDEXED: &str = "OES_draw_buffers_indexed";
+
+/// What the WebGL2 context supports, inserted as a resource when the context is created
+/// (can be used to pick quality settings at startup).
+#[derive(Debug, Clone, Default)]
+pub struct WebGL2Capabilities {
+    /// Supported extensions (WebGL names)
+    pub extensions: Vec<String>,
+    pub limits: WebGL2Limits,
+    /// `VERSION`
+    pub version: String,
+    /// `SHADING_LANGUAGE_VERSION`
+    pub shading_language_version: String,
+    /// Unmasked vendor (via `WEBGL_debug_renderer_info`) if available, `VENDOR` otherwise
+    pub vendor: String,
+    /// Unmasked renderer (via `WEBGL_debug_renderer_info`) if available, `RENDERER` otherwise
+    pub renderer: String,
+    /// Floating point (`R32F`, `RGBA32F`, ...) color attachments are supported
+    pub float_render_target: bool,
+    /// Half float (`R16F`, `RGBA16F`, ...) color attachments are supported
+    pub half_float_render_target: bool,
+    /// Color attachments can have different blend states and write masks
+    pub draw_buffers_indexed: bool,
+}
+
+impl WebGL2Capabilities {
+    /// Queries capabilities of `gl`, enabling extensions needed for float render targets
+    pub fn query(gl: &dyn GlBackend) -> Self {
+        let extensions = gl.get_supported_extensions();
+        let supports = |name: &str| extensions.iter().any(|extension| extension == name);
+        let string = |pname| gl.get_parameter_string(pname).unwrap_or_default();
+        let (vendor, renderer) =
+            if supports(DEBUG_RENDERER_INFO) && gl.enable_extension(DEBUG_RENDERER_INFO) {
+                (
+                    string(UNMASKED_VENDOR_WEBGL),
+                    string(UNMASKED_RENDERER_WEBGL),
+                )
+            } else {
+                (string(Gl::VENDOR), string(Gl::RENDERER))
+            };
+        let float_render_target =
+            supports(COLOR_BUFFER_FLOAT) && gl.enable_extension(COLOR_BUFFER_FLOAT);
+        // EXT_color_buffer_float covers half floats as well
+        let half_float_render_target = float_render_target
+            || (supports(COLOR_BUFFER_HALF_FLOAT) && gl.enable_extension(COLOR_BUFFER_HALF_FLOAT));
+        let draw_buffers_indexed =
+            supports(DRAW_BUFFERS_INDEXED) && gl.enable_extension(DRAW_BUFFERS_INDEXED);
+        Self {
+            limits: WebGL2Limits::query(gl),
+            version: string(Gl::VERSION),
+            shading_language_version: string(Gl::SHADING_LANGUAGE_VERSION),
+            vendor,
+            renderer,
+            float_render_target,
+            half_float_render_target,
+            draw_buffers_indexed,
+            extensions,
+        }
+    }
+
+    pub fn supports_extension(&self, name: &str) -> bool {
+        self.extensions.iter().any(|extension| extension == name)
+    }
+}
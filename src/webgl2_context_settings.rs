@@ -0,0 +1,81 @@
+use crate::renderer::js_sys;
+use wasm_bindgen::JsValue;
+
+/// `powerPreference` context creation attribute
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebGL2PowerPreference {
+    Default,
+    HighPerformance,
+    LowPower,
+}
+
+impl WebGL2PowerPreference {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebGL2PowerPreference::Default => "default",
+            WebGL2PowerPreference::HighPerformance => "high-performance",
+            WebGL2PowerPreference::LowPower => "low-power",
+        }
+    }
+}
+
+/// WebGL2 context creation attributes, read by the plugin when the window is created
+/// (insert the resource before adding the plugin to change them).
+///
+/// `Default` values are the same as browser defaults. They are ignored by native backend.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebGL2ContextSettings {
+    /// Drawing buffer has alpha channel (needed for transparent canvas)
+    pub alpha: bool,
+    pub antialias: bool,
+    /// Drawing buffer has depth buffer
+    pub depth: bool,
+    /// Drawing buffer has stencil buffer
+    pub stencil: bool,
+    /// Page compositor assumes colors of drawing buffer have premultiplied alpha
+    pub premultiplied_alpha: bool,
+    /// Drawing buffer keeps its contents until cleared (e.g. to read canvas for screenshots)
+    pub preserve_drawing_buffer: bool,
+    pub power_preference: WebGL2PowerPreference,
+    /// Context creation fails on low performance (e.g. software) implementations
+    pub fail_if_major_performance_caveat: bool,
+}
+
+impl Default for WebGL2ContextSettings {
+    fn default() -> Self {
+        Self {
+            alpha: true,
+            antialias: true,
+            depth: true,
+            stencil: false,
+            premultiplied_alpha: true,
+            preserve_drawing_buffer: false,
+            power_preference: WebGL2PowerPreference::Default,
+            fail_if_major_performance_caveat: false,
+        }
+    }
+}
+
+impl WebGL2ContextSettings {
+    /// `WebGLContextAttributes` dictionary passed to `getContext`
+    pub fn context_attributes(&self) -> js_sys::Object {
+        let attributes = js_sys::Object::new();
+        let values: [(&str, JsValue); 8] = [
+            ("alpha", self.alpha.into()),
+            ("antialias", self.antialias.into()),
+            ("depth", self.depth.into()),
+            ("stencil", self.stencil.into()),
+            ("premultipliedAlpha", self.premultiplied_alpha.into()),
+            ("preserveDrawingBuffer", self.preserve_drawing_buffer.into()),
+            ("powerPreference", self.power_preference.as_str().into()),
+            (
+                "failIfMajorPerformanceCaveat",
+                self.fail_if_major_performance_caveat.into(),
+            ),
+        ];
+        for (name, value) in values.iter() {
+            js_sys::Reflect::set(&attributes, &(*name).into(), value).unwrap();
+        }
+        attributes
+    }
+}
@@ -0,0 +1,136 @@
+use crate::renderer::{Gl, GlBackend};
+
+/// Limits of WebGL2 context, queried at initialization and inserted as a resource.
+///
+/// `Default` values are minimums guaranteed by WebGL2, used until the context is created.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WebGL2Limits {
+    /// `UNIFORM_BUFFER_OFFSET_ALIGNMENT`, alignment of (dynamic) uniform buffer offsets
+    pub uniform_buffer_offset_alignment: usize,
+    /// `MAX_UNIFORM_BLOCK_SIZE`
+    pub max_uniform_block_size: usize,
+    /// `MAX_UNIFORM_BUFFER_BINDINGS`
+    pub max_uniform_buffer_bindings: u32,
+    /// `MAX_VERTEX_UNIFORM_BLOCKS`
+    pub max_vertex_uniform_blocks: u32,
+    /// `MAX_FRAGMENT_UNIFORM_BLOCKS`
+    pub max_fragment_uniform_blocks: u32,
+    /// `MAX_COMBINED_TEXTURE_IMAGE_UNITS`
+    pub max_texture_units: u32,
+    /// `MAX_VERTEX_TEXTURE_IMAGE_UNITS`
+    pub max_vertex_texture_units: u32,
+    /// `MAX_TEXTURE_IMAGE_UNITS` (fragment shader)
+    pub max_fragment_texture_units: u32,
+    /// `MAX_VERTEX_ATTRIBS`
+    pub max_vertex_attribs: u32,
+    /// `MAX_TEXTURE_SIZE`
+    pub max_texture_size: usize,
+    /// `MAX_CUBE_MAP_TEXTURE_SIZE`
+    pub max_cube_map_texture_size: usize,
+    /// `MAX_3D_TEXTURE_SIZE`
+    pub max_3d_texture_size: usize,
+    /// `MAX_ARRAY_TEXTURE_LAYERS`
+    pub max_array_texture_layers: usize,
+    /// `MAX_RENDERBUFFER_SIZE`
+    pub max_renderbuffer_size: usize,
+    /// `MAX_COLOR_ATTACHMENTS`
+    pub max_color_attachments: u32,
+    /// `MAX_DRAW_BUFFERS`
+    pub max_draw_buffers: u32,
+    /// `MAX_SAMPLES`
+    pub max_samples: u32,
+}
+
+impl Default for WebGL2Limits {
+    fn default() -> Self {
+        Self {
+            uniform_buffer_offset_alignment: 256,
+            max_uniform_block_size: 16384,
+            max_uniform_buffer_bindings: 24,
+            max_vertex_uniform_blocks: 12,
+            max_fragment_uniform_blocks: 12,
+            max_texture_units: 32,
+            max_vertex_texture_units: 16,
+            max_fragment_texture_units: 16,
+            max_vertex_attribs: 16,
+            max_texture_size: 2048,
+            max_cube_map_texture_size: 2048,
+            max_3d_texture_size: 256,
+            max_array_texture_layers: 256,
+            max_renderbuffer_size: 2048,
+            max_color_attachments: 4,
+            max_draw_buffers: 4,
+            max_samples: 4,
+        }
+    }
+}
+
+impl WebGL2Limits {
+    /// Queries limits of `gl`, keeping default values for ones it doesn't report
+    pub fn query(gl: &dyn GlBackend) -> Self {
+        let default = Self::default();
+        let param = |pname, default: usize| match gl.get_parameter_i32(pname) {
+            value if value > 0 => value as usize,
+            _ => default,
+        };
+        let param_u32 = |pname, default: u32| param(pname, default as usize) as u32;
+        Self {
+            uniform_buffer_offset_alignment: param(
+                Gl::UNIFORM_BUFFER_OFFSET_ALIGNMENT,
+                default.uniform_buffer_offset_alignment,
+            ),
+            max_uniform_block_size: param(
+                Gl::MAX_UNIFORM_BLOCK_SIZE,
+                default.max_uniform_block_size,
+            ),
+            max_uniform_buffer_bindings: param_u32(
+                Gl::MAX_UNIFORM_BUFFER_BINDINGS,
+                default.max_uniform_buffer_bindings,
+            ),
+            max_vertex_uniform_blocks: param_u32(
+                Gl::MAX_VERTEX_UNIFORM_BLOCKS,
+                default.max_vertex_uniform_blocks,
+            ),
+            max_fragment_uniform_blocks: param_u32(
+                Gl::MAX_FRAGMENT_UNIFORM_BLOCKS,
+                default.max_fragment_uniform_blocks,
+            ),
+            max_texture_units: param_u32(
+                Gl::MAX_COMBINED_TEXTURE_IMAGE_UNITS,
+                default.max_texture_units,
+            ),
+            max_vertex_texture_units: param_u32(
+                Gl::MAX_VERTEX_TEXTURE_IMAGE_UNITS,
+                default.max_vertex_texture_units,
+            ),
+            max_fragment_texture_units: param_u32(
+                Gl::MAX_TEXTURE_IMAGE_UNITS,
+                default.max_fragment_texture_units,
+            ),
+            max_vertex_attribs: param_u32(Gl::MAX_VERTEX_ATTRIBS, default.max_vertex_attribs),
+            max_texture_size: param(Gl::MAX_TEXTURE_SIZE, default.max_texture_size),
+            max_cube_map_texture_size: param(
+                Gl::MAX_CUBE_MAP_TEXTURE_SIZE,
+                default.max_cube_map_texture_size,
+            ),
+            max_3d_texture_size: param(Gl::MAX_3D_TEXTURE_SIZE, default.max_3d_texture_size),
+            max_array_texture_layers: param(
+                Gl::MAX_ARRAY_TEXTURE_LAYERS,
+                default.max_array_texture_layers,
+            ),
+            max_renderbuffer_size: param(Gl::MAX_RENDERBUFFER_SIZE, default.max_renderbuffer_size),
+            max_color_attachments: param_u32(
+                Gl::MAX_COLOR_ATTACHMENTS,
+                default.max_color_attachments,
+            ),
+            max_draw_buffers: param_u32(Gl::MAX_DRAW_BUFFERS, default.max_draw_buffers),
+            max_samples: param_u32(Gl::MAX_SAMPLES, default.max_samples),
+        }
+    }
+
+    /// Rounds `size` up to multiple of `uniform_buffer_offset_alignment`
+    pub fn align_uniform_size(&self, size: usize) -> usize {
+        let alignment = self.uniform_buffer_offset_alignment;
+        (size + alignment - 1) / alignment * alignment
+    }
+}
@@ -1,22 +1,174 @@
-use crate::{gl_call, renderer::*, Buffer, ScissorsState};
+use crate::{
+    converters::*, gl_call, renderer::*, Buffer, GlBufferInfo, GlVertexBufferDescripror,
+    ScissorsState, SpriteBatch, SpriteBatchKey, SpriteBatchKind, WebGL2RenderResourceBinding,
+    SPRITE_BIND_GROUP_INDEX,
+};
+use bevy::asset::Handle;
+use bevy::log::prelude::*;
 use bevy::render::{
     pass::RenderPass,
     pipeline::{
-        BindGroupDescriptorId, BlendFactor, BlendOperation, Face, FrontFace, IndexFormat,
-        PipelineDescriptor,
+        BindGroupDescriptorId, ColorTargetState, ColorWrite, CompareFunction, Face, FrontFace,
+        IndexFormat, InputStepMode, PipelineDescriptor, StencilFaceState, StencilOperation,
+        StencilState,
     },
-    renderer::{BindGroupId, BufferId, BufferUsage, RenderContext},
+    renderer::{BindGroupId, BufferId, RenderContext},
 };
-use bevy::{asset::Handle, render::pipeline::PrimitiveTopology};
+use bevy::utils::HashMap;
 use std::ops::Range;
 
 pub struct WebGL2RenderPass<'a> {
     pub render_context: &'a WebGL2RenderContext,
     pub pipeline: Option<Handle<PipelineDescriptor>>,
+    pub sprite_batch: SpriteBatch,
+    /// reference value for stencil tests, set with `set_stencil_reference`
+    pub stencil_reference: u32,
+}
+
+/// Stencil test is needed unless both faces always pass and keep stencil values
+fn is_stencil_enabled(stencil: &StencilState) -> bool {
+    let is_ignored = |face: &StencilFaceState| {
+        matches!(face.compare, CompareFunction::Always)
+            && matches!(face.fail_op, StencilOperation::Keep)
+            && matches!(face.depth_fail_op, StencilOperation::Keep)
+            && matches!(face.pass_op, StencilOperation::Keep)
+    };
+    !is_ignored(&stencil.front) || !is_ignored(&stencil.back)
+}
+
+/// Sets stencil functions of both faces, which depend on pipeline state and stencil reference
+fn set_stencil_func(gl: &dyn GlBackend, stencil: &StencilState, reference: u32) {
+    for (face, state) in [(Gl::FRONT, &stencil.front), (Gl::BACK, &stencil.back)].iter() {
+        gl_call!(gl.stencil_func_separate(
+            *face,
+            state.compare.webgl2_into(),
+            reference as i32,
+            stencil.read_mask,
+        ));
+    }
+}
+
+/// Sets blend state and color write mask of all draw buffers
+fn set_color_target_state(gl: &dyn GlBackend, state: &ColorTargetState) {
+    if let Some(blend) = &state.blend {
+        gl_call!(gl.enable(Gl::BLEND));
+        gl_call!(gl.blend_func_separate(
+            blend.color.src_factor.webgl2_into(),
+            blend.color.dst_factor.webgl2_into(),
+            blend.alpha.src_factor.webgl2_into(),
+            blend.alpha.dst_factor.webgl2_into(),
+        ));
+        gl_call!(gl.blend_equation_separate(
+            blend.color.operation.webgl2_into(),
+            blend.alpha.operation.webgl2_into(),
+        ));
+    } else {
+        gl_call!(gl.disable(Gl::BLEND));
+    }
+    let mask = state.write_mask;
+    gl_call!(gl.color_mask(
+        mask.contains(ColorWrite::RED),
+        mask.contains(ColorWrite::GREEN),
+        mask.contains(ColorWrite::BLUE),
+        mask.contains(ColorWrite::ALPHA),
+    ));
+}
+
+/// Sets blend state and color write mask of draw buffer `index` (`OES_draw_buffers_indexed`)
+fn set_color_target_state_indexed(gl: &dyn GlBackend, index: u32, state: &ColorTargetState) {
+    if let Some(blend) = &state.blend {
+        gl_call!(gl.enable_i(Gl::BLEND, index));
+        gl_call!(gl.blend_func_separate_i(
+            index,
+            blend.color.src_factor.webgl2_into(),
+            blend.color.dst_factor.webgl2_into(),
+            blend.alpha.src_factor.webgl2_into(),
+            blend.alpha.dst_factor.webgl2_into(),
+        ));
+        gl_call!(gl.blend_equation_separate_i(
+            index,
+            blend.color.operation.webgl2_into(),
+            blend.alpha.operation.webgl2_into(),
+        ));
+    } else {
+        gl_call!(gl.disable_i(Gl::BLEND, index));
+    }
+    let mask = state.write_mask;
+    gl_call!(gl.color_mask_i(
+        index,
+        mask.contains(ColorWrite::RED),
+        mask.contains(ColorWrite::GREEN),
+        mask.contains(ColorWrite::BLUE),
+        mask.contains(ColorWrite::ALPHA),
+    ));
+}
+
+/// Binds vertex buffers and sets attribute pointers of currently bound VAO.
+/// WebGL2 has no native support for base vertex and first instance, so they are
+/// emulated by offsetting attribute pointers of per-vertex buffers by
+/// `base_vertex * stride` and of per-instance buffers by `first_instance * stride`.
+fn set_vertex_attributes(
+    gl: &dyn GlBackend,
+    vertex_buffer_descriptors: &[GlVertexBufferDescripror],
+    vertex_buffers: &[Option<(BufferId, u64)>],
+    buffers: &HashMap<BufferId, GlBufferInfo>,
+    base_vertex: i32,
+    first_instance: u32,
+) {
+    for (slot, vertex_buffer_descriptor) in vertex_buffer_descriptors.iter().enumerate() {
+        let (buffer_id, buffer_offset) = match vertex_buffers.get(slot) {
+            Some(Some((buffer_id, buffer_offset))) => (buffer_id, *buffer_offset as i32),
+            _ => {
+                warn!(
+                    "no vertex buffer bound to slot {} ({:?})",
+                    slot, vertex_buffer_descriptor.name
+                );
+                continue;
+            }
+        };
+        let buffer = buffers.get(buffer_id).unwrap();
+        if let Buffer::WebGlBuffer(buffer_id) = &buffer.buffer {
+            gl_call!(gl.bind_buffer(Gl::ARRAY_BUFFER, Some(*buffer_id)));
+        } else {
+            panic!("binding in-memory buffer");
+        }
+        let stride = vertex_buffer_descriptor.stride;
+        let (divisor, base_offset) = match vertex_buffer_descriptor.step_mode {
+            InputStepMode::Vertex => (0, buffer_offset + base_vertex * stride),
+            InputStepMode::Instance => (1, buffer_offset + first_instance as i32 * stride),
+        };
+        for attr_descr in vertex_buffer_descriptor.attributes.iter() {
+            if attr_descr.attrib_location >= 0 {
+                gl_call!(gl.enable_vertex_attrib_array(attr_descr.attrib_location as u32));
+                if attr_descr.format.format == Gl::FLOAT
+                    || attr_descr.format.format == Gl::HALF_FLOAT
+                {
+                    gl_call!(gl.vertex_attrib_pointer_f32(
+                        attr_descr.attrib_location as u32,
+                        attr_descr.format.nr_of_components,
+                        attr_descr.format.format,
+                        attr_descr.format.normalized,
+                        stride,
+                        base_offset + attr_descr.offset,
+                    ));
+                } else {
+                    gl_call!(gl.vertex_attrib_pointer_i32(
+                        attr_descr.attrib_location as u32,
+                        attr_descr.format.nr_of_components,
+                        attr_descr.format.format,
+                        stride,
+                        base_offset + attr_descr.offset,
+                    ));
+                }
+                gl_call!(gl.vertex_attrib_divisor(attr_descr.attrib_location as u32, divisor));
+            }
+        }
+    }
 }
 
 impl<'a> WebGL2RenderPass<'a> {
-    pub fn setup_vao(&self) {
+    /// Binds pipeline's VAO, updating its attribute pointers if needed.
+    pub fn setup_vao(&self, base_vertex: i32, first_instance: u32) {
         let gl = &self.render_context.device.get_context();
 
         let resources = &self.render_context.render_resource_context.resources;
@@ -24,7 +176,13 @@ impl<'a> WebGL2RenderPass<'a> {
         let pipeline_handle = self.pipeline.as_ref().unwrap();
         let pipeline = pipelines.get_mut(&pipeline_handle).unwrap();
 
-        gl_call!(gl.bind_vertex_array(Some(&pipeline.vao)));
+        gl_call!(gl.bind_vertex_array(Some(pipeline.vao)));
+
+        if pipeline.base_vertex != base_vertex || pipeline.first_instance != first_instance {
+            pipeline.base_vertex = base_vertex;
+            pipeline.first_instance = first_instance;
+            pipeline.update_vao = true;
+        }
 
         if !pipeline.update_vao {
             return;
@@ -32,50 +190,323 @@ impl<'a> WebGL2RenderPass<'a> {
         pipeline.update_vao = false;
         let buffers = resources.buffers.write();
 
-        if let Some(buffer_id) = pipeline.vertex_buffer {
+        if let Some(buffer_id) = pipeline.index_buffer {
             let buffer = buffers.get(&buffer_id).unwrap();
             if let Buffer::WebGlBuffer(buffer_id) = &buffer.buffer {
-                gl_call!(gl.bind_buffer(Gl::ARRAY_BUFFER, Some(buffer_id)));
+                gl_call!(gl.bind_buffer(Gl::ELEMENT_ARRAY_BUFFER, Some(*buffer_id)));
             } else {
                 panic!("binding in-memory buffer");
             }
         }
 
-        if let Some(buffer_id) = pipeline.index_buffer {
-            let buffer = buffers.get(&buffer_id).unwrap();
+        set_vertex_attributes(
+            &**gl,
+            &pipeline.vertex_buffer_descriptors,
+            &pipeline.vertex_buffers,
+            &buffers,
+            base_vertex,
+            first_instance,
+        );
+    }
+
+    /// Checks that negative `base_vertex` doesn't move attribute pointers of per-vertex
+    /// buffers before start of the buffer (offset of `vertexAttribPointer` can't be negative)
+    fn check_base_vertex(&self, base_vertex: i32) -> bool {
+        if base_vertex >= 0 {
+            return true;
+        }
+        let resources = &self.render_context.render_resource_context.resources;
+        let pipelines = resources.pipelines.read();
+        let pipeline = match self
+            .pipeline
+            .as_ref()
+            .and_then(|handle| pipelines.get(handle))
+        {
+            Some(pipeline) => pipeline,
+            None => return true,
+        };
+        for (slot, descriptor) in pipeline.vertex_buffer_descriptors.iter().enumerate() {
+            if let (InputStepMode::Vertex, Some(Some((_, offset)))) =
+                (&descriptor.step_mode, pipeline.vertex_buffers.get(slot))
+            {
+                if (*offset as i64) + (base_vertex as i64) * (descriptor.stride as i64) < 0 {
+                    error!(
+                        "base_vertex {} points before start of vertex buffer in slot {} \
+                         (offset {}), skipping draw",
+                        base_vertex, slot, offset
+                    );
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    /// Binds resources of the bind group to their GL binding points / texture units
+    fn bind_group(
+        &mut self,
+        index: u32,
+        bind_group_id: BindGroupId,
+        dynamic_uniform_indices: Option<&[u32]>,
+    ) {
+        let resources = &self.render_context.render_resource_context.resources;
+        let bind_groups = resources.bind_groups.read();
+        let bind_group = bind_groups.get(&bind_group_id).unwrap();
+        let buffers = resources.buffers.read();
+        let textures = resources.textures.read();
+        let samplers = resources.samplers.read();
+        let non_filtering_samplers = resources.non_filtering_samplers.read();
+        let gl = &self.render_context.device.get_context();
+        for (i, binding) in bind_group.iter().enumerate() {
+            match binding {
+                WebGL2RenderResourceBinding::Buffer {
+                    binding_point,
+                    buffer,
+                    range,
+                } => {
+                    let offset = *dynamic_uniform_indices
+                        .and_then(|indices| indices.get(i))
+                        .unwrap_or(&(range.start as u32));
+                    let buffer = buffers.get(&buffer).unwrap();
+                    let size = (range.end - range.start) as usize;
+                    if let Buffer::WebGlBuffer(buffer_id) = &buffer.buffer {
+                        gl_call!(gl.bind_buffer_range(
+                            Gl::UNIFORM_BUFFER,
+                            *binding_point,
+                            Some(*buffer_id),
+                            offset as i32,
+                            size as i32,
+                        ));
+                    } else {
+                        panic!("binding in-memory buffer");
+                    }
+                }
+                WebGL2RenderResourceBinding::Texture {
+                    texture,
+                    texture_unit,
+                    target,
+                } => {
+                    // it seems it may not work
+                    // (forcing texture_unit=1 do not work properly)
+                    if let Some(texture) = textures.get(texture) {
+                        gl_call!(gl.active_texture(Gl::TEXTURE0 + texture_unit));
+                        gl_call!(gl.bind_texture(*target, Some(*texture)));
+                    }
+                }
+                WebGL2RenderResourceBinding::Sampler {
+                    sampler,
+                    texture_unit,
+                    filtering,
+                } => {
+                    let sampler = if *filtering {
+                        samplers.get(sampler)
+                    } else {
+                        non_filtering_samplers
+                            .get(sampler)
+                            .or_else(|| samplers.get(sampler))
+                    };
+                    if let Some(sampler) = sampler {
+                        gl_call!(gl.bind_sampler(*texture_unit, Some(*sampler)));
+                    }
+                }
+                WebGL2RenderResourceBinding::StorageBuffer {
+                    texture_unit,
+                    buffer,
+                } => {
+                    // storage buffers too large for data texture have none (see
+                    // `create_storage_texture`), shaders read zeros from unbound texture
+                    let storage_texture = buffers
+                        .get(buffer)
+                        .and_then(|buffer| buffer.storage_texture.as_ref())
+                        .map(|storage_texture| storage_texture.texture);
+                    gl_call!(gl.active_texture(Gl::TEXTURE0 + texture_unit));
+                    gl_call!(gl.bind_texture(Gl::TEXTURE_2D, storage_texture));
+                    // texture unit may be shared with textures of other pipelines,
+                    // filtering sampler would make integer texture incomplete
+                    gl_call!(gl.bind_sampler(*texture_unit, None));
+                }
+            }
+        }
+        self.sprite_batch.bind_groups.insert(
+            index,
+            (
+                bind_group_id,
+                dynamic_uniform_indices.map(|indices| indices.to_vec()),
+            ),
+        );
+    }
+
+    fn sprite_batch_kind(&self) -> Option<SpriteBatchKind> {
+        let resources = &self.render_context.render_resource_context.resources;
+        let pipelines = resources.pipelines.read();
+        let pipeline = pipelines.get(self.pipeline.as_ref()?)?;
+        pipeline.sprite_batch.as_ref().map(|batch| batch.kind)
+    }
+
+    /// Packs instance data of a sprite from uniform buffer shadows of its bind group,
+    /// returns `false` if uniform data is not available
+    fn write_sprite_instance(
+        &self,
+        kind: SpriteBatchKind,
+        bind_group_id: BindGroupId,
+        dynamic_uniform_indices: Option<&[u32]>,
+        instance: &mut Vec<u8>,
+    ) -> bool {
+        let resources = &self.render_context.render_resource_context.resources;
+        let bind_groups = resources.bind_groups.read();
+        let buffers = resources.buffers.read();
+        let binding_points = resources.binding_points.read();
+        let bind_group = match bind_groups.get(&bind_group_id) {
+            Some(bind_group) => bind_group,
+            None => return false,
+        };
+        // binding 0: Transform, binding 1: Sprite / TextureAtlasSprite
+        let mut blocks: [Option<&[u8]>; 2] = [None, None];
+        for (i, binding) in bind_group.iter().enumerate() {
+            if let WebGL2RenderResourceBinding::Buffer {
+                binding_point,
+                buffer,
+                range,
+            } = binding
+            {
+                let offset = *dynamic_uniform_indices
+                    .and_then(|indices| indices.get(i))
+                    .unwrap_or(&(range.start as u32)) as usize;
+                let size = (range.end - range.start) as usize;
+                let block = (0..blocks.len()).find(|index| {
+                    binding_points.get(&(SPRITE_BIND_GROUP_INDEX, *index as u32))
+                        == Some(binding_point)
+                });
+                if let Some(block) = block {
+                    blocks[block] = buffers
+                        .get(buffer)
+                        .and_then(|buffer| buffer.shadow.as_ref())
+                        .and_then(|shadow| shadow.get(offset..offset + size));
+                }
+            }
+        }
+        if let [Some(transform), Some(sprite)] = blocks {
+            kind.write_instance(instance, transform, sprite);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns batch key for the draw call if it can be added to a sprite batch
+    fn sprite_batch_key(&self, indices: &Range<u32>, base_vertex: i32) -> Option<SpriteBatchKey> {
+        self.sprite_batch.instance.as_ref()?;
+        let resources = &self.render_context.render_resource_context.resources;
+        let pipelines = resources.pipelines.read();
+        let pipeline_handle = self.pipeline.as_ref()?;
+        let pipeline = pipelines.get(pipeline_handle)?;
+        pipeline.sprite_batch.as_ref()?;
+        Some(SpriteBatchKey {
+            pipeline: pipeline_handle.clone_weak(),
+            vertex_buffers: pipeline.vertex_buffers.clone(),
+            index_buffer: pipeline.index_buffer,
+            index_buffer_offset: pipeline.index_buffer_offset,
+            index_format: pipeline.index_format,
+            indices: indices.clone(),
+            base_vertex,
+        })
+    }
+
+    /// Binds deferred per-sprite bind group, needed if the sprite is drawn without batching
+    fn bind_deferred_sprite_bind_group(&mut self) {
+        if let Some((bind_group_id, dynamic_uniform_indices)) =
+            self.sprite_batch.deferred_bind_group.take()
+        {
+            self.bind_group(
+                SPRITE_BIND_GROUP_INDEX,
+                bind_group_id,
+                dynamic_uniform_indices.as_deref(),
+            );
+        }
+    }
+
+    /// Draws collected sprites with a single instanced draw call
+    pub fn flush_sprite_batch(&mut self) {
+        let key = match self.sprite_batch.key.take() {
+            Some(key) => key,
+            None => return,
+        };
+        let instance_count = std::mem::replace(&mut self.sprite_batch.instance_count, 0);
+
+        let gl = &self.render_context.device.get_context();
+        let resources = &self.render_context.render_resource_context.resources;
+        let programs = resources.programs.read();
+        let pipelines = resources.pipelines.read();
+        let buffers = resources.buffers.read();
+        let pipeline = pipelines.get(&key.pipeline).unwrap();
+        let batch = pipeline.sprite_batch.as_ref().unwrap();
+
+        gl_call!(gl.use_program(Some(batch.program)));
+        gl_call!(gl.bind_vertex_array(Some(batch.vao)));
+        if let Some(buffer) = key.index_buffer.and_then(|id| buffers.get(&id)) {
             if let Buffer::WebGlBuffer(buffer_id) = &buffer.buffer {
-                gl_call!(gl.bind_buffer(Gl::ELEMENT_ARRAY_BUFFER, Some(buffer_id)));
+                gl_call!(gl.bind_buffer(Gl::ELEMENT_ARRAY_BUFFER, Some(*buffer_id)));
             } else {
                 panic!("binding in-memory buffer");
             }
         }
+        set_vertex_attributes(
+            &**gl,
+            &pipeline.vertex_buffer_descriptors,
+            &key.vertex_buffers,
+            &buffers,
+            key.base_vertex,
+            0,
+        );
 
-        assert!(pipeline.vertex_buffer_descriptors.len() == 1);
-        let vertex_buffer_descriptor = &pipeline.vertex_buffer_descriptors[0];
-        for attr_descr in vertex_buffer_descriptor.attributes.iter() {
-            if attr_descr.attrib_location >= 0 {
-                gl_call!(gl.enable_vertex_attrib_array(attr_descr.attrib_location as u32 as u32));
-                if attr_descr.format.format == Gl::FLOAT
-                    || attr_descr.format.format == Gl::HALF_FLOAT
-                {
-                    gl_call!(gl.vertex_attrib_pointer_with_i32(
-                        attr_descr.attrib_location as u32,
-                        attr_descr.format.nr_of_components,
-                        attr_descr.format.format,
-                        attr_descr.format.normalized,
-                        vertex_buffer_descriptor.stride,
-                        attr_descr.offset,
-                    ));
-                } else {
-                    gl_call!(gl.vertex_attrib_i_pointer_with_i32(
-                        attr_descr.attrib_location as u32,
-                        attr_descr.format.nr_of_components,
-                        attr_descr.format.format,
-                        vertex_buffer_descriptor.stride,
-                        attr_descr.offset,
-                    ));
-                }
+        gl_call!(gl.bind_buffer(Gl::ARRAY_BUFFER, Some(batch.instance_buffer)));
+        gl_call!(gl.buffer_data_u8_slice(
+            Gl::ARRAY_BUFFER,
+            &self.sprite_batch.instances,
+            Gl::STREAM_DRAW
+        ));
+        let stride = batch.kind.instance_stride() as i32;
+        for attribute in batch.kind.instance_attributes() {
+            gl_call!(gl.enable_vertex_attrib_array(attribute.location));
+            if attribute.format == Gl::FLOAT {
+                gl_call!(gl.vertex_attrib_pointer_f32(
+                    attribute.location,
+                    attribute.nr_of_components,
+                    attribute.format,
+                    false,
+                    stride,
+                    attribute.offset,
+                ));
+            } else {
+                gl_call!(gl.vertex_attrib_pointer_i32(
+                    attribute.location,
+                    attribute.nr_of_components,
+                    attribute.format,
+                    stride,
+                    attribute.offset,
+                ));
             }
+            gl_call!(gl.vertex_attrib_divisor(attribute.location, 1));
+        }
+        self.sprite_batch.instances.clear();
+
+        let (index_type, type_size): (u32, i32) = key.index_format.webgl2_into();
+        gl_call!(gl.draw_elements_instanced(
+            pipeline.primitive.topology.webgl2_into(),
+            (key.indices.end - key.indices.start) as i32,
+            index_type,
+            key.index_buffer_offset as i32 + key.indices.start as i32 * type_size,
+            instance_count as i32,
+        ));
+
+        // restore program of the current pipeline
+        if let Some(program) = self
+            .pipeline
+            .as_ref()
+            .and_then(|handle| pipelines.get(handle))
+            .and_then(|pipeline| programs.get(&pipeline.shader_stages))
+        {
+            gl_call!(gl.use_program(Some(program.program)));
         }
     }
 }
@@ -85,6 +516,7 @@ impl<'a> RenderPass for WebGL2RenderPass<'a> {
         self.render_context
     }
     fn set_scissor_rect(&mut self, x: u32, y: u32, w: u32, h: u32) {
+        self.flush_sprite_batch();
         let pipeline_handle = self.pipeline.as_ref().unwrap();
         let mut pipelines = self
             .render_context
@@ -97,11 +529,8 @@ impl<'a> RenderPass for WebGL2RenderPass<'a> {
         let gl = &self.render_context.device.get_context();
 
         // TODO - there might be a better way to get a viewport size (make WindowDescriptor available in RenderPass?)
-        let viewport: js_sys::Int32Array = gl_call!(gl.get_parameter(Gl::VIEWPORT))
-            .unwrap()
-            .dyn_into()
-            .unwrap();
-        let viewport_height = viewport.get_index(3);
+        let viewport = gl_call!(gl.get_parameter_i32_array(Gl::VIEWPORT));
+        let viewport_height = viewport[3];
 
         let (x, y, w, h) = (
             x as i32,
@@ -114,167 +543,179 @@ impl<'a> RenderPass for WebGL2RenderPass<'a> {
         gl_call!(gl.enable(Gl::SCISSOR_TEST));
         gl_call!(gl.scissor(x as i32, y as i32, w as i32, h as i32));
     }
-    fn set_vertex_buffer(&mut self, _start_slot: u32, buffer_id: BufferId, _offset: u64) {
-        // TODO - start_slot and offset parameters
+    fn set_vertex_buffer(&mut self, start_slot: u32, buffer_id: BufferId, offset: u64) {
         let resources = &self.render_context.render_resource_context.resources;
         let mut pipelines = resources.pipelines.write();
         let pipeline_handle = self.pipeline.as_ref().unwrap();
         let pipeline = pipelines.get_mut(&pipeline_handle).unwrap();
 
-        if pipeline.vertex_buffer != Some(buffer_id) {
-            pipeline.vertex_buffer = Some(buffer_id);
+        let slot = start_slot as usize;
+        if pipeline.vertex_buffers.len() <= slot {
+            pipeline.vertex_buffers.resize(slot + 1, None);
+        }
+        if pipeline.vertex_buffers[slot] != Some((buffer_id, offset)) {
+            pipeline.vertex_buffers[slot] = Some((buffer_id, offset));
             pipeline.update_vao = true;
         }
     }
 
     fn set_viewport(&mut self, x: f32, y: f32, w: f32, h: f32, _min_depth: f32, _max_depth: f32) {
+        self.flush_sprite_batch();
         let ctx = &self.render_context;
         let gl = &ctx.device.get_context();
         gl_call!(gl.viewport(x as i32, y as i32, w as i32, h as i32));
     }
 
-    fn set_stencil_reference(&mut self, _reference: u32) {}
-
-    fn set_index_buffer(&mut self, buffer_id: BufferId, _offset: u64, index_format: IndexFormat) {
-        // TODO - offset parameter
-        // TODO - index_format parameter
+    fn set_stencil_reference(&mut self, reference: u32) {
+        if reference == self.stencil_reference {
+            return;
+        }
+        self.flush_sprite_batch();
+        self.stencil_reference = reference;
+        let resources = &self.render_context.render_resource_context.resources;
+        let pipelines = resources.pipelines.read();
+        let depth_stencil = self
+            .pipeline
+            .as_ref()
+            .and_then(|handle| pipelines.get(handle))
+            .and_then(|pipeline| pipeline.depth_stencil.as_ref());
+        if let Some(state) = depth_stencil {
+            if is_stencil_enabled(&state.stencil) {
+                let gl = &self.render_context.device.get_context();
+                set_stencil_func(&**gl, &state.stencil, reference);
+            }
+        }
+    }
 
+    fn set_index_buffer(&mut self, buffer_id: BufferId, offset: u64, index_format: IndexFormat) {
         let resources = &self.render_context.render_resource_context.resources;
         let mut pipelines = resources.pipelines.write();
         let pipeline_handle = self.pipeline.as_ref().unwrap();
         let pipeline = pipelines.get_mut(&pipeline_handle).unwrap();
 
+        // index buffer offset and format are passed to drawElements directly
+        // and do not need VAO update
+        pipeline.index_buffer_offset = offset;
+        pipeline.index_format = index_format;
         if pipeline.index_buffer != Some(buffer_id) {
             pipeline.index_buffer = Some(buffer_id);
-            pipeline.index_format = index_format;
             pipeline.update_vao = true;
         }
     }
 
-    fn draw_indexed(&mut self, indices: Range<u32>, _base_vertex: i32, instances: Range<u32>) {
+    fn draw_indexed(&mut self, indices: Range<u32>, base_vertex: i32, instances: Range<u32>) {
+        if !self.check_base_vertex(base_vertex) {
+            return;
+        }
+        if instances.end - instances.start == 1 {
+            if let Some(key) = self.sprite_batch_key(&indices, base_vertex) {
+                if self.sprite_batch.key.as_ref() != Some(&key) {
+                    self.flush_sprite_batch();
+                    self.sprite_batch.key = Some(key);
+                }
+                let batch = &mut self.sprite_batch;
+                batch
+                    .instances
+                    .extend_from_slice(batch.instance.as_ref().unwrap());
+                batch.instance_count += 1;
+                return;
+            }
+        }
+        self.flush_sprite_batch();
+        self.bind_deferred_sprite_bind_group();
+
         // mysterious "Parking not supported on this platform" panic if you let this code
         // out of its block.
-        let (primitives, (index_type, type_size)) = {
+        let (primitives, (index_type, type_size), index_buffer_offset): (u32, (u32, i32), i32) = {
             let resources = &self.render_context.render_resource_context.resources;
             let pipelines = resources.pipelines.read();
             let pipeline_handle = self.pipeline.as_ref().unwrap();
             let pipeline = pipelines.get(&pipeline_handle).unwrap();
-
-            let primitives = match pipeline.primitive.topology {
-                PrimitiveTopology::PointList => Gl::POINTS,
-                PrimitiveTopology::LineList => Gl::LINES,
-                PrimitiveTopology::LineStrip => Gl::LINE_STRIP,
-                PrimitiveTopology::TriangleList => Gl::TRIANGLES,
-                PrimitiveTopology::TriangleStrip => Gl::TRIANGLE_STRIP,
-            };
-
             (
-                primitives,
-                match pipeline.index_format {
-                    IndexFormat::Uint16 => (Gl::UNSIGNED_SHORT, 2),
-                    IndexFormat::Uint32 => (Gl::UNSIGNED_INT, 4),
-                },
+                pipeline.primitive.topology.webgl2_into(),
+                pipeline.index_format.webgl2_into(),
+                pipeline.index_buffer_offset as i32,
             )
         };
 
         let ctx = &self.render_context;
         let gl = &ctx.device.get_context();
-        self.setup_vao();
-        gl_call!(gl.draw_elements_instanced_with_i32(
+        self.setup_vao(base_vertex, instances.start);
+        gl_call!(gl.draw_elements_instanced(
             primitives,
             (indices.end - indices.start) as i32,
             index_type,
-            indices.start as i32 * type_size,
+            index_buffer_offset + indices.start as i32 * type_size,
             (instances.end - instances.start) as i32,
         ));
-        let gl_null = None;
-        gl_call!(gl.bind_vertex_array(gl_null));
     }
 
-    fn draw(&mut self, vertices: Range<u32>, _instances: Range<u32>) {
-        let resources = &self.render_context.render_resource_context.resources;
-        let pipelines = resources.pipelines.read();
-        let pipeline_handle = self.pipeline.as_ref().unwrap();
-        let pipeline = pipelines.get(&pipeline_handle).unwrap();
+    fn draw(&mut self, vertices: Range<u32>, instances: Range<u32>) {
+        self.flush_sprite_batch();
+        self.bind_deferred_sprite_bind_group();
+
+        let primitives: u32 = {
+            let resources = &self.render_context.render_resource_context.resources;
+            let pipelines = resources.pipelines.read();
+            let pipeline_handle = self.pipeline.as_ref().unwrap();
+            let pipeline = pipelines.get(&pipeline_handle).unwrap();
+            pipeline.primitive.topology.webgl2_into()
+        };
         let ctx = &self.render_context;
         let gl = &ctx.device.get_context();
-        self.setup_vao();
-        let primitives = match pipeline.primitive.topology {
-            PrimitiveTopology::PointList => Gl::POINTS,
-            PrimitiveTopology::LineList => Gl::LINES,
-            PrimitiveTopology::LineStrip => Gl::LINE_STRIP,
-            PrimitiveTopology::TriangleList => Gl::TRIANGLES,
-            PrimitiveTopology::TriangleStrip => Gl::TRIANGLE_STRIP,
-        };
-        gl_call!(gl.draw_arrays(
+        self.setup_vao(0, instances.start);
+        gl_call!(gl.draw_arrays_instanced(
             primitives,
             vertices.start as i32,
-            (vertices.end - vertices.start) as i32
+            (vertices.end - vertices.start) as i32,
+            (instances.end - instances.start) as i32,
         ));
-        let gl_null = None;
-        gl_call!(gl.bind_vertex_array(gl_null));
     }
 
     fn set_bind_group(
         &mut self,
-        _index: u32,
+        index: u32,
         _bind_group_descriptor_id: BindGroupDescriptorId,
         bind_group_id: BindGroupId,
         dynamic_uniform_indices: Option<&[u32]>,
     ) {
-        let resources = &self.render_context.render_resource_context.resources;
-        let bind_groups = resources.bind_groups.read();
-        let bind_group = bind_groups.get(&bind_group_id).unwrap();
-        let buffers = resources.buffers.read();
-        let textures = resources.textures.read();
-        let gl = &self.render_context.device.get_context();
-        for (i, binding) in bind_group.iter().enumerate() {
-            match binding {
-                crate::WebGL2RenderResourceBinding::Buffer {
-                    binding_point,
-                    buffer,
-                    range,
-                } => {
-                    let offset = *dynamic_uniform_indices
-                        .and_then(|indices| indices.get(i))
-                        .unwrap_or(&(range.start as u32));
-                    let buffer = buffers.get(&buffer).unwrap();
-                    let size = if buffer.info.buffer_usage.contains(BufferUsage::STORAGE) {
-                        STORAGE_BUFFER_SIZE
-                    } else {
-                        (range.end - range.start) as usize
-                    };
-                    if let Buffer::WebGlBuffer(buffer_id) = &buffer.buffer {
-                        gl_call!(gl.bind_buffer_range_with_i32_and_i32(
-                            Gl::UNIFORM_BUFFER,
-                            *binding_point,
-                            Some(buffer_id),
-                            offset as i32,
-                            size as i32,
-                        ));
-                    } else {
-                        panic!("binding in-memory buffer");
-                    }
-                }
-                crate::WebGL2RenderResourceBinding::Texture {
-                    texture,
-                    texture_unit,
-                } => {
-                    // it seems it may not work
-                    // (forcing texture_unit=1 do not work properly)
-                    if let Some(texture) = textures.get(texture) {
-                        gl_call!(gl.active_texture(Gl::TEXTURE0 + texture_unit));
-                        gl_call!(gl.bind_texture(Gl::TEXTURE_2D, Some(texture)));
-                    }
-                }
-                crate::WebGL2RenderResourceBinding::Sampler(_) => {
-                    // TODO
+        // per-sprite bind group of sprite pipelines is not bound to GL,
+        // its uniforms become instance data of the sprite batch
+        if index == SPRITE_BIND_GROUP_INDEX {
+            if let Some(kind) = self.sprite_batch_kind() {
+                let mut instance = self.sprite_batch.instance.take().unwrap_or_default();
+                if self.write_sprite_instance(
+                    kind,
+                    bind_group_id,
+                    dynamic_uniform_indices,
+                    &mut instance,
+                ) {
+                    self.sprite_batch.instance = Some(instance);
+                    self.sprite_batch.deferred_bind_group = Some((
+                        bind_group_id,
+                        dynamic_uniform_indices.map(|indices| indices.to_vec()),
+                    ));
+                    return;
                 }
             }
         }
+        let is_bound = matches!(
+            self.sprite_batch.bind_groups.get(&index),
+            Some((id, indices))
+                if *id == bind_group_id && indices.as_deref() == dynamic_uniform_indices
+        );
+        if !is_bound {
+            self.flush_sprite_batch();
+        }
+        self.bind_group(index, bind_group_id, dynamic_uniform_indices);
     }
 
     fn set_pipeline(&mut self, pipeline_handle: &Handle<PipelineDescriptor>) {
+        if matches!(&self.sprite_batch.key, Some(key) if key.pipeline != *pipeline_handle) {
+            self.flush_sprite_batch();
+        }
+        self.sprite_batch.instance = None;
+        self.sprite_batch.deferred_bind_group = None;
         self.pipeline = Some(pipeline_handle.as_weak());
 
         let resources = &self.render_context.render_resource_context.resources;
@@ -307,61 +748,65 @@ impl<'a> RenderPass for WebGL2RenderPass<'a> {
         }
 
         if let Some(state) = &pipeline.depth_stencil {
-            let depth_func = match state.depth_compare {
-                bevy::render::pipeline::CompareFunction::Never => Gl::NEVER,
-                bevy::render::pipeline::CompareFunction::Less => Gl::LESS,
-                bevy::render::pipeline::CompareFunction::Equal => Gl::EQUAL,
-                bevy::render::pipeline::CompareFunction::LessEqual => Gl::LEQUAL,
-                bevy::render::pipeline::CompareFunction::Greater => Gl::GREATER,
-                bevy::render::pipeline::CompareFunction::NotEqual => Gl::NOTEQUAL,
-                bevy::render::pipeline::CompareFunction::GreaterEqual => Gl::GEQUAL,
-                bevy::render::pipeline::CompareFunction::Always => Gl::ALWAYS,
-            };
-            gl_call!(gl.depth_func(depth_func));
-        }
-
-        if let Some(state) = pipeline.color_target_states.get(0) {
-            gl_call!(gl.enable(Gl::BLEND));
-            fn blend_factor(factor: &BlendFactor) -> u32 {
-                match factor {
-                    BlendFactor::Zero => Gl::ZERO,
-                    BlendFactor::One => Gl::ONE,
-                    BlendFactor::Src => Gl::SRC_COLOR,
-                    BlendFactor::OneMinusSrc => Gl::ONE_MINUS_SRC_COLOR,
-                    BlendFactor::SrcAlpha => Gl::SRC_ALPHA,
-                    BlendFactor::OneMinusSrcAlpha => Gl::ONE_MINUS_SRC_ALPHA,
-                    BlendFactor::Dst => Gl::DST_COLOR,
-                    BlendFactor::OneMinusDst => Gl::ONE_MINUS_DST_COLOR,
-                    BlendFactor::DstAlpha => Gl::DST_ALPHA,
-                    BlendFactor::OneMinusDstAlpha => Gl::ONE_MINUS_DST_ALPHA,
-                    BlendFactor::SrcAlphaSaturated => Gl::SRC_ALPHA_SATURATE,
-                    BlendFactor::Constant => Gl::CONSTANT_COLOR, // TODO: COLOR or ALPHA?
-                    BlendFactor::OneMinusConstant => Gl::ONE_MINUS_CONSTANT_COLOR, // TODO: COLOR or ALPHA?
+            gl_call!(gl.enable(Gl::DEPTH_TEST));
+            gl_call!(gl.depth_func(state.depth_compare.webgl2_into()));
+            gl_call!(gl.depth_mask(state.depth_write_enabled));
+
+            if is_stencil_enabled(&state.stencil) {
+                gl_call!(gl.enable(Gl::STENCIL_TEST));
+                set_stencil_func(&**gl, &state.stencil, self.stencil_reference);
+                for (face, state) in [
+                    (Gl::FRONT, &state.stencil.front),
+                    (Gl::BACK, &state.stencil.back),
+                ]
+                .iter()
+                {
+                    gl_call!(gl.stencil_op_separate(
+                        *face,
+                        state.fail_op.webgl2_into(),
+                        state.depth_fail_op.webgl2_into(),
+                        state.pass_op.webgl2_into(),
+                    ));
                 }
+                gl_call!(gl.stencil_mask_separate(Gl::FRONT_AND_BACK, state.stencil.write_mask));
+            } else {
+                gl_call!(gl.disable(Gl::STENCIL_TEST));
+            }
+
+            // depth bias clamp is not supported by WebGL2
+            let bias = &state.bias;
+            if bias.constant != 0 || bias.slope_scale != 0.0 {
+                gl_call!(gl.enable(Gl::POLYGON_OFFSET_FILL));
+                gl_call!(gl.polygon_offset(bias.slope_scale, bias.constant as f32));
+            } else {
+                gl_call!(gl.disable(Gl::POLYGON_OFFSET_FILL));
             }
-            if let Some(blend) = &state.blend {
-                let src_factor = blend_factor(&blend.color.src_factor);
-                let dst_factor = blend_factor(&blend.color.dst_factor);
-                let src_alpha = blend_factor(&blend.alpha.src_factor);
-                let dst_alpha = blend_factor(&blend.alpha.dst_factor);
-                gl_call!(gl.blend_func_separate(src_factor, dst_factor, src_alpha, dst_alpha));
-
-                let op = match blend.color.operation {
-                    BlendOperation::Add => Gl::FUNC_ADD,
-                    BlendOperation::Subtract => Gl::FUNC_SUBTRACT,
-                    BlendOperation::ReverseSubtract => Gl::FUNC_REVERSE_SUBTRACT,
-                    BlendOperation::Min => Gl::MIN,
-                    BlendOperation::Max => Gl::MAX,
-                };
-                gl_call!(gl.blend_equation(op));
+        } else {
+            gl_call!(gl.disable(Gl::DEPTH_TEST));
+            gl_call!(gl.disable(Gl::STENCIL_TEST));
+            gl_call!(gl.disable(Gl::POLYGON_OFFSET_FILL));
+        }
+
+        let color_target_states = &pipeline.color_target_states;
+        if !pipeline.has_uniform_color_targets()
+            && resources.capabilities.read().draw_buffers_indexed
+        {
+            for (i, state) in color_target_states.iter().enumerate() {
+                set_color_target_state_indexed(&**gl, i as u32, state);
             }
+        } else if let Some(state) = color_target_states.get(0) {
+            // without OES_draw_buffers_indexed all attachments use state of the first one,
+            // warned about in `create_render_pipeline`
+            set_color_target_state(&**gl, state);
         } else {
             gl_call!(gl.disable(Gl::BLEND));
         }
+        let [r, g, b, a] = *resources.blend_constant.read();
+        gl_call!(gl.blend_color(r, g, b, a));
 
         let program = programs.get(&pipeline.shader_stages).unwrap();
 
-        gl_call!(gl.use_program(Some(&program.program)));
+        gl_call!(gl.use_program(Some(program.program)));
 
         if let Some(ScissorsState { x, y, w, h }) = pipeline.scissors_state.clone() {
             gl_call!(gl.enable(Gl::SCISSOR_TEST));
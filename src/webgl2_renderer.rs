@@ -1,10 +1,13 @@
-use crate::renderer::{WebGL2RenderContext, WebGL2RenderResourceContext};
+use crate::renderer::{GlBackend, GlStateCache, WebGL2RenderContext, WebGL2RenderResourceContext};
+use bevy::asset::Assets;
 use bevy::ecs::world::{Mut, World};
 use bevy::render::{
+    pipeline::PipelineCompiler,
     render_graph::{
         DependentNodeStager, Edge, NodeId, RenderGraph, RenderGraphStager, ResourceSlots,
     },
     renderer::RenderResourceContext,
+    shader::Shader,
 };
 use std::sync::Arc;
 
@@ -14,18 +17,19 @@ use std::cell::{Ref, RefCell};
 
 #[derive(Default)]
 pub struct Device {
-    context: RefCell<Option<web_sys::WebGl2RenderingContext>>,
+    context: RefCell<Option<Box<dyn GlBackend>>>,
 }
 
 impl Device {
-    pub fn get_context(&self) -> std::cell::Ref<web_sys::WebGl2RenderingContext> {
+    pub fn get_context(&self) -> std::cell::Ref<dyn GlBackend> {
         return Ref::map(self.context.borrow(), |t| {
-            t.as_ref().expect("webgl context is set")
+            t.as_deref().expect("webgl context is set")
         });
     }
 
-    pub fn set_context(&self, context: web_sys::WebGl2RenderingContext) {
-        *self.context.borrow_mut() = Some(context);
+    /// Sets GL context, wrapped with [`GlStateCache`] skipping redundant state changes
+    pub fn set_context(&self, context: Box<dyn GlBackend>) {
+        *self.context.borrow_mut() = Some(Box::new(GlStateCache::new(context)));
     }
 }
 
@@ -116,6 +120,18 @@ impl WebGL2Renderer {
     }
 
     pub fn update(&mut self, world: &mut World) {
+        if let (Some(render_resource_context), Some(pipeline_compiler), Some(shaders)) = (
+            world.get_resource::<Box<dyn RenderResourceContext>>(),
+            world.get_resource::<PipelineCompiler>(),
+            world.get_resource::<Assets<Shader>>(),
+        ) {
+            // sprite pipelines compiled in this frame's draw systems
+            if let Some(context) =
+                render_resource_context.downcast_ref::<WebGL2RenderResourceContext>()
+            {
+                context.create_sprite_batch_pipelines(&pipeline_compiler, &shaders);
+            }
+        }
         self.run_graph(world);
         if let Some(render_resource_context) =
             world.get_resource::<Box<dyn RenderResourceContext>>()
@@ -1,11 +1,15 @@
 use crate::{
+    converters::CompressedTextureFormat,
     gl_call,
     renderer::{
-        gl_vertex_format, WebGl2RenderingContext, WebGlBuffer, WebGlFramebuffer, WebGlProgram,
-        WebGlShader, WebGlTexture, WebGlVertexArrayObject,
+        gl_vertex_format, GlBackend, NativeBuffer, NativeFramebuffer, NativeProgram,
+        NativeRenderbuffer, NativeSampler, NativeShader, NativeTexture, NativeVertexArray,
     },
+    sprite_batch::GlSpriteBatchPipeline,
+    WebGL2Capabilities, WebGL2ContextSettings,
 };
 use bevy::asset::{Handle, HandleUntyped};
+use bevy::log::prelude::*;
 use bevy::render::{
     pipeline::{
         BindGroupDescriptor, BindGroupDescriptorId, ColorTargetState, DepthStencilState,
@@ -16,7 +20,7 @@ use bevy::render::{
     shader::ShaderStages,
     texture::TextureDescriptor,
 };
-use bevy::utils::HashMap;
+use bevy::utils::{HashMap, HashSet};
 use parking_lot::RwLock;
 use std::ops::Range;
 use std::{borrow::Cow, sync::Arc};
@@ -36,11 +40,11 @@ pub struct GlVertexAttribute {
 
 impl GlVertexAttribute {
     pub fn from(
-        gl: &WebGl2RenderingContext,
-        program: &WebGlProgram,
+        gl: &dyn GlBackend,
+        program: NativeProgram,
         attr: &VertexAttribute,
     ) -> GlVertexAttribute {
-        let attrib_location = gl_call!(gl.get_attrib_location(&program, &*attr.name));
+        let attrib_location = gl_call!(gl.get_attrib_location(program, &*attr.name));
         GlVertexAttribute {
             name: attr.name.to_owned(),
             offset: attr.offset as i32,
@@ -59,8 +63,8 @@ pub struct GlVertexBufferDescripror {
 
 impl GlVertexBufferDescripror {
     pub fn from(
-        gl: &WebGl2RenderingContext,
-        program: &WebGlProgram,
+        gl: &dyn GlBackend,
+        program: NativeProgram,
         vertex_buffer_descriptor: &VertexBufferLayout,
     ) -> GlVertexBufferDescripror {
         GlVertexBufferDescripror {
@@ -79,15 +83,31 @@ impl GlVertexBufferDescripror {
 pub struct WebGL2Pipeline {
     pub shader_stages: ShaderStages,
     pub vertex_buffer_descriptors: Vec<GlVertexBufferDescripror>,
-    pub vao: WebGlVertexArrayObject,
-    pub vertex_buffer: Option<BufferId>,
+    pub vao: NativeVertexArray,
+    pub vertex_buffers: Vec<Option<(BufferId, u64)>>,
     pub index_buffer: Option<BufferId>,
+    pub index_buffer_offset: u64,
     pub index_format: IndexFormat,
+    /// base vertex the attribute pointers of per-vertex buffers were last set up for
+    pub base_vertex: i32,
+    /// first instance the attribute pointers of per-instance buffers were last set up for
+    pub first_instance: u32,
     pub update_vao: bool,
     pub color_target_states: Vec<ColorTargetState>,
     pub depth_stencil: Option<DepthStencilState>,
     pub primitive: PrimitiveState,
     pub scissors_state: Option<ScissorsState>,
+    pub sprite_batch: Option<GlSpriteBatchPipeline>,
+}
+
+impl WebGL2Pipeline {
+    /// Whether all color targets share blend state and write mask, otherwise they have to be
+    /// set per draw buffer (requires OES_draw_buffers_indexed)
+    pub fn has_uniform_color_targets(&self) -> bool {
+        self.color_target_states.windows(2).all(|states| {
+            states[0].blend == states[1].blend && states[0].write_mask == states[1].write_mask
+        })
+    }
 }
 
 #[derive(Clone)]
@@ -108,13 +128,21 @@ pub enum WebGL2RenderResourceBinding {
     Texture {
         texture_unit: u32,
         texture: TextureId,
+        target: u32,
     },
-    Sampler(SamplerId),
+    Sampler {
+        texture_unit: u32,
+        sampler: SamplerId,
+        /// `false` for samplers of integer textures, which are bound with NEAREST filters
+        filtering: bool,
+    },
+    /// Storage buffer, bound as its [`GlStorageTexture`]
+    StorageBuffer { texture_unit: u32, buffer: BufferId },
 }
 
 #[derive(Debug)]
 pub enum Buffer {
-    WebGlBuffer(WebGlBuffer),
+    WebGlBuffer(NativeBuffer),
     Data(Vec<u8>),
 }
 
@@ -122,18 +150,50 @@ pub enum Buffer {
 pub struct GlBufferInfo {
     pub buffer: Buffer,
     pub info: BufferInfo,
+    /// CPU copy of uniform buffer contents, used to pack per-sprite uniforms
+    /// into instance data of sprite batches (kept only for buffers of sprite bind groups)
+    pub shadow: Option<Vec<u8>>,
+    /// Data texture emulating storage buffer
+    pub storage_texture: Option<GlStorageTexture>,
+}
+
+/// `RGBA32UI` texture holding contents of storage buffer (one texel per 16 bytes,
+/// row by row), read in shaders with `texelFetch`. WebGL2 has no storage buffers,
+/// so they are declared in GLSL as `usampler2D` with `buffer` binding comment:
+/// `uniform highp usampler2D TextureAtlas_textures; // set = 1, binding = 1, buffer`
+#[derive(Debug)]
+pub struct GlStorageTexture {
+    pub texture: NativeTexture,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl GlStorageTexture {
+    pub const TEXEL_SIZE: usize = 16;
+
+    /// Size of GL buffer backing the texture (data padded to full rows)
+    pub fn buffer_size(&self) -> usize {
+        (self.width * self.height) as usize * Self::TEXEL_SIZE
+    }
 }
 
 pub struct GlShader {
-    pub shader: WebGlShader,
+    pub shader: NativeShader,
     pub bind_groups: GlBindGroups,
+    /// names of `usampler2D` uniforms emulating storage buffers
+    pub storage_buffers: HashSet<String>,
 }
 
 impl GlShader {
-    pub fn new(shader: WebGlShader, bind_groups: GlBindGroups) -> Self {
+    pub fn new(
+        shader: NativeShader,
+        bind_groups: GlBindGroups,
+        storage_buffers: HashSet<String>,
+    ) -> Self {
         Self {
             shader,
             bind_groups,
+            storage_buffers,
         }
     }
 }
@@ -141,19 +201,29 @@ impl GlShader {
 pub type GlBindGroups = HashMap<String, (u32, u32)>;
 
 pub struct GlProgram {
-    pub program: WebGlProgram,
+    pub program: NativeProgram,
     pub bind_groups: GlBindGroups,
+    pub storage_buffers: HashSet<String>,
 }
 
 impl GlProgram {
-    pub fn new(program: WebGlProgram, bind_groups: GlBindGroups) -> Self {
+    pub fn new(
+        program: NativeProgram,
+        bind_groups: GlBindGroups,
+        storage_buffers: HashSet<String>,
+    ) -> Self {
         Self {
             program,
             bind_groups,
+            storage_buffers,
         }
     }
 }
 
+/// Textures attached to framebuffer: color attachments (`None` for ones which
+/// aren't textures) followed by depth / stencil attachment
+pub type FramebufferAttachments = Vec<Option<TextureId>>;
+
 #[derive(Default, Clone)]
 pub struct WebGL2Resources {
     pub binding_point_seq: Arc<RwLock<u32>>,
@@ -165,13 +235,38 @@ pub struct WebGL2Resources {
     pub bind_groups: Arc<RwLock<HashMap<BindGroupId, Vec<WebGL2RenderResourceBinding>>>>,
     pub buffers: Arc<RwLock<HashMap<BufferId, GlBufferInfo>>>,
     pub texture_descriptors: Arc<RwLock<HashMap<TextureId, TextureDescriptor>>>,
-    pub textures: Arc<RwLock<HashMap<TextureId, WebGlTexture>>>,
+    pub textures: Arc<RwLock<HashMap<TextureId, NativeTexture>>>,
+    /// GL targets of `textures`, see [`crate::converters::default_view_dimension`]
+    pub texture_targets: Arc<RwLock<HashMap<TextureId, u32>>>,
+    /// multisampled textures (`sample_count > 1`), which can only be rendered to and resolved
+    pub renderbuffers: Arc<RwLock<HashMap<TextureId, NativeRenderbuffer>>>,
+    pub samplers: Arc<RwLock<HashMap<SamplerId, NativeSampler>>>,
+    /// NEAREST variants of filtering samplers, used with integer textures
+    /// (incomplete when sampled with LINEAR filters)
+    pub non_filtering_samplers: Arc<RwLock<HashMap<SamplerId, NativeSampler>>>,
     pub asset_resources: Arc<RwLock<HashMap<(HandleUntyped, u64), RenderResourceId>>>,
     pub bind_group_layouts: Arc<RwLock<HashMap<BindGroupDescriptorId, BindGroupDescriptor>>>,
     pub pipelines: Arc<RwLock<HashMap<Handle<PipelineDescriptor>, WebGL2Pipeline>>>,
+    /// layouts of sprite bind groups of batched pipelines, their uniform buffers are shadowed
+    pub sprite_bind_group_layouts: Arc<RwLock<HashSet<BindGroupDescriptorId>>>,
     pub short_buffer_id_seq: Arc<RwLock<u32>>,
     pub short_buffer_ids: Arc<RwLock<HashMap<BufferId, u32>>>,
-    pub framebuffers: Arc<RwLock<HashMap<TextureId, WebGlFramebuffer>>>,
+    /// offscreen framebuffers, keyed by textures attached to them (see [`FramebufferAttachments`])
+    pub framebuffers: Arc<RwLock<HashMap<FramebufferAttachments, NativeFramebuffer>>>,
+    /// framebuffers with resolve target attached, used to resolve multisampled passes
+    pub resolve_framebuffers: Arc<RwLock<HashMap<TextureId, NativeFramebuffer>>>,
+    /// read / draw framebuffers reused by `copy_texture_to_texture`
+    pub copy_framebuffers: Arc<RwLock<Option<(NativeFramebuffer, NativeFramebuffer)>>>,
+    pub compressed_texture_formats: Arc<RwLock<HashSet<CompressedTextureFormat>>>,
+    pub compressed_textures: Arc<RwLock<HashMap<TextureId, CompressedTextureFormat>>>,
+    /// textures whose next `copy_buffer_to_texture` is ignored (placeholder data queued
+    /// by bevy for textures replaced in `ktx2_texture_system`)
+    pub skipped_uploads: Arc<RwLock<HashSet<TextureId>>>,
+    pub capabilities: Arc<RwLock<WebGL2Capabilities>>,
+    /// attributes the context was created with, tell which buffers the canvas has
+    pub context_settings: Arc<RwLock<WebGL2ContextSettings>>,
+    /// blend constant used by `BlendFactor::Constant` / `OneMinusConstant`
+    pub blend_constant: Arc<RwLock<[f32; 4]>>,
     // pub fence_sync: Arc<RwLock<Option<WebGlSync>>>,
 }
 
@@ -187,19 +282,47 @@ impl WebGL2Resources {
         })
     }
 
-    pub fn get_or_create_binding_point(&self, group_index: u32, index: u32) -> u32 {
+    pub fn texture_target(&self, texture: TextureId) -> u32 {
+        *self.texture_targets.read().get(&texture).unwrap()
+    }
+
+    /// Uniform buffer binding point of the binding, `None` (with logged error) if bindings
+    /// of all bind groups need more than MAX_UNIFORM_BUFFER_BINDINGS points
+    pub fn get_or_create_binding_point(&self, group_index: u32, index: u32) -> Option<u32> {
+        let max_binding_points = self.capabilities.read().limits.max_uniform_buffer_bindings;
         let mut storage = self.binding_points.write();
         let mut seq = self.binding_point_seq.write();
-        self._get_or_create(&mut *storage, &mut seq, (group_index, index))
+        let binding_point = self._get_or_create(&mut *storage, &mut seq, (group_index, index));
+        if binding_point >= max_binding_points {
+            error!(
+                "uniform block (set = {}, binding = {}) exceeds MAX_UNIFORM_BUFFER_BINDINGS ({}), \
+                 it won't be bound",
+                group_index, index, max_binding_points
+            );
+            return None;
+        }
+        Some(binding_point)
     }
 
-    pub fn get_or_create_texture_unit(&self, group_index: u32, index: u32) -> u32 {
+    /// Texture unit of the binding, `None` (with logged error) if bindings of all bind groups
+    /// need more than MAX_COMBINED_TEXTURE_IMAGE_UNITS units
+    pub fn get_or_create_texture_unit(&self, group_index: u32, index: u32) -> Option<u32> {
+        let max_texture_units = self.capabilities.read().limits.max_texture_units;
         let mut storage = self.texture_units.write();
         let mut seq = self.texture_unit_seq.write();
 
-        self._get_or_create(&mut *storage, &mut seq, (group_index, index))
+        let texture_unit = self._get_or_create(&mut *storage, &mut seq, (group_index, index));
         // adding 1 to return value here should force using next texture unit
         // but it seems to not work
+        if texture_unit >= max_texture_units {
+            error!(
+                "texture (set = {}, binding = {}) exceeds MAX_COMBINED_TEXTURE_IMAGE_UNITS ({}), \
+                 it won't be bound",
+                group_index, index, max_texture_units
+            );
+            return None;
+        }
+        Some(texture_unit)
     }
 
     pub fn short_buffer_id(&self, buffer_id: BufferId) -> u32 {
@@ -0,0 +1,68 @@
+//! Renders on offscreen OSMesa context and reads the result back:
+//! `cargo test --features native --test headless`
+#![cfg(all(feature = "native", not(target_arch = "wasm32")))]
+
+use bevy::render::{
+    color::Color,
+    pass::{LoadOp, Operations, PassDescriptor, RenderPassColorAttachment, TextureAttachment},
+    renderer::{
+        BufferInfo, BufferUsage, RenderContext, RenderResourceBindings, RenderResourceContext,
+    },
+    texture::{Extent3d, TextureDescriptor, TextureFormat},
+};
+use bevy_webgl2::renderer::{WebGL2RenderContext, WebGL2RenderResourceContext};
+use bevy_webgl2::Device;
+use std::cell::RefCell;
+use std::sync::Arc;
+
+const SIZE: u32 = 16;
+
+#[test]
+fn clear_offscreen_texture_and_read_it_back() {
+    let device = Arc::new(Device::default());
+    let mut resource_context = WebGL2RenderResourceContext::new(device.clone());
+    resource_context
+        .initialize_headless(SIZE, SIZE)
+        .expect("headless context");
+
+    let extent = Extent3d::new(SIZE, SIZE, 1);
+    let texture = resource_context.create_texture(TextureDescriptor {
+        size: extent,
+        format: TextureFormat::Rgba8Unorm,
+        ..Default::default()
+    });
+    let buffer_size = (SIZE * SIZE * 4) as usize;
+    let buffer = resource_context.create_buffer(BufferInfo {
+        size: buffer_size,
+        buffer_usage: BufferUsage::COPY_DST | BufferUsage::MAP_READ,
+        ..Default::default()
+    });
+
+    let mut context = WebGL2RenderContext::new(device, resource_context.clone());
+    let pass_descriptor = PassDescriptor {
+        color_attachments: vec![RenderPassColorAttachment {
+            attachment: TextureAttachment::Id(texture),
+            resolve_target: None,
+            ops: Operations {
+                load: LoadOp::Clear(Color::rgba_linear(1.0, 0.0, 0.0, 1.0)),
+                store: true,
+            },
+        }],
+        depth_stencil_attachment: None,
+        sample_count: 1,
+    };
+    context.begin_pass(
+        &pass_descriptor,
+        &RenderResourceBindings::default(),
+        &mut |_| {},
+    );
+    context.copy_texture_to_buffer(texture, [0, 0, 0], 0, buffer, 0, SIZE * 4, extent);
+
+    let pixels = RefCell::new(Vec::new());
+    resource_context.read_mapped_buffer(buffer, 0..buffer_size as u64, &|data, _| {
+        pixels.borrow_mut().extend_from_slice(data)
+    });
+    let pixels = pixels.into_inner();
+    assert_eq!(pixels.len(), buffer_size);
+    assert!(pixels.chunks(4).all(|pixel| pixel == [255, 0, 0, 255]));
+}